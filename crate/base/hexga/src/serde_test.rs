@@ -1,11 +1,12 @@
 use crate::prelude::*;
+use hexga_encoding::diff::Value;
 use hexga_encoding::markup::*;
+use std::path::Path;
 
 fn test_serialize_deserialize_tmp_bin<T>(value: &T)
 where
     T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
 {
-    // Not a self describing format
     let format = value.to_tmp_bin().unwrap();
     let from_format = T::from_tmp_bin_bytes(&format).unwrap();
     assert_eq!(*value, from_format);
@@ -171,3 +172,1424 @@ fn serialize_grid() { serde_test(&Grid2::from_fn(vector([3, 4]), |x| x.sum_axis(
 
 #[test]
 fn serialize_image() { serde_test(&Image::from_fn(vector([3, 4]), |x| RgbaU8::rgb(x.x as _, x.y as _, 0))); }
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+enum Tile
+{
+    Empty,
+    Wall(u8),
+    Door
+    {
+        locked: bool,
+        key: Option<String>,
+    },
+    Portal(i32, i32),
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Room
+{
+    name: String,
+    tiles: Vec<Tile>,
+    spawn: Option<Vector2<i32>>,
+    loot: std::collections::BTreeMap<String, Vec<u64>>,
+    unit: (),
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Level
+{
+    rooms: Vec<Room>,
+    grid: Grid2<Tile>,
+    nested: Option<Option<Box<Level>>>,
+}
+
+fn room(name: &str) -> Room
+{
+    Room {
+        name: name.to_owned(),
+        tiles: vec![
+            Tile::Empty,
+            Tile::Wall(3),
+            Tile::Door {
+                locked: true,
+                key: Some("gold".to_owned()),
+            },
+            Tile::Door { locked: false, key: None },
+            Tile::Portal(-1, 42),
+        ],
+        spawn: Some(vec2i(4, -2)),
+        loot: [("chest".to_owned(), vec![1, 2, 3]), ("".to_owned(), vec![])].into_iter().collect(),
+        unit: (),
+    }
+}
+
+fn level() -> Level
+{
+    Level {
+        rooms: vec![room("entry"), room("boss")],
+        grid: Grid2::from_fn(vector([3, 2]), |p| if p.x == p.y { Tile::Wall(p.x as _) } else { Tile::Empty }),
+        nested: Some(Some(Box::new(Level {
+            rooms: vec![],
+            grid: Grid2::from_fn(vector([1, 1]), |_| Tile::Portal(0, 0)),
+            nested: Some(None),
+        }))),
+    }
+}
+
+fn test_save_load<T>(value: &T, extension: &str)
+where
+    T: Save + Load + PartialEq + Debug,
+{
+    let (bytes, saved_extension) = value.save_to_bytes(Some(extension)).unwrap();
+    assert_eq!(saved_extension.as_deref(), Some(extension));
+    let loaded = T::load_from_bytes(&bytes, Some(extension)).unwrap();
+    assert_eq!(*value, loaded);
+}
+
+fn test_save_load_tmp_bin<T>(value: &T)
+where
+    T: Save + Load + PartialEq + Debug,
+{
+    test_save_load(value, Extension::TMP_BIN);
+}
+
+#[test]
+fn tmp_bin_nested()
+{
+    serde_test(&room("room"));
+    // Json can't distinguish `Some(None)` from `None`
+    test_serialize_deserialize_tmp_bin(&level());
+    test_serialize_deserialize_ron(&level());
+    test_save_load_tmp_bin(&room("room"));
+    test_save_load_tmp_bin(&level());
+}
+
+#[test]
+fn tmp_bin_enum()
+{
+    for tile in room("room").tiles
+    {
+        serde_test(&tile);
+        test_save_load_tmp_bin(&tile);
+    }
+}
+
+#[test]
+fn tmp_bin_option()
+{
+    serde_test(&Option::<u8>::None);
+    serde_test(&Some(5u8));
+    test_serialize_deserialize_tmp_bin(&Some(Some(())));
+    test_serialize_deserialize_tmp_bin(&Some(Option::<()>::None));
+    test_save_load_tmp_bin(&Some(vec![None, Some(-3i64)]));
+}
+
+#[test]
+fn tmp_bin_map()
+{
+    let map: std::collections::HashMap<(i32, bool), Vec<Tile>> = [((0, true), vec![Tile::Empty]), ((-5, false), vec![])].into_iter().collect();
+    // Json only support string keys
+    test_serialize_deserialize_tmp_bin(&map);
+    test_serialize_deserialize_ron(&map);
+    test_save_load_tmp_bin(&map);
+}
+
+#[test]
+fn tmp_bin_grid()
+{
+    test_serialize_deserialize_tmp_bin(&Grid2::from_fn(vector([3, 4]), |x| x.sum_axis()));
+    test_serialize_deserialize_tmp_bin(&Grid3::from_fn(vector([2, 3, 4]), |x| x.x as f32 * 0.5));
+    test_save_load_tmp_bin(&Image::from_fn(vector([3, 4]), |x| RgbaU8::rgb(x.x as _, x.y as _, 0)));
+}
+
+#[test]
+fn tmp_bin_scalar()
+{
+    test_serialize_deserialize_tmp_bin(&i128::MIN);
+    test_serialize_deserialize_tmp_bin(&u128::MAX);
+    serde_test(&f64::MIN_POSITIVE);
+    serde_test(&'\u{1F980}');
+    serde_test(&true);
+    serde_test(&"x".repeat(300));
+    serde_test(&vec![0u8; 200]);
+}
+
+#[test]
+fn tmp_bin_invalid()
+{
+    let bytes = room("room").to_tmp_bin().unwrap();
+
+    assert!(Room::from_tmp_bin_bytes(&[]).is_err());
+    assert!(Room::from_tmp_bin_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Room::from_tmp_bin_bytes(&bytes[1..]).is_err());
+    assert!(Level::from_tmp_bin_bytes(&bytes).is_err());
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(Room::from_tmp_bin_bytes(&trailing).is_err());
+}
+
+fn test_save_load_txt<T>(value: &T, expected: &str)
+where
+    T: Save + Load + PartialEq + Debug,
+{
+    let (bytes, extension) = value.save_to_bytes(Some(Extension::TXT)).unwrap();
+    assert_eq!(extension.as_deref(), Some(Extension::TXT));
+    assert_eq!(str::from_utf8(&bytes).unwrap(), expected);
+    let loaded = T::load_from_bytes(&bytes, Some(Extension::TXT)).unwrap();
+    assert_eq!(*value, loaded);
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone, Copy)]
+enum Difficulty
+{
+    Easy,
+    Hard,
+}
+
+#[test]
+fn txt_scalar()
+{
+    test_save_load_txt(&"hello\nworld".to_owned(), "hello\nworld");
+    test_save_load_txt(&42i32, "42");
+    test_save_load_txt(&-0.5f32, "-0.5");
+    test_save_load_txt(&true, "true");
+    test_save_load_txt(&'x', "x");
+    test_save_load_txt(&Difficulty::Hard, "Hard");
+    test_save_load_txt(&Some(7u8), "7");
+    test_save_load_txt(&Option::<u8>::None, "");
+}
+
+#[test]
+fn txt_lines()
+{
+    test_save_load_txt(&vec!["apple".to_owned(), "".to_owned(), "pear".to_owned()], "apple\n\npear\n");
+    test_save_load_txt(&Vec::<String>::new(), "");
+    test_save_load_txt(&vec![1.5f64, -2., 3.25], "1.5\n-2\n3.25\n");
+    test_save_load_txt(&(1u8, false, Difficulty::Easy), "1\nfalse\nEasy\n");
+    test_save_load_txt(&vec![Some(1), None, Some(3)], "1\n\n3\n");
+
+    // Hand edited: no trailing line break, windows line breaks and extra spaces around numbers
+    assert_eq!(Vec::<u32>::load_from_bytes(b"1\r\n 2 \r\n3", Some(Extension::TXT)).unwrap(), vec![1, 2, 3]);
+    assert_eq!(
+        Vec::<Option<char>>::load_from_bytes(b" x \r\n \r\ny", Some(Extension::TXT)).unwrap(),
+        vec![Some('x'), None, Some('y')]
+    );
+    assert_eq!(Option::<u8>::load_from_bytes(b" \n", Some(Extension::TXT)).unwrap(), None);
+}
+
+#[test]
+fn txt_structural_is_error()
+{
+    assert!(room("room").save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!(vec![vec![1, 2], vec![3]].save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!(vec!["a\nb".to_owned()].save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!(vec!["a\r".to_owned()].save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!(' '.save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!(Tile::Wall(3).save_to_bytes(Some(Extension::TXT)).is_err());
+    assert!([("a".to_owned(), 1)].to_hashmap().save_to_bytes(Some(Extension::TXT)).is_err());
+
+    assert!(Room::load_from_bytes(b"room", Some(Extension::TXT)).is_err());
+    assert!(Vec::<u32>::load_from_bytes(b"1\ntwo\n3", Some(Extension::TXT)).is_err());
+    assert!(Tile::load_from_bytes(b"Wall", Some(Extension::TXT)).is_err());
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Config
+{
+    title: String,
+    volume: f32,
+    fullscreen: bool,
+    resolution: Vector2<i32>,
+    difficulty: Difficulty,
+    last_level: Option<String>,
+    keys: std::collections::BTreeMap<String, String>,
+}
+
+fn config() -> Config
+{
+    Config {
+        title: "hexga".to_owned(),
+        volume: 0.75,
+        fullscreen: false,
+        resolution: vec2i(1920, 1080),
+        difficulty: Difficulty::Hard,
+        last_level: None,
+        keys: [("jump".to_owned(), "space".to_owned()), ("fire".to_owned(), "ctrl".to_owned())]
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[test]
+fn toml_config()
+{
+    test_save_load(&config(), Extension::TOML);
+    test_save_load(
+        &Config {
+            last_level: Some("boss".to_owned()),
+            ..config()
+        },
+        Extension::TOML,
+    );
+
+    let cfg = Config::from_toml(
+        r#"
+        title = "hand written"
+        volume = 1.0
+        fullscreen = true
+        resolution = [800, 600]
+        difficulty = "Easy"
+
+        [keys]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(cfg.resolution, vec2i(800, 600));
+    assert_eq!(cfg.last_level, None);
+
+    // The root of a toml document is always a table
+    assert!(42.save_to_bytes(Some(Extension::TOML)).is_err());
+}
+
+#[test]
+fn yaml_nested()
+{
+    test_save_load(&config(), Extension::YAML);
+    test_save_load(&room("room"), Extension::YAML);
+    test_save_load(&room("room"), Extension::YML);
+    for tile in room("room").tiles
+    {
+        test_save_load(&tile, Extension::YAML);
+    }
+    assert_eq!(Vec::<u32>::from_yaml("- 1\n- 2\n").unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn msgpack_nested()
+{
+    test_save_load(&config(), Extension::MSGPACK);
+    test_save_load(&room("room"), Extension::MSGPACK);
+    let grid = Grid2::from_fn(vector([3, 4]), |x| x.sum_axis());
+    assert_eq!(Grid2::from_msgpack_bytes(&grid.to_msgpack().unwrap()).unwrap(), grid);
+    test_save_load(&Image::from_fn(vector([3, 4]), |x| RgbaU8::rgb(x.x as _, x.y as _, 0)), Extension::MSGPACK);
+    test_save_load(&i64::MIN, Extension::MSGPACK);
+
+    let map: std::collections::HashMap<(i32, bool), Vec<Tile>> = [((0, true), vec![Tile::Empty]), ((-5, false), vec![])].into_iter().collect();
+    test_save_load(&map, Extension::MSGPACK);
+
+    // Readable by other MessagePack tools: the fields are named
+    let bytes = config().to_msgpack().unwrap();
+    assert!(bytes.windows(b"volume".len()).any(|w| w == b"volume"));
+}
+
+#[test]
+fn format_from_extension()
+{
+    assert_eq!(AnyFormat::try_from(Extension::TOML), Ok(AnyFormat::Markup(FormatMarkup::Toml)));
+    assert_eq!(AnyFormat::try_from(Extension::YAML), Ok(AnyFormat::Markup(FormatMarkup::Yaml)));
+    assert_eq!(AnyFormat::try_from(Extension::YML), Ok(AnyFormat::Markup(FormatMarkup::Yaml)));
+    assert_eq!(AnyFormat::try_from(Extension::MSGPACK), Ok(AnyFormat::Special(FormatSpecial::MsgPack)));
+    for format in AnyFormat::ALL
+    {
+        assert_eq!(AnyFormat::try_from(format.extension()), Ok(*format));
+    }
+}
+
+#[test]
+fn compression_extension()
+{
+    for extension in ["ron.gz", "json.zst", "tmp.deflate", "yaml.gz.zst"]
+    {
+        test_save_load(&room("room"), extension);
+    }
+
+    let room = room("room");
+    let (compressed, extension) = room.save_to_bytes(Some("ron.gz")).unwrap();
+    assert_eq!(extension.as_deref(), Some("ron.gz"));
+    assert!(compressed.len() < room.to_ron().unwrap().len());
+
+    // Without inner extension, the default format is used
+    let (_, extension) = room.save_to_bytes(Some(Extension::ZSTD)).unwrap();
+    assert_eq!(extension.as_deref(), Some("ron.zst"));
+
+    // Not compressed
+    assert!(Room::load_from_bytes(room.to_ron().unwrap().as_bytes(), Some("ron.gz")).is_err());
+}
+
+#[test]
+fn compression_path()
+{
+    assert_eq!(Path::new("world.ron.gz").full_extension(), Some("ron.gz"));
+    assert_eq!(Path::new("dir.v2/my.world.json.gz.zst").full_extension(), Some("json.gz.zst"));
+    assert_eq!(Path::new("world.ron").full_extension(), Some("ron"));
+    assert_eq!(Path::new("world.gz").full_extension(), Some("gz"));
+    assert_eq!(Path::new("world").full_extension(), None);
+    assert_eq!(Path::new("dir/world.ron.gz").with_full_extension("json.zst"), Path::new("dir/world.json.zst"));
+    assert_eq!(Path::new("world").with_full_extension("ron.gz"), Path::new("world.ron.gz"));
+
+    let dir = std::env::temp_dir().join(format!("hexga_compression_path_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let room = room("room");
+    room.save(dir.join("room.ron.gz")).unwrap();
+    assert_eq!(Room::load(dir.join("room.ron.gz")).unwrap(), room);
+    // Probing the load extensions also try the compressed variants
+    assert_eq!(Room::load(dir.join("room")).unwrap(), room);
+
+    // The extension is completed
+    room.save(dir.join("other.zst")).unwrap();
+    assert_eq!(Room::load(dir.join("other.ron.zst")).unwrap(), room);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn extension_args()
+{
+    let room = room("room");
+
+    let (compact, extension) = room.save_to_bytes(Some("compact.ron")).unwrap();
+    assert_eq!(extension.as_deref(), Some("compact.ron"));
+    assert!(!compact.iter().any(|b| *b == b'\n'));
+    assert_eq!(Room::load_from_bytes(&compact, Some("compact.ron")).unwrap(), room);
+
+    let (pretty, _) = room.save_to_bytes(Some("pretty.json")).unwrap();
+    assert!(pretty.iter().any(|b| *b == b'\n'));
+    test_save_load(&room, "compact.json");
+    test_save_load(&room, "compact.ron.gz");
+
+    // Unknown format, the default one is used
+    let (_, extension) = room.save_to_bytes(Some("compact.unknown")).unwrap();
+    assert_eq!(extension.as_deref(), Some("compact.ron"));
+
+    assert!(matches!(room.save_to_bytes(Some("flat.ron")), Err(EncodeError::UnsupportedExtensionArg { .. })));
+    assert!(matches!(
+        Room::load_from_bytes(&compact, Some("flat.ron")),
+        Err(EncodeError::UnsupportedExtensionArg { .. })
+    ));
+
+    // Custom extension arguments
+    let image = Image::from_fn(vector([16, 16]), |x| RgbaU8::rgb(x.x as _, x.y as _, 0));
+    for extension in ["png", "fast.png", "best.png"]
+    {
+        let (bytes, _) = image.save_to_bytes(Some(extension)).unwrap();
+        assert_eq!(Image::load_from_bytes(&bytes, Some(extension)).unwrap(), image);
+    }
+    assert!(image.save_to_bytes(Some("compact.best.png")).is_ok());
+}
+
+#[test]
+fn extension_args_path()
+{
+    let is_arg = |arg: &str| ExtensionArgs::COMMON.contains(&arg);
+    assert_eq!(Path::new("my.level.compact.ron.gz").full_extension_with_args(is_arg), Some("compact.ron.gz"));
+    assert_eq!(Path::new("my.level.ron").full_extension_with_args(is_arg), Some("ron"));
+    assert_eq!(
+        Path::new("my.level.compact.ron").with_full_extension_with_args("json", is_arg),
+        Path::new("my.level.json")
+    );
+
+    let dir = std::env::temp_dir().join(format!("hexga_extension_args_path_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let room = room("room");
+    room.save(dir.join("my.room.compact.json")).unwrap();
+    assert!(!std::fs::read(dir.join("my.room.compact.json")).unwrap().iter().any(|b| *b == b'\n'));
+    assert_eq!(Room::load(dir.join("my.room.compact.json")).unwrap(), room);
+
+    // `room` is not an argument, it's part of the name
+    room.save(dir.join("my.room.ron")).unwrap();
+    assert_eq!(Room::load(dir.join("my.room.ron")).unwrap(), room);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn grid_extension_args()
+{
+    let grid = Grid2::from_fn(vector([3, 2]), |p| p.x + p.y * 10);
+    let (nested, _) = grid.save_to_bytes(Some("nested.compact.ron")).unwrap();
+    assert_eq!(str::from_utf8(&nested).unwrap(), "(size:(3,2),values:[[0,1,2],[10,11,12]])");
+    let (flat, _) = grid.save_to_bytes(Some("flat.compact.ron")).unwrap();
+    assert_eq!(flat, grid.save_to_bytes(Some("compact.ron")).unwrap().0);
+
+    for extension in ["nested.ron", "nested.json", "nested.msgpack", "nested.tmp", "flat.ron", "ron"]
+    {
+        test_save_load(&grid, extension);
+        test_save_load(&Grid3::from_fn(vector([2, 3, 4]), |p| p.x - p.y * p.z), extension);
+        test_save_load(&Grid2::<i32>::from_fn(vector([3, 0]), |_| 0), extension);
+        test_save_load(&Grid2::<i32>::from_fn(vector([0, 3]), |_| 0), extension);
+    }
+
+    assert_eq!(Grid2::<i32>::load_from_bytes_borrowed(&nested, Some("nested.ron")).unwrap(), grid);
+    assert_eq!(Grid2::<i32>::load_from_reader(nested.as_slice(), Some("nested.ron")).unwrap(), grid);
+    // Wrong format, but the arguments are kept
+    let (nested_json, _) = grid.save_to_bytes(Some("nested.json")).unwrap();
+    assert_eq!(Grid2::<i32>::load_from_bytes(&nested_json, Some("nested.ron")).unwrap(), grid);
+    assert!(matches!(
+        Grid2::<i32>::load_from_reader(nested.as_slice(), Some("diagonal.ron")),
+        Err(EncodeError::UnsupportedExtensionArg { .. })
+    ));
+
+    // The layout is not guessed
+    assert!(Grid2::<i32>::load_from_bytes(&nested, Some("ron")).is_err());
+    assert!(Grid2::<i32>::load_from_bytes(&flat, Some("nested.ron")).is_err());
+    // The rows have different lengths, or don't match the size
+    assert!(Grid2::<i32>::load_from_bytes(b"(size:(3,2),values:[[0,1,2],[10,11]])", Some("nested.ron")).is_err());
+    assert!(Grid2::<i32>::load_from_bytes(b"(size:(2,3),values:[[0,1,2],[10,11,12]])", Some("nested.ron")).is_err());
+}
+
+#[test]
+fn decode_limits()
+{
+    let nested = vec![vec![vec![1, 2], vec![3]], vec![]];
+    let limits = DecodeLimits::UNLIMITED;
+    for format in AnyFormat::ALL.iter().filter(|f| {
+        !matches!(
+            f,
+            AnyFormat::Markup(FormatMarkup::Xml | FormatMarkup::Toml) | AnyFormat::Special(FormatSpecial::Txt)
+        )
+    })
+    {
+        let bytes = format.encode(&nested).unwrap();
+        assert_eq!(format.from_bytes_with_limits::<Vec<Vec<Vec<i32>>>>(&bytes, limits).unwrap(), nested);
+        assert_eq!(
+            format
+                .from_bytes_with_limits::<Vec<Vec<Vec<i32>>>>(&bytes, limits.with_max_depth(3).with_max_len(2))
+                .unwrap(),
+            nested
+        );
+
+        assert_eq!(
+            format.from_bytes_with_limits::<Vec<Vec<Vec<i32>>>>(&bytes, limits.with_max_depth(2)),
+            Err(EncodeError::LimitExceeded {
+                limit: DecodeLimit::Depth,
+                max: 2
+            }),
+            "{format}"
+        );
+        assert_eq!(
+            format.from_bytes_with_limits::<Vec<Vec<Vec<i32>>>>(&bytes, limits.with_max_len(1)),
+            Err(EncodeError::LimitExceeded {
+                limit: DecodeLimit::Len,
+                max: 1
+            }),
+            "{format}"
+        );
+        assert_eq!(
+            format.from_bytes_with_limits::<Vec<Vec<Vec<i32>>>>(&bytes, limits.with_max_bytes(bytes.len() - 1)),
+            Err(EncodeError::LimitExceeded {
+                limit: DecodeLimit::Bytes,
+                max: bytes.len() - 1
+            }),
+            "{format}"
+        );
+    }
+
+    let lines = vec![1, 2, 3];
+    let txt = AnyFormat::Special(FormatSpecial::Txt);
+    let bytes = txt.encode(&lines).unwrap();
+    assert_eq!(txt.from_bytes_with_limits::<Vec<i32>>(&bytes, limits.with_max_len(3)).unwrap(), lines);
+    assert!(matches!(
+        txt.from_bytes_with_limits::<Vec<i32>>(&bytes, limits.with_max_len(2)),
+        Err(EncodeError::LimitExceeded { limit: DecodeLimit::Len, .. })
+    ));
+
+    // Deeply nested untrusted input
+    let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+    assert!(matches!(
+        Vec::<Vec<Vec<i32>>>::load_from_bytes(deep.as_bytes(), Some(Extension::RON)),
+        Err(EncodeError::Markup { .. } | EncodeError::LimitExceeded { .. })
+    ));
+
+    // Also apply on the decompressed bytes
+    let room = room("room");
+    let (compressed, _) = room.save_to_bytes(Some("ron.gz")).unwrap();
+    let size = room.to_ron().unwrap().len();
+    assert_eq!(
+        Room::load_from_bytes_with_limits(&compressed, Some("ron.gz"), limits.with_max_bytes(size)).unwrap(),
+        room
+    );
+    assert!(matches!(
+        Room::load_from_bytes_with_limits(&compressed, Some("ron.gz"), limits.with_max_bytes(size - 1)),
+        Err(EncodeError::LimitExceeded { limit: DecodeLimit::Bytes, .. })
+    ));
+
+    // And on the custom extensions
+    let image = Image::from_fn(vector([16, 16]), |x| RgbaU8::rgb(x.x as _, x.y as _, 0));
+    let (png, _) = image.save_to_bytes(Some("png")).unwrap();
+    assert_eq!(
+        Image::load_from_bytes_with_limits(&png, Some("png"), limits.with_max_bytes(png.len())).unwrap(),
+        image
+    );
+    assert!(matches!(
+        Image::load_from_bytes_with_limits(&png, Some("png"), limits.with_max_bytes(png.len() / 2)),
+        Err(EncodeError::LimitExceeded { limit: DecodeLimit::Bytes, .. })
+    ));
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(version = 1, name = "Profile")]
+struct ProfileV1
+{
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(version = 2, name = "Profile")]
+struct ProfileV2
+{
+    name: String,
+    level: u32,
+}
+impl Migrate for ProfileV2
+{
+    type Previous = ProfileV1;
+    fn migrate(previous: Self::Previous) -> Self { Self { name: previous.name, level: 1 } }
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(version = 3)]
+struct Profile
+{
+    name: String,
+    level: u32,
+    title: Option<String>,
+}
+impl Migrate for Profile
+{
+    type Previous = ProfileV2;
+    fn migrate(previous: Self::Previous) -> Self
+    {
+        Self {
+            name: previous.name,
+            level: previous.level,
+            title: None,
+        }
+    }
+}
+
+#[test]
+fn versioned_migrate()
+{
+    let profile = Profile {
+        name: "hexga".to_owned(),
+        level: 5,
+        title: Some("admin".to_owned()),
+    };
+
+    for extension in ["ron", "json", "toml", "yaml", "tmp", "msgpack"]
+    {
+        test_save_load(&profile, extension);
+
+        let (v1, _) = ProfileV1 { name: "old".to_owned() }.save_to_bytes(Some(extension)).unwrap();
+        assert_eq!(
+            Profile::load_from_bytes(&v1, Some(extension)).unwrap(),
+            Profile {
+                name: "old".to_owned(),
+                level: 1,
+                title: None
+            },
+            "{extension}"
+        );
+
+        let (v2, _) = ProfileV2 {
+            name: "old".to_owned(),
+            level: 3,
+        }
+        .save_to_bytes(Some(extension))
+        .unwrap();
+        assert_eq!(Profile::load_from_bytes(&v2, Some(extension)).unwrap().level, 3, "{extension}");
+
+        // Can't go back to an older version
+        let (v3, _) = profile.save_to_bytes(Some(extension)).unwrap();
+        assert!(ProfileV2::load_from_bytes(&v3, Some(extension)).is_err(), "{extension}");
+    }
+
+    // The header is written
+    let ron = String::from_utf8(profile.save_to_bytes(Some(Extension::RON)).unwrap().0).unwrap();
+    assert!(str::contains(&ron, "type: \"Profile\""), "{ron}");
+    assert!(str::contains(&ron, "version: 3"), "{ron}");
+
+    // Saves of other types are rejected
+    let (room, _) = room("room").save_to_bytes(Some(Extension::RON)).unwrap();
+    assert!(Profile::load_from_bytes(&room, Some(Extension::RON)).is_err());
+}
+
+/// Saved as a versioned [`Profile`]
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(save_as = Profile, load_from = Profile)]
+struct PlayerName(String);
+
+impl From<&PlayerName> for Profile
+{
+    fn from(value: &PlayerName) -> Self
+    {
+        Self {
+            name: value.0.clone(),
+            level: 1,
+            title: None,
+        }
+    }
+}
+impl From<Profile> for PlayerName
+{
+    fn from(value: Profile) -> Self { Self(value.name) }
+}
+
+#[test]
+fn versioned_conversion()
+{
+    let name = PlayerName("hexga".to_owned());
+    for extension in ["ron", "json", "tmp", "msgpack"]
+    {
+        test_save_load(&name, extension);
+
+        let (bytes, _) = name.save_to_bytes(Some(extension)).unwrap();
+        assert_eq!(Profile::load_from_bytes(&bytes, Some(extension)).unwrap(), Profile::from(&name), "{extension}");
+        assert_eq!(PlayerName::load_from_reader(bytes.as_slice(), Some(extension)).unwrap(), name, "{extension}");
+
+        // Migrated by the source
+        let (v1, _) = ProfileV1 { name: "old".to_owned() }.save_to_bytes(Some(extension)).unwrap();
+        assert_eq!(
+            PlayerName::load_from_bytes(&v1, Some(extension)).unwrap(),
+            PlayerName("old".to_owned()),
+            "{extension}"
+        );
+    }
+    let ron = String::from_utf8(name.save_to_bytes(Some(Extension::RON)).unwrap().0).unwrap();
+    assert!(str::contains(&ron, "version: 3"), "{ron}");
+}
+
+#[io(version = 1)]
+#[derive(PartialEq, Debug)]
+struct Settings
+{
+    volume: u8,
+}
+
+#[test]
+fn versioned_io_attribute()
+{
+    test_save_load(&Settings { volume: 7 }, Extension::JSON);
+    assert!(Settings::load_from_bytes(br#"{"volume":7}"#, Some(Extension::JSON)).is_err());
+    assert_eq!(
+        Settings::load_from_bytes(br#"{"type":"Settings","version":1,"value":{"volume":7}}"#, Some(Extension::JSON)).unwrap(),
+        Settings { volume: 7 }
+    );
+
+    // The header fields are required, once
+    for json in [
+        r#"{"version":1,"value":{"volume":7}}"#,
+        r#"{"type":"Settings","type":"Settings","version":1,"value":{"volume":7}}"#,
+        r#"{"type":"Settings","version":1,"version":1,"value":{"volume":7}}"#,
+        r#"{"type":"Settings","version":1,"value":{"volume":7},"value":{"volume":7}}"#,
+    ]
+    {
+        assert!(Settings::load_from_bytes(json.as_bytes(), Some(Extension::JSON)).is_err(), "{json}");
+    }
+    let err = Settings::load_from_bytes(br#"{"version":1,"value":{"volume":7}}"#, Some(Extension::JSON)).unwrap_err();
+    assert!(str::contains(&err.to_string(), "missing field `type`"), "{err}");
+}
+
+/// `lvl` format: the name on the first line, then one row of tiles per line
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(extensions = ["lvl", "level"], args = ["flat"], save_with = save_level_map, load_with = load_level_map)]
+struct LevelMap
+{
+    name: String,
+    rows: Vec<String>,
+}
+
+fn save_level_map<W: std::io::Write>(map: &LevelMap, mut writer: W, _extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult
+{
+    writeln!(writer, "{}", map.name)?;
+    let separator = if args.contains("flat") { "" } else { "\n" };
+    write!(writer, "{}", map.rows.join(separator))?;
+    Ok(())
+}
+
+fn load_level_map<R: std::io::Read>(mut reader: R, _extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult<LevelMap>
+{
+    let mut txt = String::new();
+    reader.read_to_string(&mut txt)?;
+    let mut lines = txt.lines();
+    let name = lines.next().ok_or(EncodeError::custom("missing level name"))?.to_owned();
+    let rows = match args.contains("flat")
+    {
+        true => lines
+            .next()
+            .unwrap_or_default()
+            .as_bytes()
+            .chunks(4)
+            .map(|row| String::from_utf8_lossy(row).into_owned())
+            .collect(),
+        false => lines.map(|row| row.to_owned()).collect(),
+    };
+    Ok(LevelMap { name, rows })
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+#[io(save_as = LevelMap, load_from = LevelMap)]
+struct MapName(String);
+
+impl From<&MapName> for LevelMap
+{
+    fn from(value: &MapName) -> Self
+    {
+        Self {
+            name: value.0.clone(),
+            rows: vec![],
+        }
+    }
+}
+impl From<LevelMap> for MapName
+{
+    fn from(value: LevelMap) -> Self { Self(value.name) }
+}
+
+#[test]
+fn derive_custom_extension()
+{
+    let map = LevelMap {
+        name: "intro".to_owned(),
+        rows: vec!["#..#".to_owned(), "#..#".to_owned()],
+    };
+    assert!(LevelMap::save_extensions().any(|e| e == "lvl"));
+    assert!(LevelMap::load_extensions().any(|e| e == "level"));
+
+    let (bytes, _) = map.save_to_bytes(Some("lvl")).unwrap();
+    assert_eq!(bytes, b"intro\n#..#\n#..#");
+    assert_eq!(LevelMap::load_from_bytes(&bytes, Some("level")).unwrap(), map);
+
+    let (flat, _) = map.save_to_bytes(Some("flat.lvl")).unwrap();
+    assert_eq!(flat, b"intro\n#..##..#");
+    assert_eq!(LevelMap::load_from_bytes(&flat, Some("flat.lvl")).unwrap(), map);
+
+    // The serde formats are still available
+    test_save_load(&map, Extension::RON);
+
+    // Conversion types
+    let name = MapName("intro".to_owned());
+    let (bytes, _) = name.save_to_bytes(Some("lvl")).unwrap();
+    assert_eq!(bytes, b"intro\n");
+    assert_eq!(MapName::load_from_bytes(&bytes, Some("lvl")).unwrap(), name);
+}
+
+#[test]
+fn sniff_format()
+{
+    let room = room("room");
+    let ron = room.to_ron().unwrap();
+    let json = room.to_json().unwrap();
+    let tmp = room.to_tmp_bin().unwrap();
+
+    assert_eq!(sniff_extension(ron.as_bytes()), Some(Extension::RON));
+    assert_eq!(sniff_extension(b"  (x: 1)"), Some(Extension::RON));
+    assert_eq!(sniff_extension(json.as_bytes()), Some(Extension::JSON));
+    assert_eq!(sniff_extension(b"<room/>"), Some(Extension::XML));
+    assert_eq!(sniff_extension(b"[room]\nname = 1"), Some(Extension::TOML));
+    assert_eq!(sniff_extension(b"name: room"), Some(Extension::YAML));
+    assert_eq!(sniff_extension(&tmp), Some(Extension::TMP_BIN));
+    assert_eq!(sniff_extension(b"\x89PNG\r\n\x1a\n...."), Some(Extension::PNG));
+    assert_eq!(sniff_extension(b"42"), None);
+
+    // Missing extension
+    assert_eq!(Room::load_from_bytes(ron.as_bytes(), None).unwrap(), room);
+    assert_eq!(Room::load_from_bytes(json.as_bytes(), None).unwrap(), room);
+    assert_eq!(Room::load_from_bytes(&tmp, None).unwrap(), room);
+    let (compressed, _) = room.save_to_bytes(Some("json.gz")).unwrap();
+    assert_eq!(Room::load_from_bytes(&compressed, None).unwrap(), room);
+
+    // Wrong extension
+    assert_eq!(Room::load_from_bytes(json.as_bytes(), Some(Extension::RON)).unwrap(), room);
+    assert_eq!(Room::load_from_bytes(&tmp, Some(Extension::JSON)).unwrap(), room);
+
+    // Precise errors
+    assert_eq!(
+        Room::load_from_bytes(ron.as_bytes(), Some("ron.gz")),
+        Err(EncodeError::ExtensionMismatch {
+            extension: "ron.gz".into(),
+            detected: "ron".into()
+        })
+    );
+    assert_eq!(
+        Room::load_from_bytes(b"\x89PNG\r\n\x1a\n....", None),
+        Err(EncodeError::load_unsupported_extension::<Room>(Some("png".into())))
+    );
+
+    // A single red pixel, with a wrong media type
+    let png = "data:image/jpeg;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAEElEQVR4AQEFAPr/AP8AAP8FAAH/+lyI0QAAAABJRU5ErkJggg==";
+    assert_eq!(Image::from_url(png).unwrap().size(), vec2i(1, 1));
+}
+
+#[test]
+fn error_location()
+{
+    let room = room("room");
+    let ron = room.to_ron().unwrap().replacen("locked: true", "locked: 3", 1);
+    let json = room.to_json().unwrap().replacen("\"locked\": true", "\"locked\": 3", 1);
+    let yaml = room.to_yaml().unwrap().replacen("locked: true", "locked: 3", 1);
+
+    for (extension, text, line, column) in [(Extension::RON, ron, 7, 21), (Extension::JSON, json, 10, 19), (Extension::YAML, yaml, 6, 11)]
+    {
+        let err = Room::load_from_bytes(text.as_bytes(), Some(extension)).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.path, "tiles[2].locked");
+        let position = location.position.as_ref().unwrap();
+        assert_eq!((position.line, position.column), (line, column));
+        assert_eq!(&text[position.offset..position.offset + 1], "3");
+        assert!(str::contains(&position.source_line, "locked"));
+        assert!(str::contains(&err.to_string(), &format!(" {line} | {}\n", position.source_line)));
+    }
+
+    // Syntax error
+    let err = Room::load_from_bytes(b"(name: \"a\",\n  tiles: [Wall(3), Wal(2)],", Some(Extension::RON)).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.path, "tiles[1]");
+    assert_eq!(location.position.as_ref().map(|p| (p.line, p.column)), Some((2, 20)));
+
+    // The map keys, the previous ones are not kept in the path
+    let ron = b"{\"a\": {1: [true]}, \"b\": {2: [true], 30: [true, 3]}}";
+    let err = BTreeMap::<String, BTreeMap<u32, Vec<bool>>>::load_from_bytes(ron, Some(Extension::RON)).unwrap_err();
+    assert_eq!(err.location().unwrap().path, "b.30[1]");
+
+    #[cfg(feature = "toml")]
+    {
+        let err = Room::load_from_bytes(b"name = \"a\"\ntiles = 3", Some(Extension::TOML)).unwrap_err();
+        assert_eq!(err.location().unwrap().path, "tiles");
+        assert_eq!(err.location().unwrap().position.as_ref().map(|p| p.offset), Some(19));
+    }
+
+    assert_eq!(
+        SourcePosition::from_offset("a\nbc\u{e9}d".as_bytes(), 6),
+        Some(SourcePosition {
+            line: 2,
+            column: 4,
+            offset: 6,
+            source_line: "bc\u{e9}d".to_owned()
+        })
+    );
+}
+
+#[test]
+fn diff_patch()
+{
+    let old = room("room");
+    let mut new = old.clone();
+    new.tiles[2] = Tile::Door {
+        locked: false,
+        key: Some("gold".to_owned()),
+    };
+    new.tiles.push(Tile::Wall(7));
+    new.loot.remove("");
+    new.loot.insert("barrel".to_owned(), vec![9]);
+
+    let patch = old.diff(&new).unwrap();
+    let mut patched = old.clone();
+    patched.apply_patch(&patch).unwrap();
+    assert_eq!(patched, new);
+    assert!(old.diff(&old).unwrap().is_keep());
+
+    // Element-level changes: only the `locked` field of the door is stored
+    let Patch::Map { changes, removed } = &patch
+    else
+    {
+        panic!("{patch:?}")
+    };
+    assert!(removed.is_empty());
+    let tiles = changes
+        .iter()
+        .find(|(key, _)| *key == Value::String("tiles".to_owned()))
+        .map(|(_, patch)| patch);
+    assert_eq!(
+        tiles,
+        Some(&Patch::Seq {
+            len: 6,
+            changes: vec![
+                (
+                    2,
+                    Patch::Variant(Box::new(Patch::Map {
+                        changes: vec![(Value::String("locked".to_owned()), Patch::Replace(Value::Bool(false)))],
+                        removed: vec![]
+                    }))
+                ),
+                (5, Patch::Replace(Value::Variant("Wall".to_owned(), Box::new(Value::U64(7))))),
+            ]
+        })
+    );
+
+    // The patch can be saved
+    for extension in [Extension::RON, Extension::JSON, Extension::TMP_BIN]
+    {
+        let (bytes, _) = patch.save_to_bytes(Some(extension)).unwrap();
+        let loaded = Patch::load_from_bytes(&bytes, Some(extension)).unwrap();
+        assert_eq!(loaded, patch);
+        let mut patched = old.clone();
+        patched.apply_patch(&loaded).unwrap();
+        assert_eq!(patched, new);
+    }
+
+    // A patch for another value fail
+    let mut other = room("other");
+    other.tiles.clear();
+    assert!(other.apply_patch(&patch).is_err());
+}
+
+#[test]
+fn diff_map_reordered()
+{
+    // Two `HashMap` don't iterate in the same order
+    let old = (0..1000).map(|i| (format!("key{i}"), i)).collect::<std::collections::HashMap<_, _>>();
+    let mut new = (0..1000).map(|i| (format!("key{i}"), i)).collect::<std::collections::HashMap<_, _>>();
+    new.insert("key10".to_owned(), -10);
+    new.remove("key20");
+    new.insert("added".to_owned(), 1);
+
+    let patch = old.diff(&new).unwrap();
+    let Patch::Map { changes, removed } = &patch
+    else
+    {
+        panic!("{patch:?}")
+    };
+    assert_eq!(changes.len(), 2);
+    assert_eq!(removed, &[Value::String("key20".to_owned())]);
+
+    let mut patched = old.clone();
+    patched.apply_patch(&patch).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn diff_grid()
+{
+    let old = Grid2::from_fn(vector([64, 64]), |p| p.x * p.y);
+    let mut new = old.clone();
+    new[vector([10, 20])] = -1;
+
+    let patch = old.diff(&new).unwrap();
+    let (bytes, _) = patch.save_to_bytes(Some(Extension::RON)).unwrap();
+    assert!(bytes.len() < 200, "{}", String::from_utf8_lossy(&bytes));
+
+    let mut patched = old.clone();
+    patched.apply_patch(&patch).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn command_line()
+{
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Command
+    {
+        SetBlock
+        {
+            x: Relative<i32>,
+            y: Relative<i32>,
+            z: Relative<i32>,
+            block: String,
+        },
+        Say
+        {
+            message: String,
+        },
+        Give
+        {
+            item: String,
+            count: Option<u32>,
+        },
+        Fly(bool),
+        Kill(Vec<String>),
+        Day,
+    }
+
+    assert_eq!(
+        Command::from_command("/setblock ~10 ~ 8 grass").unwrap(),
+        Command::SetBlock {
+            x: Relative::offset(10),
+            y: Relative::offset(0),
+            z: Relative::absolute(8),
+            block: "grass".to_owned()
+        }
+    );
+    assert_eq!(
+        Command::from_command("/say \"hello 'world'\"").unwrap(),
+        Command::Say {
+            message: "hello 'world'".to_owned()
+        }
+    );
+    assert_eq!(Command::from_command("say 'a \\' b'").unwrap(), Command::Say { message: "a ' b".to_owned() });
+    assert_eq!(
+        Command::from_command("/give apple").unwrap(),
+        Command::Give {
+            item: "apple".to_owned(),
+            count: None
+        }
+    );
+    assert_eq!(
+        Command::from_command("/give apple 3").unwrap(),
+        Command::Give {
+            item: "apple".to_owned(),
+            count: Some(3)
+        }
+    );
+    assert_eq!(Command::from_command("/fly true").unwrap(), Command::Fly(true));
+    assert_eq!(
+        Command::from_command("/kill bob alice").unwrap(),
+        Command::Kill(vec!["bob".to_owned(), "alice".to_owned()])
+    );
+    assert_eq!(Command::from_command("/day").unwrap(), Command::Day);
+
+    let position = "~-2".parse::<Relative<i32>>().unwrap();
+    assert_eq!(position.resolve(10), 8);
+    assert_eq!(Relative::absolute(3).resolve(10), 3);
+
+    // Errors point at the faulty argument
+    let column = |command: &str| {
+        Command::from_command(command)
+            .unwrap_err()
+            .location()
+            .unwrap()
+            .position
+            .as_ref()
+            .unwrap()
+            .column
+    };
+    assert_eq!(column("/setblock ~1 two 3 stone"), 14);
+    assert_eq!(column("/give apple 3 4"), 15);
+    assert_eq!(column("/jump 4"), 2);
+    assert_eq!(column("/say \"hello"), 6);
+    let err = Command::from_command("/setblock 1 2 3").unwrap_err().to_string();
+    assert!(str::contains(&err, "missing argument `block`"), "{err}");
+
+    // Completions
+    let completions = Command::command_completions("/s");
+    assert_eq!(completions.start, 1);
+    assert_eq!(completions.candidates, ["setblock", "say"]);
+    assert_eq!(Command::command_completions("").candidates.len(), 6);
+    assert_eq!(Command::command_completions("/fly ").candidates, ["false", "true"]);
+    assert_eq!(Command::command_completions("/fly t").candidates, ["true"]);
+
+    let completions = Command::command_completions("/setblock 1 ~");
+    assert_eq!(completions.start, 12);
+    assert_eq!(completions.argument, Some("y"));
+    assert!(completions.candidates.is_empty());
+    assert_eq!(Command::command_completions("/give apple ").argument, Some("count"));
+}
+
+#[test]
+fn checksum_envelope()
+{
+    for extension in ["ron.crc", "json.gz.crc", "tmp.crc.zst", "msgpack.crc"]
+    {
+        test_save_load(&room("room"), extension);
+    }
+
+    let room = room("room");
+    let (bytes, extension) = room.save_to_bytes(Some("ron.crc")).unwrap();
+    assert_eq!(extension.as_deref(), Some("ron.crc"));
+    assert_eq!(sniff_extension(&bytes), Some(Extension::CRC32));
+    assert_eq!(Room::load_from_bytes(&bytes, None).unwrap(), room);
+
+    // Damaged or truncated
+    let mut damaged = bytes.clone();
+    *damaged.last_mut().unwrap() ^= 1;
+    assert!(matches!(Room::load_from_bytes(&damaged, Some("ron.crc")), Err(EncodeError::Corrupted { .. })));
+    assert!(matches!(
+        Room::load_from_bytes(&bytes[..bytes.len() - 3], Some("ron.crc")),
+        Err(EncodeError::Corrupted { .. })
+    ));
+    assert!(matches!(
+        Room::load_from_bytes(&bytes[..6], Some("ron.crc")),
+        Err(EncodeError::Corrupted { .. })
+    ));
+    assert!(matches!(
+        Room::load_from_bytes(room.to_ron().unwrap().as_bytes(), Some("ron.crc")),
+        Err(EncodeError::Corrupted { .. })
+    ));
+
+    // Signed
+    let mut signed = Vec::new();
+    let extension = room.save_to_writer_signed(&mut signed, Some(Extension::JSON), b"secret").unwrap();
+    assert_eq!(extension, "json.hmac");
+    assert_eq!(Room::load_from_bytes_signed(&signed, Some("json.hmac"), b"secret").unwrap(), room);
+    assert_eq!(
+        Room::load_from_reader_signed(signed.as_slice(), Some(Extension::JSON), b"secret").unwrap(),
+        room
+    );
+    assert!(matches!(
+        Room::load_from_bytes_signed(&signed, Some("json.hmac"), b"other"),
+        Err(EncodeError::Corrupted { .. })
+    ));
+    // A crc envelope is not signed
+    assert!(matches!(
+        Room::load_from_bytes_signed(&bytes, Some("ron"), b"secret"),
+        Err(EncodeError::Corrupted { .. })
+    ));
+    // The key is needed
+    assert!(room.save_to_bytes(Some("ron.hmac")).is_err());
+    assert!(Room::load_from_bytes(&signed, Some("json.hmac")).is_err());
+
+    assert_eq!(Path::new("save.ron.gz.crc").full_extension(), Some("ron.gz.crc"));
+    assert_eq!(Path::new("save.json.hmac").full_extension(), Some("json.hmac"));
+}
+
+impl MediaType for Room
+{
+    fn media_type() -> &'static str { "application" }
+}
+
+#[test]
+fn data_url()
+{
+    let room = room("room");
+    for extension in ["ron", "json", "tmp", "yaml.gz", "ron.crc"]
+    {
+        let url = room.to_data_url(extension).unwrap();
+        assert!(url.starts_with(&format!("data:application/{extension};base64,")), "{url}");
+        assert_eq!(Room::from_data_url(&url).unwrap(), room);
+    }
+    // The deduced extension is used
+    assert!(room.to_data_url("unknown").unwrap().starts_with("data:application/ron;base64,"));
+
+    // Percent-encoded, charset and structured syntax suffix
+    let url = "data:application/ld+json;charset=utf-8,%7B%22name%22%3A%22a b%22%2C%22tiles%22%3A%5B%5D%2C%22spawn%22%3Anull%2C%22loot%22%3A%7B%7D%2C%22unit%22%3Anull%7D";
+    let loaded = Room::from_data_url(url).unwrap();
+    assert_eq!(loaded.name, "a b");
+    assert!(loaded.tiles.is_empty());
+
+    assert_eq!(String::from_data_url("data:,Hello%2C%20World%21").unwrap(), "Hello, World!");
+    assert_eq!(String::from_data_url("data:text/plain;charset=iso-8859-1,caf%E9").unwrap(), "café");
+    assert_eq!(String::from_data_url("data:text/plain;charset=UTF-8;base64,Y2Fmw6k=").unwrap(), "café");
+    assert!(String::from_data_url("data:text/plain;charset=utf-16,a").is_err());
+    assert!(String::from_data_url("data:,100%").is_err());
+
+    let meta = hexga_encoding::UrlDataMeta::try_from("data:application/x-yaml;charset=utf-8;name=\"a\";base64,").unwrap();
+    assert_eq!(meta.parameter("name"), Some("a"));
+    assert_eq!(meta.load_extension(), Some("yaml"));
+    assert_eq!(
+        hexga_encoding::UrlDataMeta::try_from("data:application/octet-stream,")
+            .unwrap()
+            .load_extension(),
+        None
+    );
+
+    // The format of an octet-stream is detected
+    let (bytes, _) = room.save_to_bytes(Some(Extension::TMP_BIN)).unwrap();
+    let url = hexga_encoding::ToBase64::to_base64_in(bytes.as_slice(), "data:application/octet-stream;base64,".to_owned());
+    assert_eq!(Room::from_data_url(&url).unwrap(), room);
+
+    // A single red pixel
+    let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAEElEQVR4AQEFAPr/AP8AAP8FAAH/+lyI0QAAAABJRU5ErkJggg==";
+    let image = Image::from_data_url(png).unwrap();
+    assert_eq!(Image::from_data_url(&image.to_data_url("png").unwrap()).unwrap(), image);
+}
+
+#[test]
+fn load_iter()
+{
+    let rooms = vec![room("entry"), room("boss"), room("[\"],")];
+    for extension in ["ron", "json", "tmp", "yaml", "ron.gz"]
+    {
+        let (bytes, _) = rooms.save_to_bytes(Some(extension)).unwrap();
+        let loaded: Vec<Room> = Room::load_iter(bytes.as_slice(), Some(extension)).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(loaded, rooms, "{extension}");
+
+        // The streaming formats don't need to read everything first
+        assert_eq!(Vec::<Room>::load_from_reader(bytes.as_slice(), Some(extension)).unwrap(), rooms, "{extension}");
+    }
+
+    let ron = r##"#![enable(implicit_some)]
+    // Comments, chars and raw strings
+    [ ("a", 'x'), /* ] */ (r#"b")"#, ','), (r"c", ']'), ]"##;
+    let loaded: Vec<(String, Option<char>)> = <(String, Option<char>)>::load_iter(ron.as_bytes(), Some("ron"))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        loaded,
+        [("a".to_owned(), Some('x')), ("b\")".to_owned(), Some(',')), ("c".to_owned(), Some(']'))]
+    );
+    assert_eq!(u8::load_iter("[]".as_bytes(), Some("json")).unwrap().count(), 0);
+
+    // The valid elements before the error are still decoded, then the iterator stop
+    let mut values = u8::load_iter("[1, 2, 300, 4]".as_bytes(), Some("json")).unwrap();
+    assert_eq!(values.next().unwrap().unwrap(), 1);
+    assert_eq!(values.next().unwrap().unwrap(), 2);
+    assert!(values.next().unwrap().is_err());
+    assert!(values.next().is_none());
+
+    let mut values = u8::load_iter("[1, 2".as_bytes(), Some("ron")).unwrap();
+    assert_eq!(values.next().unwrap().unwrap(), 1);
+    assert!(values.next().unwrap().is_err());
+    assert!(u8::load_iter("[1] 2".as_bytes(), Some("json")).unwrap().any(|v| v.is_err()));
+    assert!(u8::load_iter("1".as_bytes(), Some("json")).is_err());
+
+    // Limits
+    let limits = DecodeLimits::DEFAULT.with_max_len(2);
+    for extension in ["ron", "tmp"]
+    {
+        let (bytes, _) = rooms.save_to_bytes(Some(extension)).unwrap();
+        let result: EncodeResult<Vec<Room>> = Room::load_iter_with_limits(bytes.as_slice(), Some(extension), limits).and_then(|v| v.collect());
+        assert!(matches!(result, Err(EncodeError::LimitExceeded { limit: DecodeLimit::Len, .. })), "{extension}");
+    }
+    let (bytes, _) = rooms.save_to_bytes(Some("json")).unwrap();
+    let limits = DecodeLimits::DEFAULT.with_max_bytes(bytes.len() / 2);
+    let result: EncodeResult<Vec<Room>> = Room::load_iter_with_limits(bytes.as_slice(), Some("json"), limits).and_then(|v| v.collect());
+    assert!(matches!(result, Err(EncodeError::LimitExceeded { limit: DecodeLimit::Bytes, .. })));
+    assert!(matches!(
+        Vec::<Room>::load_from_reader_with_limits(bytes.as_slice(), Some("json"), limits),
+        Err(EncodeError::LimitExceeded { limit: DecodeLimit::Bytes, .. })
+    ));
+}
+
+/// Give the bytes one by one, so the tokens are cut between two reads
+struct ByteByByte<'a>(&'a [u8]);
+impl std::io::Read for ByteByByte<'_>
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        match (self.0.split_first(), buf.first_mut())
+        {
+            (Some((first, rest)), Some(b)) =>
+            {
+                *b = *first;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn load_from_reader_ron()
+{
+    for extension in ["ron", "compact.ron"]
+    {
+        let (bytes, _) = level().save_to_bytes(Some(extension)).unwrap();
+        assert_eq!(Level::load_from_reader(ByteByByte(&bytes), Some(extension)).unwrap(), level(), "{extension}");
+    }
+
+    // Without type hints
+    #[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Untyped
+    {
+        Number(i64),
+        Text(String),
+        List(Vec<Untyped>),
+    }
+    let ron = "[1, \"a\", [-2, []], r\"b\"]";
+    assert_eq!(
+        Untyped::load_from_reader(ByteByByte(ron.as_bytes()), Some("ron")).unwrap(),
+        Untyped::load_from_bytes(ron.as_bytes(), Some("ron")).unwrap()
+    );
+
+    let ron = r##"/* a /* nested */ comment */ Room(
+        name: r#"a "raw" string)"#, // comment )
+        tiles: [Empty, Wall(3,), Door(locked: true, key: Some("g\"old"),), Portal(-1, 0x2A)],
+        spawn: None,
+        loot: {"chest": [1, 2_000,], "": []},
+        unit: (),
+    )"##;
+    let room = Room::load_from_reader(ByteByByte(ron.as_bytes()), Some("ron")).unwrap();
+    assert_eq!(room, Room::load_from_bytes(ron.as_bytes(), Some("ron")).unwrap());
+    assert_eq!(room.name, "a \"raw\" string)");
+
+    // The extensions are read with the whole text
+    let ron = "#![enable(implicit_some)]\n(name: \"a\", tiles: [], spawn: (1, 2), loot: {}, unit: ())";
+    assert_eq!(Room::load_from_reader(ron.as_bytes(), Some("ron")).unwrap().spawn, Some(vector2(1, 2)));
+
+    // Errors
+    let err = Room::load_from_reader("(name: \"a\",\n  tiles: [Wall(300)])".as_bytes(), Some("ron")).unwrap_err();
+    assert!(str::contains(&err.to_string(), "at line 2"), "{err}");
+    assert!(Vec::<u8>::load_from_reader("[1, 2] 3".as_bytes(), Some("ron")).is_err());
+    assert!(Vec::<u8>::load_from_reader("[1, 2".as_bytes(), Some("ron")).is_err());
+    assert_eq!(Vec::<u8>::load_from_reader("[1, 2] // end".as_bytes(), Some("ron")).unwrap(), [1, 2]);
+
+    let (bytes, _) = level().save_to_bytes(Some("ron")).unwrap();
+    let limits = DecodeLimits::DEFAULT.with_max_bytes(bytes.len() / 2);
+    assert!(matches!(
+        Level::load_from_reader_with_limits(ByteByByte(&bytes), Some("ron"), limits),
+        Err(EncodeError::LimitExceeded { limit: DecodeLimit::Bytes, .. })
+    ));
+}
+
+/// Serialized as bytes, not as a sequence of `u8`
+#[derive(PartialEq, Debug)]
+struct Blob<'a>(&'a [u8]);
+impl Serialize for Blob<'_>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> { serializer.serialize_bytes(self.0) }
+}
+impl<'de: 'a, 'a> Deserialize<'de> for Blob<'a>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> { <&'de [u8]>::deserialize(deserializer).map(Blob) }
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug)]
+#[io(version = 1)]
+struct Sprite<'a>
+{
+    name: &'a str,
+    #[serde(borrow)]
+    pixels: Blob<'a>,
+}
+
+#[test]
+fn load_borrowed()
+{
+    let pixels: Vec<u8> = (0..=255).collect();
+    let sprite = Sprite {
+        name: "hero",
+        pixels: Blob(&pixels),
+    };
+    for extension in ["tmp", "tmp.crc", "msgpack"]
+    {
+        let (bytes, _) = sprite.save_to_bytes(Some(extension)).unwrap();
+        let loaded = Sprite::load_from_bytes_borrowed(&bytes, Some(extension)).unwrap();
+        assert_eq!(loaded, sprite, "{extension}");
+        // Borrowed from the input, not copied
+        assert!(bytes.as_ptr_range().contains(&loaded.name.as_ptr()), "{extension}");
+        assert!(bytes.as_ptr_range().contains(&loaded.pixels.0.as_ptr()), "{extension}");
+    }
+    let (bytes, _) = sprite.save_to_bytes(Some("tmp.crc")).unwrap();
+    assert_eq!(Sprite::load_from_bytes_borrowed(&bytes, None).unwrap(), sprite);
+    // The versioned envelope is read, the extension arguments are checked
+    let small = Sprite {
+        name: "hero",
+        pixels: Blob(b"ab"),
+    };
+    let (bytes, _) = small.save_to_bytes(Some("compact.ron")).unwrap();
+    assert!(str::contains(str::from_utf8(&bytes).unwrap(), "version:1"));
+    assert_eq!(Sprite::load_from_bytes_borrowed(&bytes, Some("compact.ron")).unwrap(), small);
+    assert!(matches!(
+        Sprite::load_from_bytes_borrowed(&bytes, Some("nested.ron")),
+        Err(EncodeError::UnsupportedExtensionArg { .. })
+    ));
+
+    // The markup borrow the strings without escape sequences
+    let json = r#"["hero", "a\"b"]"#;
+    assert_eq!(
+        <(&str, String)>::load_from_bytes_borrowed(json.as_bytes(), Some("json")).unwrap(),
+        ("hero", "a\"b".to_owned())
+    );
+    assert!(<(&str, &str)>::load_from_bytes_borrowed(json.as_bytes(), Some("json")).is_err());
+    assert_eq!(<&str>::load_from_bytes_borrowed(br#""hero""#, Some("ron")).unwrap(), "hero");
+
+    let (compressed, _) = "hero".save_to_bytes(Some("tmp.gz")).unwrap();
+    assert!(<&str>::load_from_bytes_borrowed(&compressed, Some("tmp.gz")).is_err());
+    assert!(<&str>::load_from_bytes_borrowed(b"hero", Some("png")).is_err());
+
+    // Owned values still load from the borrowed TmpBin reader
+    let (bytes, _) = level().save_to_bytes(Some("tmp")).unwrap();
+    assert_eq!(Level::load_from_bytes_borrowed(&bytes, Some("tmp")).unwrap(), level());
+
+    let path = Path::new("hero.tmp");
+    let (bytes, _) = sprite.save_to_bytes(Some("tmp")).unwrap();
+    assert_eq!(Io.load_borrowed::<_, Sprite>(path, &bytes).unwrap(), sprite);
+}
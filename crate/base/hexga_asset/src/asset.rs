@@ -245,20 +245,7 @@ where
                 {
                     drop(r);
                     let mut w = manager.inner.write().unwrap();
-                    let e = w.values.get_mut(path).unwrap();
-                    match e
-                    {
-                        AssetStorage::Persistant(asset) =>
-                        {
-                            *e = AssetStorage::ReferenceCounted(asset.downgrade());
-                        }
-                        AssetStorage::ReferenceCounted(asset_weak) =>
-                        {
-                            *e = AssetStorage::Persistant(
-                                asset_weak.upgrade().unwrap(), /* The current asset have an arc to the value, so the weak one is still alive */
-                            );
-                        }
-                    }
+                    *w.values.get_mut(path).unwrap() = AssetStorage::new(self, lifetime);
                 }
             }
             AssetPersistance::Generated =>
@@ -333,6 +320,16 @@ where
             AssetPersistance::Generated => None,
         }
     }
+
+    /// The paths read while loading it, see [`DependencyGraph`]
+    pub fn dependencies(&self) -> Vec<PathBuf>
+    {
+        match self.path()
+        {
+            Some(path) => dependency_graph().dependencies(&AssetKey::of::<T>(&path)).map(|path| path.to_owned()).collect(),
+            None => Vec::new(),
+        }
+    }
 }
 impl<T> Asset<T>
 where
@@ -352,6 +349,21 @@ where
         Self::manager().load(path)
     }
 
+    /// Loads an asset in the background.
+    ///
+    /// The asset is returned in the [`AssetState::Loading`] state, then become [`AssetState::Loaded`] or [`AssetState::Error`].
+    /// See [`AssetManager::pending`] and [`AssetManager::wait_all`].
+    ///
+    /// On wasm this is not async: there is no thread and no async file system yet,
+    /// so it block like [`Self::load`] and the asset is already loaded, or failed to, when returned.
+    pub fn load_async<P>(path: P) -> Asset<T>
+    where
+        P: AsRef<Path>,
+        T: Load,
+    {
+        Self::manager().load_async(path)
+    }
+
     /// Loads an asset or creates it if it doesn't exist.
     ///
     /// If the file exist and any error occur while reading it,
@@ -409,20 +421,34 @@ where
     }
 
     /// Hot reload the value, and return the old state.
+    /// The assets depending on it are reloaded too, see [`DependencyGraph`].
     ///
     /// If the asset is not persistant, return Ok.
     pub fn hot_reload(&mut self) -> IoResult<Option<AssetState<T>>>
+    where
+        T: Load,
+    {
+        let old = self.reload_from_source();
+        if let Some(path) = self.path()
+        {
+            ASSET.get().reload_dependents(&[], &[AssetKey::of::<T>(&path)]);
+        }
+        Ok(old)
+    }
+
+    /// Same as [`Self::hot_reload`], without reloading the dependents
+    pub(crate) fn reload_from_source(&self) -> Option<AssetState<T>>
     where
         T: Load,
     {
         if self.is_persistant()
         {
             let value = self.load_from_source();
-            Ok(Some(std::mem::replace(self.state_mut().deref_mut(), value.into())))
+            Some(std::mem::replace(self.state_mut().deref_mut(), value.into()))
         }
         else
         {
-            Ok(None)
+            None
         }
     }
 
@@ -435,7 +461,7 @@ where
     {
         match self.persistance().deref()
         {
-            AssetPersistance::Persistant(path) => T::load(path),
+            AssetPersistance::Persistant(path) => ASSET.get().load_value::<T>(path),
             AssetPersistance::Generated => Err(IoError::new("", EncodeError::NotPersistant)),
         }
     }
@@ -498,7 +524,15 @@ where
         {
             AssetPersistance::Persistant(path) =>
             {
-                Asset::<T>::manager().inner.write().unwrap().values.remove(path);
+                let manager = Asset::<T>::manager();
+                let mut w = manager.inner.write().unwrap();
+                // another asset may have been loaded at this path since
+                if w.values.get(path).is_some_and(|storage| storage.upgrade().is_none())
+                {
+                    w.values.remove(path);
+                }
+                drop(w);
+                ASSET.get().dependencies.write().unwrap().remove(&AssetKey::of::<T>(path));
             }
             AssetPersistance::Generated =>
             {}
@@ -602,9 +636,18 @@ where
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
 pub enum AssetLifetime
 {
+    /// Unloaded when the last [`Asset`] is dropped
     #[default]
     ReferenceCounted,
+    /// Never unloaded
     Persistant,
+    /// Still loaded for this duration once unused, then unloaded by [`AssetManager::collect`].
+    ///
+    /// The drop of the last [`Asset`] is not tracked: the duration start from the last time the asset was seen used,
+    /// when the manager gave an [`Asset`] to it, or when [`AssetManager::collect`] found one. So it is only as precise as the calls to `collect`.
+    ///
+    /// Can be unloaded sooner to stay in the [`AssetManager::memory_budget`].
+    Timed(Duration),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
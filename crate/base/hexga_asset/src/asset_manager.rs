@@ -9,6 +9,7 @@ pub(crate) struct AssetsManagerUntyped
 {
     //path_resolver: PathResolver,
     assets: RwLock<HashMap<TypeId, Arc<DynAnyAsync>>>,
+    pub(crate) dependencies: RwLock<DependencyGraph>,
 }
 
 /*
@@ -30,17 +31,19 @@ impl AssetsManagerUntyped
             let assets_read = self.assets.read().unwrap();
             if let Some(any_manager) = assets_read.get(&type_id)
             {
-                let asset_manager = any_manager.as_any().downcast_ref::<AssetManager<T>>().unwrap();
+                let asset_manager = any_manager.as_ref().as_any().downcast_ref::<AssetManager<T>>().unwrap();
                 return asset_manager.clone();
             }
         }
 
+        // another thread may have created it in the meantime
         let mut assets_write = self.assets.write().unwrap();
-        let manager = AssetManager::<T> {
-            inner: Arc::new(RwLock::new(AssetManagerInner::new())),
-        };
-        assets_write.insert(type_id, Arc::new(manager.clone()));
-        manager
+        let any_manager = assets_write.entry(type_id).or_insert_with(|| {
+            Arc::new(AssetManager::<T> {
+                inner: Arc::new(RwLock::new(AssetManagerInner::new())),
+            })
+        });
+        any_manager.as_ref().as_any().downcast_ref::<AssetManager<T>>().unwrap().clone()
     }
 }
 
@@ -67,7 +70,7 @@ where
         P: AsRef<Path>,
         T: Load,
     {
-        self.load_or_create(&path, || match T::load(&path)
+        self.load_or_create(&path, || match ASSET.get().load_value::<T>(path.as_ref())
         {
             Ok(value) => AssetState::Loaded(value),
             Err(err) => AssetState::Error(err),
@@ -81,6 +84,8 @@ where
         O: Into<AssetInit<T>>,
         T: Load,
     {
+        // a dependency of the asset being loaded, if any
+        depends_on_asset::<T>(path.as_ref());
         let mut need_to_be_loaded = false;
         let asset = self.get_or_generate(&path, || {
             need_to_be_loaded = true;
@@ -100,6 +105,58 @@ where
         asset
     }
 
+    /// Like [`Self::load`], but the file is loaded in the background.
+    /// The asset is returned at once in the [`AssetState::Loading`] state, and [`Asset::get_or_placeholder`] give the [`Self::loading_value`] until it is loaded.
+    ///
+    /// On wasm this is not async and block until the asset is loaded, see [`Asset::load_async`].
+    pub(crate) fn load_async<P>(&self, path: P) -> Asset<T>
+    where
+        P: AsRef<Path>,
+        T: Load,
+    {
+        // a dependency of the asset being loaded, if any
+        depends_on_asset::<T>(path.as_ref());
+        let mut need_to_be_loaded = false;
+        let asset = self.get_or_generate(&path, || {
+            need_to_be_loaded = true;
+            AssetInit::with_state(AssetState::Loading)
+        });
+
+        let need_to_be_loaded = need_to_be_loaded
+            || match asset.state().deref()
+            {
+                AssetState::Error(io_error) => !io_error.kind.is_encoding(),
+                _ => false,
+            };
+        if !need_to_be_loaded
+        {
+            return asset;
+        }
+        *asset.state_mut() = AssetState::Loading;
+
+        let pending = self.inner.read().unwrap().pending.start();
+        // the workers read from the same file system as the caller
+        let fs = Io.file_system();
+        let path = path.as_ref().to_owned();
+        let loading = asset.clone();
+        loader::spawn(move || {
+            let _pending = pending;
+            let state = Io.with_file_system(fs, || ASSET.get().load_value::<T>(&path)).into();
+            *loading.state_mut() = state;
+        });
+        asset
+    }
+
+    /// The number of assets still loading in the background, see [`Asset::load_async`]
+    pub fn pending(&self) -> usize { self.inner.read().unwrap().pending.count() }
+
+    /// Block until all the assets loading in the background are loaded, or failed to load
+    pub fn wait_all(&self)
+    {
+        let pending = self.inner.read().unwrap().pending.clone();
+        pending.wait_all();
+    }
+
     /*
     pub(crate) fn load_or_create<P, F, O>(&self, path: P, init: F) -> Asset<T>
     where
@@ -213,6 +270,13 @@ where
                         return asset.clone();
                     }
                 }
+                AssetStorage::Timed { asset, .. } =>
+                {
+                    let asset = asset.clone();
+                    drop(r);
+                    self.touch(&path);
+                    return asset;
+                }
             }
         }
 
@@ -223,15 +287,33 @@ where
         let AssetInit { state: value, lifetime } = init().into();
         let asset = Asset::_new(value, AssetPersistance::Persistant(path.to_owned()));
 
-        let storage = match lifetime
+        w.values.insert(path, AssetStorage::new(&asset, lifetime));
+        asset
+    }
+
+    /// The asset at `path` if it is loaded, or loading
+    pub fn get<P>(&self, path: P) -> Option<Asset<T>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().with_extension("");
+        let asset = self.inner.read().unwrap().values.get(&path).and_then(|storage| storage.upgrade());
+        if asset.is_some()
         {
-            AssetLifetime::ReferenceCounted => asset.clone().into(),
-            AssetLifetime::Persistant => Asset::downgrade(&asset).into(),
-        };
-        w.values.insert(path, storage);
+            self.touch(&path);
+        }
         asset
     }
 
+    /// The [`AssetLifetime::Timed`] asset at `path` was just used
+    fn touch(&self, path: &Path)
+    {
+        if let Some(AssetStorage::Timed { last_used, .. }) = self.inner.write().unwrap().values.get_mut(path)
+        {
+            *last_used = Instant::now();
+        }
+    }
+
     pub fn all(&self) -> Vec<Asset<T>> { self.inner.read().unwrap().values.values().filter_map(|v| v.upgrade()).collect() }
     pub fn iter(&self) -> AssetIter<T> { AssetIter { inner: self.all().into_iter() } }
 
@@ -258,6 +340,13 @@ where
     pub(crate) values: HashMap<PathBuf, AssetStorage<T>>,
     pub(crate) loading: AssetWeak<T>,
     pub(crate) error: AssetWeak<T>,
+    /// Created by the first [`AssetManager::hot_reload_changed`].
+    /// Locked on its own: the manager is not locked while the files are compared.
+    pub(crate) watcher: Arc<Mutex<Option<Watcher>>>,
+    /// The assets loaded by [`AssetManager::load_async`]
+    pub(crate) pending: Arc<Pending>,
+    pub(crate) memory_budget: Option<usize>,
+    pub(crate) memory_size: fn(&T) -> usize,
 }
 impl<T> AssetManagerInner<T>
 where
@@ -269,6 +358,10 @@ where
             values: ___(),
             loading: ___(),
             error: ___(),
+            watcher: ___(),
+            pending: ___(),
+            memory_budget: None,
+            memory_size: |_| size_of::<T>(),
         }
     }
 }
@@ -280,6 +373,12 @@ where
 {
     Persistant(Asset<T>),
     ReferenceCounted(AssetWeak<T>),
+    Timed
+    {
+        asset: Asset<T>,
+        duration: Duration,
+        last_used: Instant,
+    },
 }
 impl<T> Clone for AssetStorage<T>
 where
@@ -291,6 +390,11 @@ where
         {
             Self::Persistant(arg0) => Self::Persistant(arg0.clone()),
             Self::ReferenceCounted(arg0) => Self::ReferenceCounted(arg0.clone()),
+            Self::Timed { asset, duration, last_used } => Self::Timed {
+                asset: asset.clone(),
+                duration: *duration,
+                last_used: *last_used,
+            },
         }
     }
 }
@@ -298,12 +402,28 @@ impl<T> AssetStorage<T>
 where
     T: Async,
 {
+    /// The manager only keep a weak reference to the [`AssetLifetime::ReferenceCounted`] assets, and a strong one to the others.
+    pub fn new(asset: &Asset<T>, lifetime: AssetLifetime) -> Self
+    {
+        match lifetime
+        {
+            AssetLifetime::ReferenceCounted => asset.downgrade().into(),
+            AssetLifetime::Persistant => asset.clone().into(),
+            AssetLifetime::Timed(duration) => Self::Timed {
+                asset: asset.clone(),
+                duration,
+                last_used: Instant::now(),
+            },
+        }
+    }
+
     pub fn lifetime(&self) -> AssetLifetime
     {
         match self
         {
             AssetStorage::Persistant(_asset) => AssetLifetime::Persistant,
             AssetStorage::ReferenceCounted(_asset_weak) => AssetLifetime::ReferenceCounted,
+            AssetStorage::Timed { duration, .. } => AssetLifetime::Timed(*duration),
         }
     }
 
@@ -311,7 +431,7 @@ where
     {
         match self
         {
-            AssetStorage::Persistant(asset) => Some(asset.clone()),
+            AssetStorage::Persistant(asset) | AssetStorage::Timed { asset, .. } => Some(asset.clone()),
             AssetStorage::ReferenceCounted(asset_weak) => asset_weak.upgrade(),
         }
     }
@@ -320,7 +440,7 @@ where
     {
         match self
         {
-            AssetStorage::Persistant(asset) => asset.downgrade(),
+            AssetStorage::Persistant(asset) | AssetStorage::Timed { asset, .. } => asset.downgrade(),
             AssetStorage::ReferenceCounted(asset_weak) => asset_weak.clone(),
         }
     }
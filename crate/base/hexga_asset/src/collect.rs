@@ -0,0 +1,127 @@
+use super::*;
+
+/// An unused [`AssetLifetime::Timed`] asset, that [`AssetManager::collect`] may unload
+struct Idle<T>
+where
+    T: Async,
+{
+    last_used: Instant,
+    expired: bool,
+    path: PathBuf,
+    asset: Asset<T>,
+}
+
+impl<T> AssetManager<T>
+where
+    T: Async,
+{
+    /// Unload the [`AssetLifetime::Timed`] assets unused for longer than their duration.
+    /// Then, if the [`Self::memory_usage`] is above the [`Self::memory_budget`], unload the least recently used ones that are unused.
+    ///
+    /// An asset is used while an [`Asset`] to it exist. To call regularly, ex: once per frame with `Instant::now()`.
+    /// Return the paths of the unloaded assets, sorted.
+    pub fn collect(&self, now: Instant) -> Vec<PathBuf>
+    {
+        // The state of an asset is never locked while the manager is: `Asset::set_persistance` lock them in the other order
+        let (idle, budget, memory_size) = {
+            let mut w = self.inner.write().unwrap();
+            let mut idle = Vec::new();
+            for (path, storage) in w.values.iter_mut()
+            {
+                let AssetStorage::Timed { asset, duration, last_used } = storage
+                else
+                {
+                    continue;
+                };
+                // only the manager have an `Asset`
+                match asset.strong_count() > 1
+                {
+                    true => *last_used = now.max(*last_used),
+                    false => idle.push(Idle {
+                        last_used: *last_used,
+                        expired: now.saturating_duration_since(*last_used) >= *duration,
+                        path: path.clone(),
+                        asset: asset.clone(),
+                    }),
+                }
+            }
+            (idle, w.memory_budget, w.memory_size)
+        };
+
+        let (mut expired, mut idle): (Vec<_>, Vec<_>) = idle.into_iter().partition(|idle| idle.expired);
+        if let Some(budget) = budget
+        {
+            let mut usage = self.memory_usage();
+            for idle in &expired
+            {
+                usage = usage.saturating_sub(memory_size_of(&idle.asset, memory_size));
+            }
+            // least recently used first
+            idle.sort_by(|a, b| (a.last_used, &a.path).cmp(&(b.last_used, &b.path)));
+            for idle in idle
+            {
+                if usage <= budget
+                {
+                    break;
+                }
+                usage = usage.saturating_sub(memory_size_of(&idle.asset, memory_size));
+                expired.push(idle);
+            }
+        }
+
+        let (mut unloaded, mut paths) = (Vec::new(), Vec::new());
+        {
+            let mut w = self.inner.write().unwrap();
+            for idle in &expired
+            {
+                // still unused: only the manager and `idle` have an `Asset`
+                let unused = match w.values.get(&idle.path)
+                {
+                    Some(AssetStorage::Timed { asset, .. }) => asset.ptr_eq(&idle.asset) && asset.strong_count() == 2,
+                    _ => false,
+                };
+                if unused
+                {
+                    unloaded.extend(w.values.remove(&idle.path));
+                    paths.push(idle.path.clone());
+                }
+            }
+        }
+
+        // unloading an asset lock the manager
+        drop(unloaded);
+        drop(expired);
+        paths.sort();
+        paths
+    }
+
+    /// The memory used by the loaded assets, according to [`Self::set_memory_size`]
+    pub fn memory_usage(&self) -> usize
+    {
+        let memory_size = self.inner.read().unwrap().memory_size;
+        // not locked while reading the assets
+        self.all().iter().map(|asset| memory_size_of(asset, memory_size)).sum()
+    }
+
+    /// Above it, [`Self::collect`] unload the unused [`AssetLifetime::Timed`] assets sooner. `None` by default: no limit.
+    pub fn memory_budget(&self) -> Option<usize> { self.inner.read().unwrap().memory_budget }
+    pub fn set_memory_budget(&self, budget: Option<usize>) -> &Self
+    {
+        self.inner.write().unwrap().memory_budget = budget;
+        self
+    }
+
+    /// How to compute the memory used by an asset, ex: the size of the pixels of a texture. By default, `size_of::<T>()`.
+    pub fn set_memory_size(&self, memory_size: fn(&T) -> usize) -> &Self
+    {
+        self.inner.write().unwrap().memory_size = memory_size;
+        self
+    }
+}
+
+fn memory_size_of<T>(asset: &Asset<T>, memory_size: fn(&T) -> usize) -> usize
+where
+    T: Async,
+{
+    asset.state().as_loaded().map_or(0, memory_size)
+}
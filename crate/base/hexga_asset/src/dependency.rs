@@ -0,0 +1,315 @@
+use super::*;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// An asset of any type, ex: in the [`DependencyGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetKey
+{
+    /// Without extension, like [`Asset::path`]
+    pub path: PathBuf,
+    pub type_name: &'static str,
+    type_id: TypeId,
+}
+
+impl AssetKey
+{
+    pub fn of<T>(path: &Path) -> Self
+    where
+        T: Async,
+    {
+        Self {
+            path: path.with_extension(""),
+            type_name: std::any::type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+        }
+    }
+
+    pub fn is<T>(&self) -> bool
+    where
+        T: Async,
+    {
+        self.type_id == TypeId::of::<T>()
+    }
+}
+
+/// Reload the asset at this path if it is still alive, and return its error
+type Reload = fn(&Path) -> Option<IoResult>;
+
+fn reload<T>(path: &Path) -> Option<IoResult>
+where
+    T: Async + Load,
+{
+    let asset = Asset::<T>::manager().get(path)?;
+    asset.reload_from_source();
+    Some(match asset.state().as_error()
+    {
+        Some(err) => Err(err.clone()),
+        None => Ok(()),
+    })
+}
+
+/// What the assets read while loading: the assets they loaded, and the paths given to [`depends_on`].
+///
+/// The paths have no extension, like [`Asset::path`], ex: `tilesets/forest` for `tilesets/forest.png`.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph
+{
+    assets: BTreeMap<AssetKey, (Dependencies, Reload)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Dependencies
+{
+    /// The assets loaded while loading, with their type: a `Texture` and a `Sound` can have the same path
+    assets: BTreeSet<AssetKey>,
+    /// The files given to [`depends_on`], they are watched with the asset
+    files: BTreeSet<PathBuf>,
+}
+
+impl Dependencies
+{
+    fn is_empty(&self) -> bool { self.assets.is_empty() && self.files.is_empty() }
+
+    fn paths(&self) -> impl Iterator<Item = &Path>
+    {
+        self.assets
+            .iter()
+            .map(|key| key.path.as_path())
+            .chain(self.files.iter().map(|path| path.as_path()))
+    }
+
+    /// The file or one of the sidecars of a dependency
+    fn changed(&self, changed: &Path) -> bool { self.paths().any(|dependency| is_source_of(dependency, changed)) }
+}
+
+impl DependencyGraph
+{
+    pub fn is_empty(&self) -> bool { self.assets.is_empty() }
+
+    /// The assets that have dependencies, with their dependencies
+    pub fn iter(&self) -> impl Iterator<Item = (&AssetKey, impl Iterator<Item = &Path>)>
+    {
+        self.assets.iter().map(|(key, (dependencies, _))| (key, dependencies.paths()))
+    }
+
+    pub fn dependencies(&self, asset: &AssetKey) -> impl Iterator<Item = &Path>
+    {
+        self.assets.get(asset).into_iter().flat_map(|(dependencies, _)| dependencies.paths())
+    }
+
+    /// The assets that directly depend on the file at `path`
+    pub fn dependents<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a AssetKey>
+    {
+        self.assets
+            .iter()
+            .filter(move |(_, (dependencies, _))| dependencies.changed(path))
+            .map(|(key, _)| key)
+    }
+
+    /// The assets that depend on one of the `changed` files or of the `reloaded` assets, directly or not,
+    /// sorted so each asset come after its dependencies.
+    ///
+    /// The assets in a cycle are at the end.
+    pub fn reload_order(&self, changed: &[PathBuf], reloaded: &[AssetKey]) -> Vec<AssetKey>
+    {
+        let mut dependents = HashMap::<&AssetKey, Vec<&AssetKey>>::new();
+        for (key, (dependencies, _)) in &self.assets
+        {
+            for dependency in &dependencies.assets
+            {
+                dependents.entry(dependency).or_default().push(key);
+            }
+        }
+
+        let mut affected = self
+            .assets
+            .iter()
+            .filter(|(_, (dependencies, _))| changed.iter().any(|path| dependencies.changed(path)))
+            .map(|(key, _)| key)
+            .collect::<BTreeSet<_>>();
+        let mut todo = affected.iter().copied().chain(reloaded).collect::<Vec<_>>();
+        while let Some(key) = todo.pop()
+        {
+            for dependent in dependents.get(key).into_iter().flatten()
+            {
+                if affected.insert(dependent)
+                {
+                    todo.push(dependent);
+                }
+            }
+        }
+
+        // Kahn's algorithm, only on the affected assets
+        let mut waiting_for = affected
+            .iter()
+            .map(|key| {
+                let count = self.assets[*key]
+                    .0
+                    .assets
+                    .iter()
+                    .filter(|dependency| *dependency != *key && affected.contains(dependency))
+                    .count();
+                (*key, count)
+            })
+            .collect::<HashMap<_, _>>();
+        let mut ready = affected.iter().copied().filter(|key| waiting_for[key] == 0).collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(affected.len());
+        while let Some(key) = ready.pop_front()
+        {
+            order.push(key.clone());
+            for dependent in dependents.get(key).into_iter().flatten().filter(|dependent| **dependent != key)
+            {
+                if let Some(count) = waiting_for.get_mut(dependent)
+                {
+                    *count -= 1;
+                    if *count == 0
+                    {
+                        ready.push_back(dependent);
+                    }
+                }
+            }
+        }
+        // a cycle
+        if order.len() < affected.len()
+        {
+            order.extend(affected.iter().filter(|key| waiting_for[*key] > 0).map(|key| (*key).clone()));
+        }
+        order
+    }
+
+    /// The files given to [`depends_on`] by the assets of type `T`
+    pub(crate) fn files_of<T>(&self) -> impl Iterator<Item = &Path>
+    where
+        T: Async,
+    {
+        self.assets
+            .iter()
+            .filter(|(key, _)| key.is::<T>())
+            .flat_map(|(_, (dependencies, _))| dependencies.files.iter().map(|path| path.as_path()))
+    }
+
+    fn set(&mut self, key: AssetKey, dependencies: Dependencies, reload: Reload)
+    {
+        match dependencies.is_empty()
+        {
+            true => self.assets.remove(&key),
+            false => self.assets.insert(key, (dependencies, reload)),
+        };
+    }
+
+    pub(crate) fn remove(&mut self, key: &AssetKey) { self.assets.remove(key); }
+}
+
+/// If `changed` is the file of the asset at `path`, or one of its sidecars.
+///
+/// The path of an asset have no extension, ex: `levels/intro` for `levels/intro.ron`,
+/// but not for `levels/intro.d/map.ron` in another directory.
+pub(crate) fn is_source_of(path: &Path, changed: &Path) -> bool
+{
+    if path == changed
+    {
+        return true;
+    }
+    match (path.file_name().and_then(|n| n.to_str()), changed.file_name().and_then(|n| n.to_str()))
+    {
+        (Some(name), Some(changed_name)) => changed.parent() == path.parent() && changed_name.strip_prefix(name).is_some_and(|rest| rest.starts_with('.')),
+        _ => false,
+    }
+}
+
+thread_local! {
+    /// The dependencies of the assets being loaded on this thread, the last one is the innermost
+    static LOADING: RefCell<Vec<Dependencies>> = const { RefCell::new(Vec::new()) };
+}
+
+fn add_dependency(f: impl FnOnce(&mut Dependencies))
+{
+    LOADING.with_borrow_mut(|loading| {
+        if let Some(dependencies) = loading.last_mut()
+        {
+            f(dependencies);
+        }
+    });
+}
+
+/// Declare that the asset being loaded depends on `path`, so it is reloaded after it when hot reloading.
+///
+/// To call from a [`Load`] implementation reading other files, they are watched by [`AssetManager::hot_reload_changed`] with the asset.
+/// The [`Asset`] loaded while loading are already dependencies.
+///
+/// Does nothing if no asset is being loaded.
+pub fn depends_on<P>(path: P)
+where
+    P: AsRef<Path>,
+{
+    add_dependency(|dependencies| {
+        dependencies.files.insert(path.as_ref().with_extension(""));
+    });
+}
+
+/// The asset at `path` is loaded, maybe by another asset
+pub(crate) fn depends_on_asset<T>(path: &Path)
+where
+    T: Async,
+{
+    add_dependency(|dependencies| {
+        dependencies.assets.insert(AssetKey::of::<T>(path));
+    });
+}
+
+/// A snapshot of the dependencies between the assets, for debugging
+pub fn dependency_graph() -> DependencyGraph { ASSET.get().dependencies.read().unwrap().clone() }
+
+impl AssetsManagerUntyped
+{
+    /// Load the value of the asset at `path`, and remember what it depends on
+    pub(crate) fn load_value<T>(&self, path: &Path) -> IoResult<T>
+    where
+        T: Async + Load,
+    {
+        /// Even if the loading panic
+        struct Pop;
+        impl Drop for Pop
+        {
+            fn drop(&mut self) { LOADING.with_borrow_mut(|loading| loading.pop()); }
+        }
+
+        LOADING.with_borrow_mut(|loading| loading.push(Dependencies::default()));
+        let pop = Pop;
+        let value = T::load(path);
+        let mut dependencies = LOADING.with_borrow_mut(|loading| std::mem::take(loading.last_mut().unwrap()));
+        drop(pop);
+
+        let key = AssetKey::of::<T>(path);
+        dependencies.assets.remove(&key);
+        dependencies.files.remove(&key.path);
+        self.dependencies.write().unwrap().set(key, dependencies, reload::<T>);
+        value
+    }
+
+    /// Reload the assets depending on the `changed` files or the already `reloaded` assets, after their own dependencies.
+    ///
+    /// Return the reloaded assets, except the already `reloaded` ones, and the errors.
+    pub(crate) fn reload_dependents(&self, changed: &[PathBuf], already_reloaded: &[AssetKey]) -> (Vec<AssetKey>, Vec<IoError>)
+    {
+        let order = self.dependencies.read().unwrap().reload_order(changed, already_reloaded);
+        let (mut reloaded, mut errors) = (Vec::new(), Vec::new());
+        for key in order.into_iter().filter(|key| !already_reloaded.contains(key))
+        {
+            // not locked while reloading, the dependencies of the asset are updated
+            let Some(reload) = self.dependencies.read().unwrap().assets.get(&key).map(|(_, reload)| *reload)
+            else
+            {
+                continue;
+            };
+            match reload(&key.path)
+            {
+                Some(result) => errors.extend(result.err()),
+                None => continue,
+            }
+            reloaded.push(key);
+        }
+        (reloaded, errors)
+    }
+}
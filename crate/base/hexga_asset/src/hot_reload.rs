@@ -7,6 +7,100 @@ pub trait HotReload
     fn hot_reload(&mut self) -> Result<Self::Ok, Self::Error>;
 }
 
+/// What [`AssetManager::hot_reload_changed`] reloaded
+#[derive(Debug, Default)]
+pub struct HotReloadChanged
+{
+    /// The paths of the reloaded assets, even if they failed to load
+    pub reloaded: Vec<PathBuf>,
+    /// The assets reloaded because one of their dependencies changed, in the order they were reloaded. See [`DependencyGraph`].
+    pub dependents: Vec<AssetKey>,
+    pub errors: Vec<IoError>,
+}
+
+impl HotReloadChanged
+{
+    pub fn is_empty(&self) -> bool { self.reloaded.is_empty() && self.dependents.is_empty() }
+}
+
+impl<T> AssetManager<T>
+where
+    T: Async + Load,
+{
+    /// Only reload the assets whose file, or a sidecar like `hero.png.meta`, changed since the last call.
+    /// Then the assets depending on them, or on a changed file given to [`depends_on`], are reloaded after their dependencies.
+    ///
+    /// The files are watched with a [`Watcher`]: the first call only start watching them, and the assets loaded since the last call
+    /// are watched from this one.
+    pub fn hot_reload_changed(&self) -> HotReloadChanged
+    {
+        let files = ASSET
+            .get()
+            .dependencies
+            .read()
+            .unwrap()
+            .files_of::<T>()
+            .map(|path| path.to_owned())
+            .collect::<Vec<_>>();
+        let (mut paths, watcher) = {
+            let inner = self.inner.read().unwrap();
+            let paths = inner
+                .values
+                .iter()
+                .filter(|(_, storage)| storage.upgrade().is_some())
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            (paths, inner.watcher.clone())
+        };
+        paths.extend(files);
+        let changed = {
+            let mut watcher = watcher.lock().unwrap();
+            let watcher = watcher.get_or_insert_with(Watcher::new);
+
+            // the changes of the newly watched paths are only seen by the next call
+            let changed = watcher.changed();
+            let unwatched = watcher
+                .watched()
+                .filter(|path| !paths.iter().any(|p| p == path))
+                .map(|path| path.to_owned())
+                .collect::<Vec<_>>();
+            for path in unwatched
+            {
+                watcher.unwatch(path);
+            }
+            for path in &paths
+            {
+                watcher.watch(path);
+            }
+            changed
+        };
+
+        let mut result = HotReloadChanged::default();
+        if changed.is_empty()
+        {
+            return result;
+        }
+        for asset in self.iter()
+        {
+            let Some(path) = asset.path().filter(|path| changed.iter().any(|c| is_source_of(path, c)))
+            else
+            {
+                continue;
+            };
+            asset.reload_from_source();
+            result.errors.extend(asset.state().as_error().cloned());
+            result.reloaded.push(path);
+        }
+        result.reloaded.sort();
+
+        let reloaded = result.reloaded.iter().map(|path| AssetKey::of::<T>(path)).collect::<Vec<_>>();
+        let (dependents, errors) = ASSET.get().reload_dependents(&changed, &reloaded);
+        result.dependents = dependents;
+        result.errors.extend(errors);
+        result
+    }
+}
+
 impl<T> HotReload for Asset<T>
 where
     T: Async + Load,
@@ -26,16 +120,18 @@ where
 
     fn hot_reload(&mut self) -> Result<Self::Ok, Self::Error>
     {
-        let mut error = Vec::new();
-        for mut asset in self.iter()
+        let mut reloaded = Vec::new();
+        let mut errors = Vec::new();
+        for asset in self.iter()
         {
-            match asset.hot_reload()
-            {
-                Ok(_) =>
-                {}
-                Err(err) => error.push(err),
-            };
+            asset.reload_from_source();
+            errors.extend(asset.state().as_error().cloned());
+            reloaded.extend(asset.path());
         }
-        if error.is_empty() { Ok(()) } else { Err(error) }
+        // the dependents are reloaded once, after all the assets
+        let reloaded = reloaded.iter().map(|path| AssetKey::of::<T>(path)).collect::<Vec<_>>();
+        let (_, dependent_errors) = ASSET.get().reload_dependents(&[], &reloaded);
+        errors.extend(dependent_errors);
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
     }
 }
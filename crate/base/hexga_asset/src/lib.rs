@@ -17,8 +17,6 @@ save()
 
 asset correct path/remove extension
 
-special type of asset that are load/unloaded automatically when not used for X second
-
 */
 
 // use std::clone::CloneToUninit;
@@ -31,9 +29,15 @@ use std::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
-    sync::{Arc, LazyLock, MappedRwLockReadGuard, MappedRwLockWriteGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak},
+    sync::{Arc, LazyLock, MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak},
+    time::Duration,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
 use hexga_core::prelude::*;
 use hexga_encoding::prelude::*;
 use hexga_io::prelude::*;
@@ -50,6 +54,14 @@ pub use bytes::*;
 mod hot_reload;
 pub use hot_reload::*;
 
+mod dependency;
+pub use dependency::*;
+
+mod loader;
+use loader::*;
+
+mod collect;
+
 #[cfg(feature = "serde")]
 pub use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor, ser::SerializeStruct};
 
@@ -0,0 +1,107 @@
+use super::*;
+use std::sync::Condvar;
+
+/// The number of assets being loaded in the background by an [`AssetManager`]
+#[derive(Debug, Default)]
+pub(crate) struct Pending
+{
+    count: Mutex<usize>,
+    done: Condvar,
+}
+
+impl Pending
+{
+    pub(crate) fn count(&self) -> usize { *self.count.lock().unwrap() }
+
+    /// Until the returned guard is dropped, even if the loading panic
+    pub(crate) fn start(self: &Arc<Self>) -> PendingGuard
+    {
+        *self.count.lock().unwrap() += 1;
+        PendingGuard { pending: self.clone() }
+    }
+
+    pub(crate) fn wait_all(&self)
+    {
+        let count = self.count.lock().unwrap();
+        drop(self.done.wait_while(count, |count| *count > 0).unwrap());
+    }
+}
+
+pub(crate) struct PendingGuard
+{
+    pending: Arc<Pending>,
+}
+
+impl Drop for PendingGuard
+{
+    fn drop(&mut self)
+    {
+        let mut count = self.pending.count.lock().unwrap();
+        *count -= 1;
+        if *count == 0
+        {
+            self.pending.done.notify_all();
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod workers
+{
+    use super::*;
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    type Job = Box<dyn FnOnce() + Send>;
+
+    /// The threads loading the assets in the background
+    static WORKERS: LazyLock<Mutex<Sender<Job>>> = LazyLock::new(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let count = std::thread::available_parallelism().map_or(1, |n| n.get()).clamp(1, 4);
+        for i in 0..count
+        {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("hexga_asset_loader_{i}"))
+                .spawn(move || work(&receiver))
+                .expect("failed to spawn an asset loader thread");
+        }
+        Mutex::new(sender)
+    });
+
+    fn work(receiver: &Mutex<Receiver<Job>>)
+    {
+        loop
+        {
+            let job = receiver.lock().unwrap().recv();
+            match job
+            {
+                // A panicking asset don't kill the worker
+                Ok(job) => drop(std::panic::catch_unwind(std::panic::AssertUnwindSafe(job))),
+                Err(_) => return,
+            }
+        }
+    }
+
+    pub(crate) fn spawn<F>(job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        // The workers never stop, so the channel is always open
+        WORKERS.lock().unwrap().send(Box::new(job)).unwrap();
+    }
+}
+
+/// There is no thread on wasm, and the file system is not async: the asset is loaded at once, blocking the caller
+#[cfg(target_arch = "wasm32")]
+mod workers
+{
+    pub(crate) fn spawn<F>(job: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        job()
+    }
+}
+
+pub(crate) use workers::spawn;
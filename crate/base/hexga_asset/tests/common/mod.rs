@@ -0,0 +1,22 @@
+//! Shared by the integration tests
+#![allow(dead_code)]
+
+use hexga_encoding::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+pub struct Room
+{
+    pub name: String,
+    pub tiles: Vec<u8>,
+    pub spawn: Option<(i32, i32)>,
+}
+
+pub fn room(name: &str) -> Room
+{
+    Room {
+        name: name.to_owned(),
+        tiles: vec![0, 3, 1, 1, 2],
+        spawn: Some((4, -2)),
+    }
+}
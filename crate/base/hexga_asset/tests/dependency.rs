@@ -0,0 +1,103 @@
+use hexga_asset::prelude::*;
+use hexga_asset::{AssetKey, dependency_graph};
+use hexga_encoding::prelude::*;
+use hexga_io::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Tileset
+{
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Save, Load, Debug)]
+struct Map
+{
+    name: String,
+    tileset: Asset<Tileset>,
+}
+
+#[derive(Serialize, Deserialize, Save, Load, Debug)]
+struct World
+{
+    maps: Vec<Asset<Map>>,
+}
+
+#[test]
+fn asset_dependencies()
+{
+    let tileset = |name: &str| Tileset { name: name.to_owned() };
+    Io.with_file_system(MemoryFs::new(), || {
+        tileset("forest").save("deps/forest.ron").unwrap();
+        let forest = Asset::<Tileset>::load("deps/forest.ron");
+        let level = |name: &str| Map {
+            name: name.to_owned(),
+            tileset: forest.clone(),
+        };
+        level("intro").save("deps/intro.ron").unwrap();
+        let intro = Asset::<Map>::load("deps/intro.ron");
+        World { maps: vec![intro.clone()] }.save("deps/world.ron").unwrap();
+        let world = Asset::<World>::load("deps/world.ron");
+
+        let intro_key = AssetKey::of::<Map>(Path::new("deps/intro"));
+        let world_key = AssetKey::of::<World>(Path::new("deps/world"));
+        let graph = dependency_graph();
+        assert_eq!(graph.dependencies(&intro_key).collect::<Vec<_>>(), [Path::new("deps/forest")]);
+        assert_eq!(world.dependencies(), [PathBuf::from("deps/intro")]);
+        assert!(forest.dependencies().is_empty());
+        assert_eq!(graph.dependents(Path::new("deps/forest.ron")).collect::<Vec<_>>(), [&intro_key]);
+        assert_eq!(graph.dependents(Path::new("deps/forest.ron.meta")).collect::<Vec<_>>(), [&intro_key]);
+        // Not a file of the tileset, only in a directory with the same prefix
+        assert_eq!(graph.dependents(Path::new("deps/forest.d/map.ron")).count(), 0);
+        assert_eq!(
+            graph.reload_order(&[PathBuf::from("deps/forest.ron")], &[]),
+            [intro_key.clone(), world_key.clone()]
+        );
+        assert_eq!(graph.reload_order(&[], std::slice::from_ref(&intro_key)), std::slice::from_ref(&world_key));
+
+        // reloading a dependency reload the dependents
+        level("intro v2").save("deps/intro.ron").unwrap();
+        tileset("forest v2").save("deps/forest.ron").unwrap();
+        forest.clone().hot_reload().unwrap();
+        assert_eq!(forest.get().unwrap().name, "forest v2");
+        assert_eq!(intro.get().unwrap().name, "intro v2");
+
+        let manager = Asset::<Tileset>::manager();
+        assert!(manager.hot_reload_changed().is_empty());
+        level("intro v3").save("deps/intro.ron").unwrap();
+        tileset("forest v3").save("deps/forest.ron").unwrap();
+        let changed = manager.hot_reload_changed();
+        assert_eq!(changed.reloaded, [Path::new("deps/forest")]);
+        assert_eq!(changed.dependents, [intro_key, world_key]);
+        assert!(changed.errors.is_empty());
+        assert_eq!(intro.get().unwrap().name, "intro v3");
+        assert!(world.get().unwrap().maps[0] == intro);
+
+        // A map and a tileset at the same path are different dependencies
+        tileset("same").save("deps/same.ron").unwrap();
+        let same_tileset = Asset::<Tileset>::load("deps/same.ron");
+        Map {
+            name: "same".to_owned(),
+            tileset: same_tileset.clone(),
+        }
+        .save("deps/same.json")
+        .unwrap();
+        let _same_map = Asset::<Map>::load("deps/same.json");
+        Map {
+            name: "uses the tileset".to_owned(),
+            tileset: same_tileset,
+        }
+        .save("deps/user.ron")
+        .unwrap();
+        let _user = Asset::<Map>::load("deps/user.ron");
+        let graph = dependency_graph();
+        let same_map_key = AssetKey::of::<Map>(Path::new("deps/same"));
+        let user_key = AssetKey::of::<Map>(Path::new("deps/user"));
+        assert_eq!(graph.reload_order(&[], &[same_map_key]), []);
+        assert_eq!(
+            graph.reload_order(&[], &[AssetKey::of::<Tileset>(Path::new("deps/same"))]),
+            [AssetKey::of::<Map>(Path::new("deps/same")), user_key]
+        );
+    });
+}
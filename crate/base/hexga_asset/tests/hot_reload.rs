@@ -0,0 +1,91 @@
+mod common;
+use common::{Room, room};
+use hexga_asset::HotReload;
+use hexga_asset::prelude::*;
+use hexga_encoding::prelude::*;
+use hexga_io::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Door
+{
+    locked: bool,
+}
+
+#[test]
+fn save_and_hot_reload()
+{
+    let fs = MemoryFs::new();
+    Io.with_file_system(fs.clone(), || {
+        Door { locked: true }.save("doors/entry.ron").unwrap();
+        let mut asset = Asset::<Door>::load("doors/entry.ron");
+        assert_eq!(*asset.get().unwrap(), Door { locked: true });
+
+        asset.replace(Door { locked: false });
+        asset.save().unwrap();
+        assert_eq!(Door::load("doors/entry.ron").unwrap(), Door { locked: false });
+
+        fs.insert("doors/entry.ron", Door { locked: true }.to_ron().unwrap().into_bytes());
+        asset.hot_reload().unwrap();
+        assert_eq!(*asset.get().unwrap(), Door { locked: true });
+    });
+}
+
+#[test]
+fn hot_reload_changed()
+{
+    let fs = MemoryFs::new();
+    Io.with_file_system(fs.clone(), || {
+        room("a").save("hot_reload_changed/a.ron").unwrap();
+        room("b").save("hot_reload_changed/b.ron").unwrap();
+        room("c").save("hot_reload_changed/c.ron").unwrap();
+        let a = Asset::<Room>::load("hot_reload_changed/a.ron");
+        let _b = Asset::<Room>::load("hot_reload_changed/b.ron");
+        let c = Asset::<Room>::load("hot_reload_changed/c.ron");
+        let manager = Asset::<Room>::manager();
+        // Start watching
+        assert!(manager.hot_reload_changed().is_empty());
+
+        room("a modified").save("hot_reload_changed/a.ron").unwrap();
+        Io.save_str("hot_reload_changed/b.ron.meta", "sidecar").unwrap();
+        let changed = manager.hot_reload_changed();
+        assert_eq!(changed.reloaded, [Path::new("hot_reload_changed/a"), Path::new("hot_reload_changed/b")]);
+        assert!(changed.errors.is_empty());
+        assert_eq!(*a.get().unwrap(), room("a modified"));
+        assert_eq!(*c.get().unwrap(), room("c"));
+        assert!(manager.hot_reload_changed().is_empty());
+
+        Io.save_str("hot_reload_changed/c.ron", "(not a room").unwrap();
+        let changed = manager.hot_reload_changed();
+        assert_eq!(changed.reloaded, [Path::new("hot_reload_changed/c")]);
+        assert_eq!(changed.errors.len(), 1);
+        assert!(c.is_error());
+    });
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Key
+{
+    id: u32,
+}
+
+#[test]
+fn hot_reload_manager_errors()
+{
+    Io.with_file_system(MemoryFs::new(), || {
+        Key { id: 1 }.save("keys/a.ron").unwrap();
+        Key { id: 2 }.save("keys/b.ron").unwrap();
+        let a = Asset::<Key>::load("keys/a.ron");
+        let b = Asset::<Key>::load("keys/b.ron");
+        let mut manager = Asset::<Key>::manager();
+        assert!(HotReload::hot_reload(&mut manager).is_ok());
+
+        Key { id: 3 }.save("keys/a.ron").unwrap();
+        Io.save_str("keys/b.ron", "(id: ").unwrap();
+        let errors = HotReload::hot_reload(&mut manager).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*a.get().unwrap(), Key { id: 3 });
+        assert!(b.is_error());
+    });
+}
@@ -0,0 +1,99 @@
+use hexga_asset::prelude::*;
+use hexga_asset::{AssetInit, AssetLifetime, AssetState, Instant};
+use hexga_encoding::prelude::*;
+use hexga_io::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Texture
+{
+    pixels: Vec<u8>,
+}
+
+#[test]
+fn asset_collect()
+{
+    let second = Duration::from_secs(1);
+    let load_timed = |name: &str, duration: Duration| {
+        let mut texture = Asset::<Texture>::load(format!("collect/{name}.ron"));
+        texture.set_lifetime(AssetLifetime::Timed(duration));
+        assert_eq!(texture.lifetime(), AssetLifetime::Timed(duration));
+    };
+    Io.with_file_system(MemoryFs::new(), || {
+        for (name, size) in [("a", 10), ("b", 20), ("c", 30)]
+        {
+            Texture { pixels: vec![0; size] }.save(format!("collect/{name}.ron")).unwrap();
+        }
+        let manager = Asset::<Texture>::manager();
+        manager.set_memory_size(|texture| texture.pixels.len());
+
+        load_timed("a", second);
+        assert!(manager.collect(Instant::now()).is_empty());
+        let a = manager.get("collect/a").unwrap();
+        assert!(manager.collect(Instant::now() + 2 * second).is_empty());
+        drop(a);
+        // the grace period start when it was last seen used
+        assert!(manager.collect(Instant::now() + 2 * second + second / 2).is_empty());
+        assert_eq!(manager.collect(Instant::now() + 4 * second), [Path::new("collect/a")]);
+        assert!(manager.get("collect/a").is_none());
+
+        // least recently used first
+        load_timed("b", 60 * second);
+        load_timed("c", 60 * second);
+        let a = Asset::<Texture>::load("collect/a.ron");
+        drop(manager.get("collect/b"));
+        assert_eq!(manager.memory_usage(), 60);
+        manager.set_memory_budget(Some(35));
+        assert_eq!(manager.collect(Instant::now()), [Path::new("collect/c")]);
+        assert_eq!(manager.memory_usage(), 30);
+        // the used assets are never unloaded
+        manager.set_memory_budget(Some(0));
+        assert_eq!(manager.collect(Instant::now()), [Path::new("collect/b")]);
+        assert_eq!(*a.get().unwrap(), Texture { pixels: vec![0; 10] });
+        manager.set_memory_budget(None);
+    });
+}
+
+#[derive(Serialize, Deserialize, Save, Load, PartialEq, Debug, Clone)]
+struct Note
+{
+    text: String,
+}
+
+/// The `ReferenceCounted` assets are dropped with their last `Asset`, the `Persistant` ones stay in the manager
+#[test]
+fn asset_lifetime()
+{
+    Io.with_file_system(MemoryFs::new(), || {
+        Note { text: "a".to_owned() }.save("lifetime/a.ron").unwrap();
+        Note { text: "b".to_owned() }.save("lifetime/b.ron").unwrap();
+        let manager = Asset::<Note>::manager();
+
+        let a = Asset::<Note>::load("lifetime/a.ron");
+        assert_eq!(a.lifetime(), AssetLifetime::ReferenceCounted);
+        // the manager only have a weak reference
+        assert_eq!(a.strong_count(), 1);
+        drop(a);
+        assert!(manager.get("lifetime/a").is_none());
+
+        let mut b = Asset::<Note>::load("lifetime/b.ron");
+        b.set_lifetime(AssetLifetime::Persistant);
+        assert_eq!(b.lifetime(), AssetLifetime::Persistant);
+        assert_eq!(b.strong_count(), 2);
+        drop(b);
+        assert_eq!(manager.get("lifetime/b").unwrap().get().unwrap().text, "b");
+
+        let c = Asset::<Note>::update_or_create(
+            &"lifetime/c",
+            AssetInit {
+                state: AssetState::Loaded(Note { text: "c".to_owned() }),
+                lifetime: AssetLifetime::Persistant,
+            },
+        );
+        assert_eq!(c.lifetime(), AssetLifetime::Persistant);
+        drop(c);
+        assert!(manager.get("lifetime/c").is_some());
+    });
+}
@@ -0,0 +1,62 @@
+mod common;
+use common::{Room, room};
+use hexga_asset::prelude::*;
+use hexga_asset::{AssetPersistance, AssetWeak};
+use hexga_encoding::prelude::*;
+use hexga_io::prelude::*;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Block the loading until the test release it
+struct GatedFs
+{
+    fs: MemoryFs,
+    gate: Arc<Mutex<()>>,
+}
+
+impl FileSystem for GatedFs
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>>
+    {
+        drop(self.gate.lock().unwrap());
+        self.fs.load_bytes(path)
+    }
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult { self.fs.save_bytes(path, bytes) }
+    fn exists(&self, path: &Path) -> bool { self.fs.exists(path) }
+}
+
+#[test]
+fn load_async()
+{
+    let fs = MemoryFs::new();
+    fs.insert("load_async/a.ron", room("a").to_ron().unwrap().into_bytes());
+    fs.insert("load_async/b.ron", room("b").to_ron().unwrap().into_bytes());
+    let gate = Arc::new(Mutex::new(()));
+    let gated = GatedFs { fs, gate: gate.clone() };
+
+    Io.with_file_system(gated, || {
+        let manager = Asset::<Room>::manager();
+        let placeholder = Asset::update_or_create(AssetPersistance::Generated, room("loading"));
+        manager.set_loading_value(placeholder.downgrade());
+
+        let closed = gate.lock().unwrap();
+        let a = Asset::<Room>::load_async("load_async/a.ron");
+        let b = Asset::<Room>::load_async("load_async/b.ron");
+        let missing = Asset::<Room>::load_async("load_async/missing.ron");
+        assert!(a.is_loading());
+        assert!(a.get().is_none());
+        assert_eq!(*a.get_or_placeholder().unwrap(), room("loading"));
+        // already loading
+        assert_eq!(Asset::<Room>::load_async("load_async/a.ron"), a);
+        assert_eq!(manager.pending(), 3);
+
+        drop(closed);
+        manager.wait_all();
+        assert_eq!(manager.pending(), 0);
+        assert_eq!(*a.get().unwrap(), room("a"));
+        assert_eq!(*b.get_or_placeholder().unwrap(), room("b"));
+        assert!(missing.is_error());
+
+        manager.set_loading_value(AssetWeak::new());
+    });
+}
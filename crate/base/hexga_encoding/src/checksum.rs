@@ -0,0 +1,175 @@
+use super::*;
+
+/// Integrity envelope applied on top of an other extension, ex: `save.ron.crc` is some Ron followed by its Crc32.
+///
+/// Detect the saves truncated or damaged after a crash: loading fail with [`EncodeError::Corrupted`] instead of garbage.
+///
+/// The envelope is `HXCK`, the checksum kind, the payload length (u64 little endian), the digest, then the payload.
+/// Like the [`Compression`](crate::Compression), it can be stacked with the other outer extensions: `save.ron.gz.crc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Checksum
+{
+    /// Detect the accidental corruptions
+    Crc32,
+    /// HMAC-SHA256 with a caller supplied key, also detect the tampering.
+    ///
+    /// See [`Save::save_to_writer_signed`] and [`Load::load_from_bytes_signed`].
+    Hmac,
+}
+
+const MAGIC: &[u8] = b"HXCK";
+const HEADER_LEN: usize = MAGIC.len() + 1 + size_of::<u64>();
+
+impl Checksum
+{
+    pub const ALL: &'static [Self] = &[Self::Crc32, Self::Hmac];
+
+    pub const fn extension(self) -> &'static str
+    {
+        match self
+        {
+            Checksum::Crc32 => Extension::CRC32,
+            Checksum::Hmac => Extension::HMAC,
+        }
+    }
+
+    /// The byte written after the magic number
+    const fn kind(self) -> u8
+    {
+        match self
+        {
+            Checksum::Crc32 => 1,
+            Checksum::Hmac => 2,
+        }
+    }
+
+    pub const fn digest_len(self) -> usize
+    {
+        match self
+        {
+            Checksum::Crc32 => size_of::<u32>(),
+            Checksum::Hmac => 32,
+        }
+    }
+
+    /// Split the outermost checksum from the inner extension.
+    ///
+    /// ```
+    /// use hexga_encoding::Checksum;
+    ///
+    /// assert_eq!(Checksum::split_extension("ron.crc"), Some((Some("ron"), Checksum::Crc32)));
+    /// assert_eq!(Checksum::split_extension("json.gz.hmac"), Some((Some("json.gz"), Checksum::Hmac)));
+    /// assert_eq!(Checksum::split_extension("ron"), None);
+    /// ```
+    pub fn split_extension(extension: &extension) -> Option<(Option<&extension>, Self)>
+    {
+        match extension.rsplit_once('.')
+        {
+            Some((inner, outer)) => Self::try_from(outer).ok().map(|c| (Some(inner), c)),
+            None => Self::try_from(extension).ok().map(|c| (None, c)),
+        }
+    }
+
+    /// Join the inner extension with this checksum, ex: `ron` give `ron.crc`
+    pub fn join_extension(self, inner: Option<&extension>) -> Extension
+    {
+        match inner
+        {
+            Some(inner) => format!("{inner}.{}", self.extension()),
+            None => self.extension().to_owned(),
+        }
+    }
+
+    fn digest(self, payload: &[u8], key: Option<&[u8]>) -> EncodeResult<Vec<u8>>
+    {
+        match self
+        {
+            Checksum::Crc32 => Ok(crc32fast::hash(payload).to_le_bytes().to_vec()),
+            Checksum::Hmac => match key
+            {
+                Some(key) => Ok(hmac_sha256::HMAC::mac(payload, key).to_vec()),
+                None => Err(self.missing_key()),
+            },
+        }
+    }
+
+    fn missing_key(self) -> EncodeError
+    {
+        EncodeError::markup::<Self>(self.extension(), "a key is needed, see `save_to_writer_signed` and `load_from_bytes_signed`")
+    }
+
+    /// Write the envelope containing `payload`. The `key` is only used by [`Checksum::Hmac`].
+    pub fn seal<W>(self, payload: &[u8], key: Option<&[u8]>, mut writer: W) -> EncodeResult
+    where
+        W: Write,
+    {
+        let digest = self.digest(payload, key)?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[self.kind()])?;
+        writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+        writer.write_all(&digest)?;
+        writer.write_all(payload)?;
+        Ok(())
+    }
+
+    /// Check the envelope and return the payload, or fail with [`EncodeError::Corrupted`]. The `key` is only used by [`Checksum::Hmac`].
+    pub fn open<'a>(self, bytes: &'a [u8], key: Option<&[u8]>) -> EncodeResult<&'a [u8]>
+    {
+        let corrupted = |reason: String| EncodeError::Corrupted { reason: reason.into() };
+
+        let Some(header) = bytes.get(..HEADER_LEN).filter(|header| header.starts_with(MAGIC))
+        else
+        {
+            return Err(corrupted(format!("missing the {self} header")));
+        };
+        let kind = header[MAGIC.len()];
+        if kind != self.kind()
+        {
+            let got = Self::ALL
+                .iter()
+                .find(|c| c.kind() == kind)
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| format!("unknow ({kind})"));
+            return Err(corrupted(format!("expected a {self} checksum, got {got}")));
+        }
+        let len = u64::from_le_bytes(header[MAGIC.len() + 1..].try_into().unwrap());
+
+        let Some((digest, payload)) = bytes[HEADER_LEN..].split_at_checked(self.digest_len())
+        else
+        {
+            return Err(corrupted(format!("missing the {self} digest")));
+        };
+        if payload.len() as u64 != len
+        {
+            return Err(corrupted(format!("expected {len} bytes, got {}", payload.len())));
+        }
+
+        let valid = match self
+        {
+            Checksum::Crc32 => self.digest(payload, key)? == digest,
+            Checksum::Hmac => hmac_sha256::HMAC::verify(payload, key.ok_or_else(|| self.missing_key())?, digest.try_into().unwrap()),
+        };
+        if !valid
+        {
+            return Err(corrupted(format!("{self} mismatch")));
+        }
+        Ok(payload)
+    }
+}
+impl Display for Checksum
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{:?}", self) }
+}
+impl<'a> TryFrom<&'a str> for Checksum
+{
+    type Error = ();
+    fn try_from(value: &'a str) -> Result<Self, Self::Error>
+    {
+        match value
+        {
+            Extension::CRC32 => Ok(Self::Crc32),
+            Extension::HMAC => Ok(Self::Hmac),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+
+/// Transport transform applied on top of an other extension, ex: `level.ron.gz` is some Ron compressed with Gzip.
+///
+/// Outer extensions can be stacked, the rightmost one is applied last when saving and first when loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression
+{
+    Gzip,
+    Zstd,
+    Deflate,
+}
+
+impl Compression
+{
+    pub const ALL: &'static [Self] = &[Self::Gzip, Self::Zstd, Self::Deflate];
+
+    pub const fn extension(self) -> &'static str
+    {
+        match self
+        {
+            Compression::Gzip => Extension::GZIP,
+            Compression::Zstd => Extension::ZSTD,
+            Compression::Deflate => Extension::DEFLATE,
+        }
+    }
+
+    /// Split the outermost compression from the inner extension.
+    ///
+    /// ```
+    /// use hexga_encoding::Compression;
+    ///
+    /// assert_eq!(Compression::split_extension("ron.gz"), Some((Some("ron"), Compression::Gzip)));
+    /// assert_eq!(Compression::split_extension("json.gz.zst"), Some((Some("json.gz"), Compression::Zstd)));
+    /// assert_eq!(Compression::split_extension("zst"), Some((None, Compression::Zstd)));
+    /// assert_eq!(Compression::split_extension("ron"), None);
+    /// ```
+    pub fn split_extension(extension: &extension) -> Option<(Option<&extension>, Self)>
+    {
+        match extension.rsplit_once('.')
+        {
+            Some((inner, outer)) => Self::try_from(outer).ok().map(|c| (Some(inner), c)),
+            None => Self::try_from(extension).ok().map(|c| (None, c)),
+        }
+    }
+
+    /// Join the inner extension with this compression, ex: `ron` give `ron.gz`
+    pub fn join_extension(self, inner: Option<&extension>) -> Extension
+    {
+        match inner
+        {
+            Some(inner) => format!("{inner}.{}", self.extension()),
+            None => self.extension().to_owned(),
+        }
+    }
+
+    fn error(self, reason: impl Display) -> EncodeError { EncodeError::markup::<Self>(self.extension(), reason) }
+
+    pub fn compress<W>(self, bytes: &[u8], mut writer: W) -> EncodeResult
+    where
+        W: Write,
+    {
+        match self
+        {
+            Compression::Gzip =>
+            {
+                let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()?;
+            }
+            Compression::Zstd => ruzstd::encoding::compress(bytes, &mut writer, ruzstd::encoding::CompressionLevel::Fastest),
+            Compression::Deflate =>
+            {
+                let mut encoder = flate2::write::DeflateEncoder::new(writer, flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn decompress<R>(self, reader: R) -> EncodeResult<Vec<u8>>
+    where
+        R: Read,
+    {
+        self.decompress_with_limits(reader, DecodeLimits::UNLIMITED)
+    }
+
+    /// Same as [`Self::decompress`], but fail when the decompressed size is above [`DecodeLimits::max_bytes`]
+    pub fn decompress_with_limits<R>(self, reader: R, limits: DecodeLimits) -> EncodeResult<Vec<u8>>
+    where
+        R: Read,
+    {
+        match self
+        {
+            Compression::Gzip => self.read_to_end(flate2::read::GzDecoder::new(reader), limits),
+            Compression::Zstd => self.read_to_end(ruzstd::decoding::StreamingDecoder::new(reader).map_err(|e| self.error(e))?, limits),
+            Compression::Deflate => self.read_to_end(flate2::read::DeflateDecoder::new(reader), limits),
+        }
+    }
+
+    fn read_to_end(self, decoder: impl Read, limits: DecodeLimits) -> EncodeResult<Vec<u8>>
+    {
+        let mut decoder = limits.reader(decoder);
+        let mut bytes = Vec::new();
+        let result = decoder.read_to_end(&mut bytes).map_err(|e| self.error(e));
+        decoder.check(result)?;
+        Ok(bytes)
+    }
+}
+impl Display for Compression
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{:?}", self) }
+}
+impl<'a> TryFrom<&'a str> for Compression
+{
+    type Error = ();
+    fn try_from(value: &'a str) -> Result<Self, Self::Error>
+    {
+        match value
+        {
+            Extension::GZIP => Ok(Self::Gzip),
+            Extension::ZSTD => Ok(Self::Zstd),
+            Extension::DEFLATE => Ok(Self::Deflate),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::*;
+
+/// Bounds applied while loading, to protect against untrusted inputs (mods, user files, network...)
+/// that would otherwise blow the stack or allocate unbounded memory.
+///
+/// Used by [`Load::load_from_reader_with_limits`] and `AnyFormat::from_reader_with_limits`.
+/// [`Load::load_from_reader`] use [`DecodeLimits::DEFAULT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeLimits
+{
+    /// Maximum nesting of sequences, maps, enums, options and newtypes.
+    pub max_depth: usize,
+    /// Maximum number of elements in a single sequence or map.
+    pub max_len: usize,
+    /// Maximum number of bytes read, also apply to the decompressed bytes.
+    pub max_bytes: usize,
+}
+
+impl DecodeLimits
+{
+    pub const DEFAULT: Self = Self {
+        max_depth: 128,
+        max_len: 1 << 28,
+        max_bytes: 1 << 30,
+    };
+    pub const UNLIMITED: Self = Self {
+        max_depth: usize::MAX,
+        max_len: usize::MAX,
+        max_bytes: usize::MAX,
+    };
+
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self
+    {
+        self.max_depth = max_depth;
+        self
+    }
+    pub const fn with_max_len(mut self, max_len: usize) -> Self
+    {
+        self.max_len = max_len;
+        self
+    }
+    pub const fn with_max_bytes(mut self, max_bytes: usize) -> Self
+    {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub const fn max(self, limit: DecodeLimit) -> usize
+    {
+        match limit
+        {
+            DecodeLimit::Depth => self.max_depth,
+            DecodeLimit::Len => self.max_len,
+            DecodeLimit::Bytes => self.max_bytes,
+        }
+    }
+
+    pub const fn error(self, limit: DecodeLimit) -> EncodeError { EncodeError::LimitExceeded { limit, max: self.max(limit) } }
+
+    pub fn check(self, limit: DecodeLimit, value: usize) -> EncodeResult { if value > self.max(limit) { Err(self.error(limit)) } else { Ok(()) } }
+
+    /// Wrap the reader to fail with [`EncodeError::LimitExceeded`] when more than [`Self::max_bytes`] are read.
+    pub fn reader<R>(self, reader: R) -> LimitedReader<R>
+    where
+        R: Read,
+    {
+        LimitedReader {
+            reader,
+            remaining: self.max_bytes,
+            limits: self,
+            exceeded: false,
+        }
+    }
+
+    /// Read everything, up to [`Self::max_bytes`]
+    pub fn read_to_end<R>(self, reader: R) -> EncodeResult<Vec<u8>>
+    where
+        R: Read,
+    {
+        let mut reader = self.reader(reader);
+        let mut bytes = Vec::with_capacity(256);
+        let result = reader.read_to_end(&mut bytes);
+        reader.check(result)?;
+        Ok(bytes)
+    }
+}
+impl Default for DecodeLimits
+{
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// One of the bound of [`DecodeLimits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeLimit
+{
+    Depth,
+    Len,
+    Bytes,
+}
+impl Display for DecodeLimit
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            DecodeLimit::Depth => write!(f, "depth"),
+            DecodeLimit::Len => write!(f, "length"),
+            DecodeLimit::Bytes => write!(f, "bytes"),
+        }
+    }
+}
+
+/// See [`DecodeLimits::reader`]
+pub struct LimitedReader<R>
+{
+    reader: R,
+    remaining: usize,
+    limits: DecodeLimits,
+    exceeded: bool,
+}
+impl<R> LimitedReader<R>
+{
+    /// True once the reader tried to go past [`DecodeLimits::max_bytes`]
+    pub fn exceeded(&self) -> bool { self.exceeded }
+
+    /// Replace any error by [`EncodeError::LimitExceeded`] if the limit was reached, since the error was probably caused by the truncated input.
+    pub fn check<T, E>(&self, result: Result<T, E>) -> EncodeResult<T>
+    where
+        E: Into<EncodeError>,
+    {
+        if self.exceeded
+        {
+            return Err(self.limits.error(DecodeLimit::Bytes));
+        }
+        result.map_err(Into::into)
+    }
+
+    pub fn into_inner(self) -> R { self.reader }
+}
+impl<R> Read for LimitedReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        if buf.is_empty()
+        {
+            return Ok(0);
+        }
+        if self.remaining == 0
+        {
+            // Only fail if there is really something after the limit
+            let mut byte = [0];
+            return match self.reader.read(&mut byte)?
+            {
+                0 => Ok(0),
+                _ =>
+                {
+                    self.exceeded = true;
+                    Err(std::io::Error::new(std::io::ErrorKind::FileTooLarge, self.limits.error(DecodeLimit::Bytes)))
+                }
+            };
+        }
+        let len = buf.len().min(self.remaining);
+        let read = self.reader.read(&mut buf[..len])?;
+        self.remaining -= read;
+        Ok(read)
+    }
+}
@@ -102,18 +102,18 @@ impl SaveExtension for String
 {
     fn save_custom_extensions() -> impl Iterator<Item = &'static extension> { ["txt", "md", "cvs"].into_iter() }
 
-    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>) -> EncodeResult
+    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult
     where
         W: Write,
     {
-        self.as_str().save_to_writer_with_custom_extension(writer, extension)
+        self.as_str().save_to_writer_with_custom_extension(writer, extension, args)
     }
 }
 impl LoadExtension for String
 {
     fn load_custom_extensions() -> impl Iterator<Item = &'static extension> { Self::save_custom_extensions() }
 
-    fn load_from_reader_with_custom_extension<R>(mut reader: R, _extension: Option<&extension>) -> EncodeResult<Self>
+    fn load_from_reader_with_custom_extension<R>(mut reader: R, _extension: Option<&extension>, _args: ExtensionArgs<'_>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: Read,
@@ -132,7 +132,7 @@ impl<'a> SaveExtension for &'a str
 {
     fn save_custom_extensions() -> impl Iterator<Item = &'static extension> { String::save_custom_extensions() }
 
-    fn save_to_writer_with_custom_extension<W>(&self, mut writer: W, _extension: Option<&extension>) -> EncodeResult
+    fn save_to_writer_with_custom_extension<W>(&self, mut writer: W, _extension: Option<&extension>, _args: ExtensionArgs<'_>) -> EncodeResult
     where
         W: Write,
     {
@@ -141,6 +141,10 @@ impl<'a> SaveExtension for &'a str
     }
 }
 
+// To load them borrowed, see `LoadBorrowed`
+impl LoadExtension for &str {}
+impl LoadExtension for &[u8] {}
+
 #[cfg(feature = "serde_rc")]
 impl_load_and_save!(Rc<T>, RcWeak<T>, Arc<T>, ArcWeak<T>,);
 
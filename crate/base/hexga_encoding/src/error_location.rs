@@ -0,0 +1,110 @@
+use super::*;
+
+/// Where a [`EncodeError::Markup`] happened in the input
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ErrorLocation
+{
+    /// The path of the value being decoded, ex: `player.inventory[3].id`. Empty when unknown or at the root.
+    pub path: String,
+    /// Only known for the text formats
+    pub position: Option<SourcePosition>,
+}
+
+impl ErrorLocation
+{
+    pub fn is_empty(&self) -> bool { self.path.is_empty() && self.position.is_none() }
+}
+
+impl Display for ErrorLocation
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match (self.path.is_empty(), &self.position)
+        {
+            (true, None) => Ok(()),
+            (true, Some(position)) => write!(f, "at {position}"),
+            (false, None) => write!(f, "at `{}`", self.path),
+            (false, Some(position)) => write!(f, "at `{}`, {position}", self.path),
+        }
+    }
+}
+
+/// A line and column in a text input, with a copy of the offending line for the error messages
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SourcePosition
+{
+    /// Start at 1
+    pub line: usize,
+    /// Start at 1, counted in characters
+    pub column: usize,
+    /// In bytes, from the start of the input
+    pub offset: usize,
+    /// The content of the line, without the line break
+    pub source_line: String,
+}
+
+impl SourcePosition
+{
+    /// `None` if the offset is outside the source
+    pub fn from_offset(source: &[u8], offset: usize) -> Option<Self>
+    {
+        let before = source.get(..offset)?;
+        let line_start = before.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        Some(Self {
+            line: 1 + before.iter().filter(|b| **b == b'\n').count(),
+            column: 1 + String::from_utf8_lossy(&before[line_start..]).chars().count(),
+            offset,
+            source_line: Self::line_at(source, line_start),
+        })
+    }
+
+    /// `None` if the line is outside the source. The column is clamped to the end of the line.
+    pub fn from_line_column(source: &[u8], line: usize, column: usize) -> Option<Self>
+    {
+        let line_start = match line
+        {
+            0 => return None,
+            1 => 0,
+            _ => source.iter().enumerate().filter(|(_, b)| **b == b'\n').nth(line - 2)?.0 + 1,
+        };
+        let source_line = Self::line_at(source, line_start);
+        let column_offset = source_line.char_indices().nth(column.saturating_sub(1)).map_or(source_line.len(), |(i, _)| i);
+        Some(Self {
+            line,
+            column: column.max(1),
+            offset: line_start + column_offset,
+            source_line,
+        })
+    }
+
+    fn line_at(source: &[u8], line_start: usize) -> String
+    {
+        let line = &source[line_start..];
+        let line = &line[..line.iter().position(|b| *b == b'\n').unwrap_or(line.len())];
+        String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned()
+    }
+}
+
+impl Display for SourcePosition
+{
+    /// `line 4, column 9` followed by a code frame:
+    ///
+    /// ```text
+    ///   4 |     id: "sword",
+    ///     |         ^
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "line {}, column {}", self.line, self.column)?;
+        let number = self.line.to_string();
+        let margin = " ".repeat(number.len());
+        // Tabs are kept so the caret stay aligned
+        let indent: String = self
+            .source_line
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(f, "\n {number} | {}\n {margin} | {indent}^", self.source_line)
+    }
+}
@@ -0,0 +1,67 @@
+use super::*;
+
+/// Arguments written as extra extensions before the real one, ex: `flat` in `mygrid.flat.ron`.
+///
+/// Multiple arguments can be given, they are separated by a `.`: `mygrid.flat.compact.ron`.
+/// A type declares the arguments it accept with [`SaveExtension::save_custom_extension_args`] / [`LoadExtension::load_custom_extension_args`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ExtensionArgs<'a>
+{
+    args: &'a str,
+}
+
+impl<'a> ExtensionArgs<'a>
+{
+    pub const NONE: Self = Self { args: "" };
+
+    /// Markup are human readable and indented (the default)
+    pub const PRETTY: &'static str = "pretty";
+    /// Markup are written on as few lines as possible
+    pub const COMPACT: &'static str = "compact";
+
+    /// Arguments understood by all the serde formats. The formats that don't care simply ignore them.
+    pub const COMMON: &'static [&'static str] = &[Self::PRETTY, Self::COMPACT];
+
+    /// `args` are separated by a `.`, ex: `flat.compact`
+    pub const fn new(args: &'a str) -> Self { Self { args } }
+
+    pub const fn as_str(self) -> &'a str { self.args }
+    pub const fn is_empty(self) -> bool { self.args.is_empty() }
+
+    pub fn iter(self) -> impl Iterator<Item = &'a str> { self.args.split('.').filter(|arg| !arg.is_empty()) }
+    pub fn contains(self, arg: &str) -> bool { self.iter().any(|a| a == arg) }
+
+    /// Split the arguments from the last extension.
+    ///
+    /// ```
+    /// use hexga_encoding::ExtensionArgs;
+    ///
+    /// assert_eq!(ExtensionArgs::split_extension("flat.compact.ron"), (ExtensionArgs::new("flat.compact"), "ron"));
+    /// assert_eq!(ExtensionArgs::split_extension("ron"), (ExtensionArgs::NONE, "ron"));
+    /// ```
+    pub fn split_extension(extension: &'a extension) -> (Self, &'a extension)
+    {
+        match extension.rsplit_once('.')
+        {
+            Some((args, extension)) => (Self::new(args), extension),
+            None => (Self::NONE, extension),
+        }
+    }
+
+    /// Inverse of [`Self::split_extension`], ex: `flat` and `ron` give `flat.ron`
+    pub fn join_extension(self, extension: &extension) -> Extension
+    {
+        if self.is_empty()
+        {
+            extension.to_owned()
+        }
+        else
+        {
+            format!("{}.{extension}", self.args)
+        }
+    }
+}
+impl<'a> Display for ExtensionArgs<'a>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { f.write_str(self.args) }
+}
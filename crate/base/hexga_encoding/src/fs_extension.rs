@@ -18,6 +18,12 @@ pub trait CommonExtensions
     const RON: &'static str = "ron";
     const JSON: &'static str = "json";
     const XML: &'static str = "xml";
+    const TOML: &'static str = "toml";
+    const YAML: &'static str = "yaml";
+    /// Alternative extension for [`Self::YAML`]
+    const YML: &'static str = "yml";
+
+    const MSGPACK: &'static str = "msgpack";
 
     // Todo: add a cfg flag for the prefered extension
     const PREFERED: &'static str = Self::RON;
@@ -26,6 +32,22 @@ pub trait CommonExtensions
     ///
     /// Not suitable for long-term storage, as the implementation or encoding may change at any time.
     const TMP_BIN: &'static str = "tmp";
+
+    const PNG: &'static str = "png";
+    const JPEG: &'static str = "jpeg";
+    /// Alternative extension for [`Self::JPEG`]
+    const JPG: &'static str = "jpg";
+    const GIF: &'static str = "gif";
+
+    const GZIP: &'static str = "gz";
+    const ZSTD: &'static str = "zst";
+    const DEFLATE: &'static str = "deflate";
+
+    const CRC32: &'static str = "crc";
+    const HMAC: &'static str = "hmac";
+
+    /// A [`Pack`](crate::Pack) archive
+    const PACK: &'static str = "pack";
 }
 
 impl CommonExtensions for Extension {}
@@ -25,15 +25,40 @@ mod default_impl;
 mod result;
 pub use result::*;
 
+mod error_location;
+pub use error_location::*;
+
 mod save;
 pub use save::*;
 
 mod load;
 pub use load::*;
 
+mod decode_limits;
+pub use decode_limits::*;
+
+mod sniff;
+pub use sniff::*;
+
+mod extension_args;
+pub use extension_args::*;
+
 mod base64;
 pub use base64::*;
 
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+pub use compression::*;
+
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "checksum")]
+pub use checksum::*;
+
+mod pack;
+pub use pack::*;
+
 #[cfg(feature = "derive")]
 pub use hexga_encoding_derive::*;
 
@@ -47,7 +72,17 @@ pub use url_data::*;
 
 pub mod prelude
 {
-    pub use super::{fs_extension::prelude::*, load::prelude::*, result::*, save::prelude::*, url_data::prelude::*};
+    pub use super::{
+        decode_limits::{DecodeLimit, DecodeLimits},
+        error_location::{ErrorLocation, SourcePosition},
+        extension_args::ExtensionArgs,
+        fs_extension::prelude::*,
+        load::prelude::*,
+        result::*,
+        save::prelude::*,
+        sniff::sniff_extension,
+        url_data::prelude::*,
+    };
 
     #[cfg(feature = "serde")]
     pub use super::serde_impl::prelude::*;
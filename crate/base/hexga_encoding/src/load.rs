@@ -2,20 +2,54 @@ use super::*;
 
 pub(crate) mod prelude
 {
+    #[cfg(feature = "serde")]
+    pub use super::LoadBorrowed;
     pub use super::{Load, LoadExtension, LoadFrom};
 }
 
 pub trait LoadExtension
 {
     fn load_custom_extensions() -> impl Iterator<Item = &'static extension> { std::iter::empty() }
-    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>) -> EncodeResult<Self>
+    /// Arguments accepted before the extension, ex: `flat` for `mygrid.flat.ron`. See [`ExtensionArgs`].
+    fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { std::iter::empty() }
+    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: Read,
     {
-        let _ = (reader, extension);
+        let _ = (reader, extension, args);
         Err(EncodeError::Unimplemented)
     }
+    /// Decode with a serde format. Use [`Self::load_from_bytes_borrowed_with_format`] by default, override this one instead.
+    #[cfg(feature = "serde")]
+    fn load_from_bytes_with_format(bytes: &[u8], format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + for<'de> Deserialize<'de>,
+    {
+        Self::load_from_bytes_borrowed_with_format(bytes, format, limits, args)
+    }
+    /// Decode with a serde format, ex: `#[io(version = N)]` read a [`VersionedLoad`] envelope.
+    /// Used by [`Load`] and [`LoadBorrowed`], so the value can borrow from `bytes`.
+    ///
+    /// The `args` were already checked against the custom and the [common](ExtensionArgs::COMMON) extension arguments.
+    #[cfg(feature = "serde")]
+    fn load_from_bytes_borrowed_with_format<'a>(bytes: &'a [u8], format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + Deserialize<'a>,
+    {
+        let _ = args;
+        format.from_bytes_with_limits(bytes, limits)
+    }
+    /// Same as [`Self::load_from_bytes_with_format`], for the [streaming formats](AnyFormat::is_streaming).
+    #[cfg(feature = "serde")]
+    fn load_from_reader_with_format<R>(reader: R, format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        let _ = args;
+        format.from_reader_with_limits(reader, limits)
+    }
 }
 
 pub trait LoadExtensionBytes: LoadExtension
@@ -24,7 +58,7 @@ pub trait LoadExtensionBytes: LoadExtension
     where
         Self: Sized,
     {
-        Self::load_from_reader_with_custom_extension(bytes, extension)
+        Self::load_from_reader_with_custom_extension(bytes, extension, ExtensionArgs::NONE)
     }
 }
 impl<T> LoadExtensionBytes for T where T: LoadExtension {}
@@ -39,42 +73,281 @@ pub trait Load: LoadExtension + for<'de> CfgDeserialize<'de>
         #[cfg(not(feature = "serde"))]
         return Self::load_custom_extensions();
     }
+    fn load_extension_args() -> impl Iterator<Item = &'static str>
+    {
+        #[cfg(feature = "serde")]
+        return Self::load_custom_extension_args().chain(ExtensionArgs::COMMON.iter().copied());
+
+        #[cfg(not(feature = "serde"))]
+        return Self::load_custom_extension_args();
+    }
     fn load_prefered_extension() -> Option<&'static extension> { Self::load_custom_extensions().next() }
 
     fn load_from_bytes(bytes: &[u8], extension: Option<&extension>) -> EncodeResult<Self>
     where
         Self: Sized,
     {
-        Self::load_from_reader(bytes, extension)
+        Self::load_from_bytes_with_limits(bytes, extension, DecodeLimits::DEFAULT)
     }
 
+    /// Load with the [`DecodeLimits::DEFAULT`] limits.
     fn load_from_reader<R>(reader: R, extension: Option<&extension>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: Read,
     {
-        if Self::load_custom_extensions().any(|e| Some(e) == extension)
+        Self::load_from_reader_with_limits(reader, extension, DecodeLimits::DEFAULT)
+    }
+
+    /// Load an untrusted input: fail with [`EncodeError::LimitExceeded`] instead of blowing the stack or allocating unbounded memory.
+    ///
+    /// When the extension is missing or does not match the content, the format is detected with [`sniff_extension`].
+    fn load_from_bytes_with_limits(bytes: &[u8], extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<Self>
+    where
+        Self: Sized,
+    {
+        limits.check(DecodeLimit::Bytes, bytes.len())?;
+
+        let extension = match extension
         {
-            return Self::load_from_reader_with_custom_extension(reader, extension);
+            Some(extension) => extension,
+            None => match sniff_extension(bytes)
+            {
+                Some(detected) if !load_supports::<Self>(detected) => return Err(EncodeError::load_unsupported_extension::<Self>(Some(detected.into()))),
+                Some(detected) => detected,
+                None => return load_from_bytes_with_extension(bytes, None, limits),
+            },
+        };
+
+        match load_from_bytes_with_extension(bytes, Some(extension), limits)
+        {
+            Err(err) if !matches!(err, EncodeError::LimitExceeded { .. } | EncodeError::Corrupted { .. }) =>
+            {
+                let (args, _) = ExtensionArgs::split_extension(extension);
+                retry_sniffed(bytes, extension, args, err, load_supports::<Self>, |detected| {
+                    load_from_bytes_with_extension(bytes, Some(detected), limits)
+                })
+            }
+            result => result,
         }
+    }
 
+    /// Decode while reading when the extension is a [streaming format](AnyFormat::is_streaming) (`ron`, `json`, `tmp`, `msgpack`),
+    /// with its [arguments](ExtensionArgs) (`flat.ron`). The extension is then trusted and the format is not [sniffed](sniff_extension).
+    ///
+    /// Otherwise read everything up to [`DecodeLimits::max_bytes`], then see [`Self::load_from_bytes_with_limits`].
+    fn load_from_reader_with_limits<R>(reader: R, extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<Self>
+    where
+        Self: Sized,
+        R: Read,
+    {
         #[cfg(feature = "serde")]
+        if let Some((args, format)) = extension
+            .map(ExtensionArgs::split_extension)
+            .filter(|(_, inner)| !Self::load_custom_extensions().any(|e| e == *inner))
+            .and_then(|(args, inner)| Some((args, AnyFormat::try_from(inner).ok()?)))
+            .filter(|(_, format)| format.is_streaming())
         {
-            let format = match extension
+            if let Some(arg) = args.iter().find(|arg| !Self::load_extension_args().any(|a| a == *arg))
             {
-                Some(ex) => AnyFormat::try_from(ex).ok(),
-                None => None,
+                return Err(EncodeError::load_unsupported_extension_arg::<Self>(arg.to_owned()));
             }
-            .unwrap_or_default();
-            return format.from_reader(reader);
+            return Self::load_from_reader_with_format(reader, format, limits, args);
         }
+        Self::load_from_bytes_with_limits(&limits.read_to_end(reader)?, extension, limits)
+    }
+
+    /// Decode the elements of a top-level sequence one at a time, with the [`DecodeLimits::DEFAULT`] limits.
+    ///
+    /// ```
+    /// use hexga_encoding::prelude::*;
+    ///
+    /// let names : Vec<String> = String::load_iter("[\"a\", \"b\"]".as_bytes(), Some("ron")).unwrap().collect::<Result<_,_>>().unwrap();
+    /// assert_eq!(names, ["a", "b"]);
+    /// ```
+    #[cfg(feature = "serde")]
+    fn load_iter<R>(reader: R, extension: Option<&extension>) -> EncodeResult<LoadIter<Self, R>>
+    where
+        Self: Sized,
+        R: Read,
+    {
+        Self::load_iter_with_limits(reader, extension, DecodeLimits::DEFAULT)
+    }
 
-        #[allow(unreachable_code)]
-        Err(EncodeError::load_unsupported_extension::<Self>(extension.map(Into::into)))
+    /// Ron, Json and TmpBin are read while iterating, so only one element is in memory at a time.
+    /// The other formats, the compressed data and the [custom extensions](LoadExtension::load_custom_extensions) are loaded as a `Vec<Self>` first.
+    ///
+    /// The [`DecodeLimits::max_len`] limit the number of elements, the other limits apply to the whole input.
+    #[cfg(feature = "serde")]
+    fn load_iter_with_limits<R>(reader: R, extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<LoadIter<Self, R>>
+    where
+        Self: Sized,
+        R: Read,
+    {
+        LoadIter::new(reader, extension, limits)
+    }
+
+    /// Load a [`Checksum::Hmac`] envelope signed with `key`, ex: saved with [`Save::save_to_writer_signed`].
+    ///
+    /// Fail with [`EncodeError::Corrupted`] if the data was modified or signed with another key.
+    #[cfg(feature = "checksum")]
+    fn load_from_bytes_signed(bytes: &[u8], extension: Option<&extension>, key: &[u8]) -> EncodeResult<Self>
+    where
+        Self: Sized,
+    {
+        let inner = match extension.and_then(Checksum::split_extension)
+        {
+            Some((inner, Checksum::Hmac)) => inner,
+            _ => extension,
+        };
+        Self::load_from_bytes(Checksum::Hmac.open(bytes, Some(key))?, inner)
+    }
+
+    /// Read everything up to [`DecodeLimits::DEFAULT`], then see [`Self::load_from_bytes_signed`].
+    #[cfg(feature = "checksum")]
+    fn load_from_reader_signed<R>(reader: R, extension: Option<&extension>, key: &[u8]) -> EncodeResult<Self>
+    where
+        Self: Sized,
+        R: Read,
+    {
+        Self::load_from_bytes_signed(&DecodeLimits::DEFAULT.read_to_end(reader)?, extension, key)
     }
 }
 impl<T> Load for T where T: LoadExtension + for<'de> CfgDeserialize<'de> + ?Sized {}
 
+/// Load a value that borrow its strings and bytes (`&'a str`, `&'a [u8]`) from the input, instead of copying them.
+///
+/// Useful for the big blobs already in memory, ex: the bytes cached by the io.
+/// TmpBin always borrow. Json, Ron and MsgPack borrow the strings without escape sequences,
+/// the other formats only give owned values, so borrowing fail for them.
+///
+/// The [`Checksum`](crate::Checksum) envelopes are opened in place, the compressed data can't be borrowed.
+/// The extension arguments and `#[io(version = N)]` are supported, see [`LoadExtension::load_from_bytes_borrowed_with_format`].
+///
+/// ```
+/// use hexga_encoding::prelude::*;
+///
+/// let bytes = "bytes".to_tmp_bin().unwrap();
+/// let borrowed : &str = <&str>::load_from_bytes_borrowed(&bytes, Some("tmp")).unwrap();
+/// assert_eq!(borrowed, "bytes");
+/// ```
+#[cfg(feature = "serde")]
+pub trait LoadBorrowed<'a>: Deserialize<'a> + LoadExtension
+{
+    /// Load with the [`DecodeLimits::DEFAULT`] limits.
+    fn load_from_bytes_borrowed(bytes: &'a [u8], extension: Option<&extension>) -> EncodeResult<Self>
+    {
+        Self::load_from_bytes_borrowed_with_limits(bytes, extension, DecodeLimits::DEFAULT)
+    }
+
+    /// When the extension is missing, the format is detected with [`sniff_extension`], or is the default one.
+    fn load_from_bytes_borrowed_with_limits(bytes: &'a [u8], extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<Self>
+    {
+        limits.check(DecodeLimit::Bytes, bytes.len())?;
+        let extension = extension.or_else(|| sniff_extension(bytes));
+
+        #[cfg(feature = "checksum")]
+        if let Some((inner, checksum)) = extension.and_then(Checksum::split_extension)
+        {
+            return Self::load_from_bytes_borrowed_with_limits(checksum.open(bytes, None)?, inner, limits);
+        }
+
+        let (args, format) = match extension.map(ExtensionArgs::split_extension)
+        {
+            Some((args, inner)) => (args, AnyFormat::try_from(inner)),
+            None => (ExtensionArgs::NONE, Ok(AnyFormat::default())),
+        };
+        match format
+        {
+            Ok(format) =>
+            {
+                let supported = || Self::load_custom_extension_args().chain(ExtensionArgs::COMMON.iter().copied());
+                if let Some(arg) = args.iter().find(|arg| !supported().any(|a| a == *arg))
+                {
+                    return Err(EncodeError::UnsupportedExtensionArg {
+                        got: arg.to_owned().into(),
+                        expected: supported().map(|arg| arg.into()).collect(),
+                    });
+                }
+                Self::load_from_bytes_borrowed_with_format(bytes, format, limits, args)
+            }
+            Err(_) =>
+            {
+                #[allow(unused_mut)]
+                let mut expected: std::collections::HashSet<CowExtensionStatic> = AnyFormat::ALL.iter().map(|f| f.extension().into()).collect();
+                #[cfg(feature = "checksum")]
+                expected.extend(Checksum::ALL.iter().map(|c| c.extension().into()));
+                Err(EncodeError::UnsupportedExtension {
+                    got: extension.map(|e| e.to_owned().into()),
+                    expected,
+                })
+            }
+        }
+    }
+}
+#[cfg(feature = "serde")]
+impl<'a, T> LoadBorrowed<'a> for T where T: Deserialize<'a> + LoadExtension {}
+
+/// The last part of the extension is a supported format, a compression or a checksum, ex: `ron` for `flat.ron` or `gz` for `ron.gz`
+fn load_supports<T: Load>(extension: &extension) -> bool
+{
+    let last = extension.rsplit('.').next().unwrap_or(extension);
+    #[cfg(feature = "compression")]
+    if Compression::try_from(last).is_ok()
+    {
+        return true;
+    }
+    #[cfg(feature = "checksum")]
+    if Checksum::try_from(last).is_ok()
+    {
+        return true;
+    }
+    T::load_extensions().any(|e| e == last)
+}
+
+fn load_from_bytes_with_extension<T: Load>(bytes: &[u8], extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<T>
+{
+    if T::load_custom_extensions().any(|e| Some(e) == extension)
+    {
+        return T::load_from_reader_with_custom_extension(bytes, extension, ExtensionArgs::NONE);
+    }
+
+    #[cfg(feature = "checksum")]
+    if let Some((inner, checksum)) = extension.and_then(Checksum::split_extension)
+    {
+        return T::load_from_bytes_with_limits(checksum.open(bytes, None)?, inner, limits);
+    }
+
+    #[cfg(feature = "compression")]
+    if let Some((inner, compression)) = extension.and_then(Compression::split_extension)
+    {
+        return T::load_from_bytes_with_limits(&compression.decompress_with_limits(bytes, limits)?, inner, limits);
+    }
+
+    let (args, inner) = match extension
+    {
+        Some(ex) => ExtensionArgs::split_extension(ex),
+        None => (ExtensionArgs::NONE, ""),
+    };
+    if let Some(arg) = args.iter().find(|arg| !T::load_extension_args().any(|a| a == *arg))
+    {
+        return Err(EncodeError::load_unsupported_extension_arg::<T>(arg.to_owned()));
+    }
+    if !args.is_empty() && T::load_custom_extensions().any(|e| e == inner)
+    {
+        return T::load_from_reader_with_custom_extension(bytes, Some(inner), args);
+    }
+
+    #[cfg(feature = "serde")]
+    {
+        let format = AnyFormat::try_from(inner).unwrap_or_default();
+        return T::load_from_bytes_with_format(bytes, format, limits, args);
+    }
+
+    #[allow(unreachable_code)]
+    Err(EncodeError::load_unsupported_extension::<T>(extension.map(|e| e.to_owned().into())))
+}
+
 pub trait LoadFrom: From<Self::Source>
 {
     type Source: LoadExtension + Into<Self>;
@@ -82,13 +355,30 @@ pub trait LoadFrom: From<Self::Source>
 impl<S> LoadExtension for S
 where
     S: LoadFrom,
+    S::Source: for<'de> CfgDeserialize<'de>,
 {
     fn load_custom_extensions() -> impl Iterator<Item = &'static extension> { S::Source::load_custom_extensions() }
-    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>) -> EncodeResult<Self>
+    fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { S::Source::load_custom_extension_args() }
+    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: Read,
     {
-        S::Source::load_from_reader_with_custom_extension(reader, extension).map(|v| v.into())
+        S::Source::load_from_reader_with_custom_extension(reader, extension, args).map(|v| v.into())
+    }
+    #[cfg(feature = "serde")]
+    fn load_from_bytes_borrowed_with_format<'a>(bytes: &'a [u8], format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + Deserialize<'a>,
+    {
+        S::Source::load_from_bytes_with_format(bytes, format, limits, args).map(|v| v.into())
+    }
+    #[cfg(feature = "serde")]
+    fn load_from_reader_with_format<R>(reader: R, format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        S::Source::load_from_reader_with_format(reader, format, limits, args).map(|v| v.into())
     }
 }
@@ -0,0 +1,255 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::io::{Seek, SeekFrom};
+use std::sync::Arc;
+
+/// An archive bundling many files in one, ex: to ship hundreds of small assets as a single `assets.pack`.
+///
+/// Only the index is read when opening, each entry is then read on demand by seeking to it.
+///
+/// The layout is `HXPK`, the version, the data of the entries, the index (path, offset and length of each entry),
+/// then the offset of the index (u64 little endian).
+///
+/// The entries paths use `/` as separator. Their extension is everything after the first `.` of the file name,
+/// ex: `ron.gz` for `levels/intro.ron.gz`.
+///
+/// ```
+/// use hexga_encoding::{Pack, PackWriter};
+/// use std::io::Cursor;
+///
+/// let mut writer = PackWriter::new(Vec::new()).unwrap();
+/// writer.add("levels/intro.json", &vec![1, 2, 3]).unwrap();
+/// writer.add_bytes("readme.txt", b"hello").unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// let mut pack = Pack::open(Cursor::new(bytes)).unwrap();
+/// assert_eq!(pack.read_bytes("readme.txt").unwrap(), b"hello");
+/// assert_eq!(pack.load::<Vec<i32>>("levels/intro.json").unwrap(), [1, 2, 3]);
+/// ```
+pub struct Pack<R>
+{
+    reader: R,
+    /// Shared with [`Self::with_reader`]
+    entries: Arc<BTreeMap<String, PackEntry>>,
+}
+
+/// Where the data of an entry is in the [`Pack`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackEntry
+{
+    pub offset: u64,
+    pub len: u64,
+}
+
+const MAGIC: &[u8] = b"HXPK";
+const VERSION: u8 = 1;
+const HEADER_LEN: u64 = MAGIC.len() as u64 + 1;
+const FOOTER_LEN: u64 = size_of::<u64>() as u64;
+
+fn pack_error(reason: impl Display) -> EncodeError { EncodeError::markup::<PackEntry>(Extension::PACK, reason) }
+
+/// The extension of an entry, everything after the first `.` of the file name
+fn entry_extension(path: &str) -> Option<&extension>
+{
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.split_once('.').map(|(_, extension)| extension).filter(|extension| !extension.is_empty())
+}
+
+impl<R> Pack<R>
+where
+    R: Read + Seek,
+{
+    /// Read the index. The entries are not read.
+    pub fn open(mut reader: R) -> EncodeResult<Self>
+    {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut header = [0; HEADER_LEN as usize];
+        reader.read_exact(&mut header).map_err(|_| pack_error("missing header"))?;
+        if !header.starts_with(MAGIC)
+        {
+            return Err(pack_error("missing header"));
+        }
+        let version = header[MAGIC.len()];
+        if version != VERSION
+        {
+            return Err(pack_error(format!("unsupported version {version}, expected {VERSION}")));
+        }
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        let index_end = len
+            .checked_sub(FOOTER_LEN)
+            .filter(|end| *end >= HEADER_LEN)
+            .ok_or_else(|| pack_error("missing index"))?;
+        reader.seek(SeekFrom::Start(index_end))?;
+        let index_offset = read_u64(&mut reader)?;
+        if !(HEADER_LEN..=index_end).contains(&index_offset)
+        {
+            return Err(pack_error(format!("invalid index offset {index_offset}")));
+        }
+
+        reader.seek(SeekFrom::Start(index_offset))?;
+        let mut index = Vec::new();
+        (&mut reader).take(index_end - index_offset).read_to_end(&mut index)?;
+        let entries = Self::read_index(&index, index_offset)?;
+
+        Ok(Self {
+            reader,
+            entries: Arc::new(entries),
+        })
+    }
+
+    fn read_index(mut index: &[u8], data_end: u64) -> EncodeResult<BTreeMap<String, PackEntry>>
+    {
+        let count = read_u64(&mut index)?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..count
+        {
+            let path_len = read_u64(&mut index)?;
+            let (path, remaining) = index
+                .split_at_checked(usize::try_from(path_len).unwrap_or(usize::MAX))
+                .ok_or_else(|| pack_error("truncated index"))?;
+            index = remaining;
+            let path = str::from_utf8(path)?.to_owned();
+            let entry = PackEntry {
+                offset: read_u64(&mut index)?,
+                len: read_u64(&mut index)?,
+            };
+            if entry.offset < HEADER_LEN || entry.offset.checked_add(entry.len).is_none_or(|end| end > data_end)
+            {
+                return Err(pack_error(format!("entry {path:?} is out of bounds")));
+            }
+            entries.insert(path, entry);
+        }
+        if !index.is_empty()
+        {
+            return Err(pack_error("unexpected trailing bytes in the index"));
+        }
+        Ok(entries)
+    }
+
+    /// Read the bytes of one entry
+    pub fn read_bytes(&mut self, path: &str) -> EncodeResult<Vec<u8>>
+    {
+        let entry = self.entry(path).ok_or_else(|| pack_error(format!("no entry {path:?}")))?;
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let mut bytes = Vec::with_capacity(entry.len as usize);
+        (&mut self.reader).take(entry.len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != entry.len
+        {
+            return Err(pack_error(format!("entry {path:?} is truncated")));
+        }
+        Ok(bytes)
+    }
+
+    /// Load one entry, using the extension of its path
+    pub fn load<T>(&mut self, path: &str) -> EncodeResult<T>
+    where
+        T: Load,
+    {
+        T::load_from_bytes(&self.read_bytes(path)?, entry_extension(path))
+    }
+}
+
+impl<R> Pack<R>
+{
+    pub fn entry(&self, path: &str) -> Option<PackEntry> { self.entries.get(path).copied() }
+    pub fn contains(&self, path: &str) -> bool { self.entries.contains_key(path) }
+
+    /// The paths of the entries, sorted
+    pub fn paths(&self) -> impl Iterator<Item = &str> { self.entries.keys().map(|path| path.as_str()) }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    pub fn into_inner(self) -> R { self.reader }
+
+    /// The same index with another reader of the same pack, without reading the index again.
+    ///
+    /// Ex: to keep the index and reopen the file for each entry.
+    pub fn with_reader<R2>(&self, reader: R2) -> Pack<R2>
+    {
+        Pack {
+            reader,
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> EncodeResult<u64>
+{
+    let mut bytes = [0; size_of::<u64>()];
+    reader.read_exact(&mut bytes).map_err(|_| pack_error("truncated index"))?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Write a [`Pack`]: the entries are written as they are added, then [`Self::finish`] write the index.
+pub struct PackWriter<W>
+where
+    W: Write,
+{
+    writer: W,
+    entries: BTreeMap<String, PackEntry>,
+    offset: u64,
+}
+
+impl<W> PackWriter<W>
+where
+    W: Write,
+{
+    pub fn new(mut writer: W) -> EncodeResult<Self>
+    {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        Ok(Self {
+            writer,
+            entries: BTreeMap::new(),
+            offset: HEADER_LEN,
+        })
+    }
+
+    pub fn add_bytes(&mut self, path: &str, bytes: &[u8]) -> EncodeResult
+    {
+        if path.is_empty() || path.starts_with('/') || path.contains('\\')
+        {
+            return Err(pack_error(format!("invalid entry path {path:?}, expected a relative path with `/` separators")));
+        }
+        if self.entries.contains_key(path)
+        {
+            return Err(pack_error(format!("duplicated entry {path:?}")));
+        }
+        self.writer.write_all(bytes)?;
+        let entry = PackEntry {
+            offset: self.offset,
+            len: bytes.len() as u64,
+        };
+        self.offset += entry.len;
+        self.entries.insert(path.to_owned(), entry);
+        Ok(())
+    }
+
+    /// Save `value` using the extension of the `path`
+    pub fn add<T>(&mut self, path: &str, value: &T) -> EncodeResult
+    where
+        T: Save + ?Sized,
+    {
+        let (bytes, _) = value.save_to_bytes(entry_extension(path))?;
+        self.add_bytes(path, &bytes)
+    }
+
+    /// Write the index, and return the writer
+    pub fn finish(mut self) -> EncodeResult<W>
+    {
+        let index_offset = self.offset;
+        self.writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for (path, entry) in &self.entries
+        {
+            self.writer.write_all(&(path.len() as u64).to_le_bytes())?;
+            self.writer.write_all(path.as_bytes())?;
+            self.writer.write_all(&entry.offset.to_le_bytes())?;
+            self.writer.write_all(&entry.len.to_le_bytes())?;
+        }
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
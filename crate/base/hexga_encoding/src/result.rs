@@ -18,10 +18,14 @@ pub enum EncodeError
     NotPersistant,
     NotLoaded,
     Unimplemented,
+    /// Build it with [`Self::markup`] or [`Self::markup_at`]. More fields can be added, so match it with `..`
+    #[non_exhaustive]
     Markup
     {
         extension: CowExtensionStatic,
         reason: Reason,
+        /// The field path and the line/column of the error, when known
+        location: Option<Box<ErrorLocation>>,
     },
     Utf8Error
     {
@@ -33,6 +37,29 @@ pub enum EncodeError
         got: Option<CowExtensionStatic>,
         expected: HashSet<CowExtensionStatic>,
     },
+    /// An [`ExtensionArgs`] argument not supported by the type
+    UnsupportedExtensionArg
+    {
+        got: Reason,
+        expected: HashSet<Reason>,
+    },
+    /// One of the [`DecodeLimits`] was exceeded while loading
+    LimitExceeded
+    {
+        limit: DecodeLimit,
+        max: usize,
+    },
+    /// The content was recognized as another format than the one of the extension, see [`sniff_extension`]
+    ExtensionMismatch
+    {
+        extension: CowExtensionStatic,
+        detected: CowExtensionStatic,
+    },
+    /// The integrity check of a [`Checksum`](crate::Checksum) envelope failed: truncated, damaged or tampered data
+    Corrupted
+    {
+        reason: Reason,
+    },
     Custom(Reason),
     Base64(Base64Error),
     Std(std::io::ErrorKind),
@@ -83,9 +110,14 @@ impl Display for EncodeError
     {
         match self
         {
-            EncodeError::Markup { extension, reason } =>
+            EncodeError::Markup { extension, reason, location } =>
             {
-                write!(f, "failed to convert to {extension} : {reason}")
+                write!(f, "failed to convert to {extension} : {reason}")?;
+                match location
+                {
+                    Some(location) if !location.is_empty() => write!(f, " {location}"),
+                    _ => Ok(()),
+                }
             }
             EncodeError::Utf8Error { valid_up_to, error_len } =>
             {
@@ -100,6 +132,10 @@ impl Display for EncodeError
                 }
             }
             EncodeError::UnsupportedExtension { got, expected } => write!(f, "unsupported extension {got:?}, expected one of {expected:?}"),
+            EncodeError::UnsupportedExtensionArg { got, expected } => write!(f, "unsupported extension argument {got:?}, expected one of {expected:?}"),
+            EncodeError::LimitExceeded { limit, max } => write!(f, "decode limit exceeded: more than {max} {limit}"),
+            EncodeError::ExtensionMismatch { extension, detected } => write!(f, "the content looks like {detected:?}, not {extension:?}"),
+            EncodeError::Corrupted { reason } => write!(f, "corrupted data: {reason}"),
             EncodeError::Custom(reason) => write!(f, "custom: {}", reason),
             EncodeError::Unknow => write!(f, "unknow"),
             EncodeError::Base64(base64) => write!(f, "base64: {}", base64),
@@ -146,12 +182,48 @@ impl EncodeError
         Self::load_unsupported_extension_with_name::<T>(got, std::any::type_name::<T>())
     }
 
+    pub fn save_unsupported_extension_arg<T: Save + ?Sized>(got: impl Into<Reason>) -> Self
+    {
+        Self::UnsupportedExtensionArg {
+            got: got.into(),
+            expected: T::save_extension_args().map(|arg| arg.into()).collect(),
+        }
+    }
+    pub fn load_unsupported_extension_arg<T: Load>(got: impl Into<Reason>) -> Self
+    {
+        Self::UnsupportedExtensionArg {
+            got: got.into(),
+            expected: T::load_extension_args().map(|arg| arg.into()).collect(),
+        }
+    }
+
     pub fn markup<T: ?Sized>(extension: impl Into<CowExtensionStatic>, reason: impl Display) -> Self
     {
         Self::Markup {
             //name: std::any::type_name::<T>().to_owned(),
             extension: extension.into(),
             reason: reason.to_string().into(),
+            location: None,
+        }
+    }
+
+    /// Same as [`Self::markup`], with the [`ErrorLocation`] of the error
+    pub fn markup_at<T: ?Sized>(extension: impl Into<CowExtensionStatic>, reason: impl Display, location: ErrorLocation) -> Self
+    {
+        Self::Markup {
+            extension: extension.into(),
+            reason: reason.to_string().into(),
+            location: (!location.is_empty()).then(|| Box::new(location)),
+        }
+    }
+
+    /// The location of a [`Self::Markup`] error
+    pub fn location(&self) -> Option<&ErrorLocation>
+    {
+        match self
+        {
+            Self::Markup { location, .. } => location.as_deref(),
+            _ => None,
         }
     }
 
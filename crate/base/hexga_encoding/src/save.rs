@@ -8,13 +8,23 @@ pub(crate) mod prelude
 pub trait SaveExtension: CfgSerialize
 {
     fn save_custom_extensions() -> impl Iterator<Item = &'static extension> { std::iter::empty() }
-    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>) -> EncodeResult
+    /// Arguments accepted before the extension, ex: `flat` for `mygrid.flat.ron`. See [`ExtensionArgs`].
+    fn save_custom_extension_args() -> impl Iterator<Item = &'static str> { std::iter::empty() }
+    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult
     where
         W: Write,
     {
-        let _ = (writer, extension);
+        let _ = (writer, extension, args);
         Err(EncodeError::Unimplemented)
     }
+    /// Encode with a serde format, ex: `#[io(version = N)]` wrap the value in a [`VersionedSave`] envelope.
+    #[cfg(feature = "serde")]
+    fn save_to_writer_with_format<W>(&self, writer: W, format: AnyFormat, args: ExtensionArgs<'_>) -> EncodeResult
+    where
+        W: Write,
+    {
+        format.encode_with_writer_and_args(&self, writer, args)
+    }
 }
 
 pub trait SaveExtensionBytes: SaveExtension
@@ -25,7 +35,7 @@ pub trait SaveExtensionBytes: SaveExtension
     }
     fn save_to_bytes_with_custom_extension_in(&self, mut bytes: Vec<u8>, extension: Option<&extension>) -> EncodeResult<Vec<u8>>
     {
-        self.save_to_writer_with_custom_extension(&mut bytes, extension)?;
+        self.save_to_writer_with_custom_extension(&mut bytes, extension, ExtensionArgs::NONE)?;
         Ok(bytes)
     }
 }
@@ -43,6 +53,14 @@ pub trait Save: SaveExtension
         #[cfg(not(feature = "serde"))]
         return Self::save_custom_extensions();
     }
+    fn save_extension_args() -> impl Iterator<Item = &'static str>
+    {
+        #[cfg(feature = "serde")]
+        return Self::save_custom_extension_args().chain(ExtensionArgs::COMMON.iter().copied());
+
+        #[cfg(not(feature = "serde"))]
+        return Self::save_custom_extension_args();
+    }
     fn save_prefered_extension() -> Option<&'static extension> { Self::save_custom_extensions().next() }
 
     fn save_to_bytes<'ext>(&self, extension: Option<&'ext extension>) -> EncodeResult<(Vec<u8>, Option<DeducedExtension<'ext>>)>
@@ -60,30 +78,82 @@ pub trait Save: SaveExtension
     {
         if Self::save_custom_extensions().any(|e| Some(e) == extension)
         {
-            return self.save_to_writer_with_custom_extension(writer, extension).map(|_| extension.map(Into::into));
+            return self
+                .save_to_writer_with_custom_extension(writer, extension, ExtensionArgs::NONE)
+                .map(|_| extension.map(Into::into));
         }
         if extension.is_none()
             && let Some(ext) = Self::save_prefered_extension()
         {
-            return self.save_to_writer_with_custom_extension(writer, Some(ext)).map(|_| Some(ext.into()));
+            return self
+                .save_to_writer_with_custom_extension(writer, Some(ext), ExtensionArgs::NONE)
+                .map(|_| Some(ext.into()));
+        }
+
+        #[cfg(feature = "checksum")]
+        if let Some((inner, checksum)) = extension.and_then(Checksum::split_extension)
+        {
+            let (bytes, inner) = self.save_to_bytes(inner)?;
+            checksum.seal(&bytes, None, writer)?;
+            return Ok(Some(checksum.join_extension(inner.as_deref()).into()));
+        }
+
+        #[cfg(feature = "compression")]
+        if let Some((inner, compression)) = extension.and_then(Compression::split_extension)
+        {
+            let (bytes, inner) = self.save_to_bytes(inner)?;
+            compression.compress(&bytes, writer)?;
+            return Ok(Some(compression.join_extension(inner.as_deref()).into()));
+        }
+
+        let (args, inner) = match extension
+        {
+            Some(ex) => ExtensionArgs::split_extension(ex),
+            None => (ExtensionArgs::NONE, ""),
+        };
+        if let Some(arg) = args.iter().find(|arg| !Self::save_extension_args().any(|a| a == *arg))
+        {
+            return Err(EncodeError::save_unsupported_extension_arg::<Self>(arg.to_owned()));
+        }
+        if !args.is_empty() && Self::save_custom_extensions().any(|e| e == inner)
+        {
+            return self
+                .save_to_writer_with_custom_extension(writer, Some(inner), args)
+                .map(|_| extension.map(Into::into));
         }
 
         #[cfg(feature = "serde")]
         {
-            let format = match extension
+            // Keep the asked extension when it is an alias of the format (ex: `yml` for `yaml`)
+            let (format, extension) = match AnyFormat::try_from(inner)
             {
-                Some(ex) => AnyFormat::try_from(ex).ok(),
-                None => None,
-            }
-            .unwrap_or_default();
+                Ok(format) => (format, extension.unwrap_or_default().into()),
+                Err(_) => (AnyFormat::default(), args.join_extension(AnyFormat::default().extension()).into()),
+            };
 
-            format.encode_with_writer(&self, writer)?;
-            return Ok(Some(format.extension().into()));
+            self.save_to_writer_with_format(writer, format, args)?;
+            return Ok(Some(extension));
         }
 
         #[allow(unreachable_code)]
         Err(EncodeError::save_unsupported_extension::<Self>(extension.map(Into::into)))
     }
+
+    /// Save in a [`Checksum::Hmac`] envelope signed with `key`, ex: `ron` or `ron.hmac` give `ron.hmac`.
+    #[cfg(feature = "checksum")]
+    fn save_to_writer_signed<'ext, W>(&self, writer: &mut W, extension: Option<&'ext extension>, key: &[u8]) -> EncodeResult<DeducedExtension<'ext>>
+    where
+        W: Write,
+    {
+        let inner = match extension.and_then(Checksum::split_extension)
+        {
+            Some((inner, Checksum::Hmac)) => inner,
+            _ => extension,
+        };
+        let (bytes, inner) = self.save_to_bytes(inner)?;
+        Checksum::Hmac.seal(&bytes, Some(key), writer)?;
+        Ok(Checksum::Hmac.join_extension(inner.as_deref()).into())
+    }
 }
 impl<T> Save for T where T: SaveExtension + ?Sized {}
 
@@ -96,10 +166,18 @@ where
     S: SaveAs + CfgSerialize,
 {
     fn save_custom_extensions() -> impl Iterator<Item = &'static extension> { S::Output::save_custom_extensions() }
-    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>) -> EncodeResult
+    fn save_custom_extension_args() -> impl Iterator<Item = &'static str> { S::Output::save_custom_extension_args() }
+    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult
+    where
+        W: Write,
+    {
+        S::Output::save_to_writer_with_custom_extension(&self.into(), writer, extension, args)
+    }
+    #[cfg(feature = "serde")]
+    fn save_to_writer_with_format<W>(&self, writer: W, format: AnyFormat, args: ExtensionArgs<'_>) -> EncodeResult
     where
         W: Write,
     {
-        S::Output::save_to_writer_with_custom_extension(&self.into(), writer, extension)
+        S::Output::save_to_writer_with_format(&self.into(), writer, format, args)
     }
 }
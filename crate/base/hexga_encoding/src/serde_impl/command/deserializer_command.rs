@@ -0,0 +1,394 @@
+use super::*;
+use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, SeqAccess, VariantAccess};
+use std::str::FromStr;
+
+/// Map the arguments of a command line on a [`Deserialize`] type:
+///
+/// - enums read their variant name from the next argument,
+/// - structs and tuples read their fields from the next arguments (positional),
+/// - sequences read all the remaining arguments,
+/// - options are `None` when there is no argument left.
+pub(crate) struct DeserializerCommand<'a>
+{
+    line: &'a str,
+    tokens: Vec<Token<'a>>,
+    index: usize,
+    /// Start of the last read argument, for the errors
+    last_start: usize,
+    /// The struct field being deserialized, for the errors and the completions
+    argument: Option<&'static str>,
+    /// Only when listing the completions: the index of the argument being typed
+    complete: Option<usize>,
+    pub(crate) completions: Option<Completions>,
+}
+
+impl<'a> DeserializerCommand<'a>
+{
+    pub fn new(line: &'a str) -> EncodeResult<Self> { Ok(Self::with_tokens(line, tokenize(line, false)?, None)) }
+
+    /// Stop at the last argument (or after it if the line end with a whitespace) to list the completions
+    pub fn new_partial(line: &'a str) -> Self
+    {
+        let tokens = tokenize(line, true).unwrap_or_default();
+        let typing = !line.is_empty() && !line.ends_with(char::is_whitespace) && !tokens.is_empty();
+        let complete = if typing { tokens.len() - 1 } else { tokens.len() };
+        Self::with_tokens(line, tokens, Some(complete))
+    }
+
+    fn with_tokens(line: &'a str, tokens: Vec<Token<'a>>, complete: Option<usize>) -> Self
+    {
+        Self {
+            line,
+            tokens,
+            index: 0,
+            last_start: 0,
+            argument: None,
+            complete,
+            completions: None,
+        }
+    }
+
+    /// Check that all the arguments were used
+    pub fn end(&self) -> EncodeResult
+    {
+        match self.tokens.get(self.index)
+        {
+            Some(token) => Err(command_error(self.line, token.start, format!("unexpected argument {:?}", token.text))),
+            None => Ok(()),
+        }
+    }
+
+    /// Add the position of the last read argument to the errors raised by the visitors
+    pub fn map_err(&self, err: EncodeError) -> EncodeError
+    {
+        match err
+        {
+            EncodeError::Custom(reason) => command_error(self.line, self.last_start, reason),
+            err => err,
+        }
+    }
+
+    fn is_completing(&self) -> bool { self.complete == Some(self.index) }
+
+    /// Record the `candidates` starting like the argument being typed, and stop the deserialization
+    fn complete(&mut self, candidates: &[&str]) -> EncodeError
+    {
+        let (start, prefix) = match self.tokens.get(self.index)
+        {
+            Some(token) => (token.start, token.text.as_ref()),
+            None => (self.line.len(), ""),
+        };
+        self.completions = Some(Completions {
+            start,
+            candidates: candidates.iter().filter(|c| c.starts_with(prefix)).map(|c| (*c).to_owned()).collect(),
+            argument: self.argument,
+        });
+        EncodeError::custom("completion")
+    }
+
+    fn next_token(&mut self) -> EncodeResult<&Token<'a>>
+    {
+        if self.is_completing()
+        {
+            return Err(self.complete(&[]));
+        }
+        let Some(token) = self.tokens.get(self.index)
+        else
+        {
+            let reason = match self.argument
+            {
+                Some(argument) => format!("missing argument `{argument}`"),
+                None => "missing argument".to_owned(),
+            };
+            return Err(command_error(self.line, self.line.len(), reason));
+        };
+        self.index += 1;
+        self.last_start = token.start;
+        Ok(token)
+    }
+
+    fn parse<T>(&mut self) -> EncodeResult<T>
+    where
+        T: FromStr,
+    {
+        let line = self.line;
+        let argument = self.argument.map(|a| format!(" for `{a}`")).unwrap_or_default();
+        let token = self.next_token()?;
+        token.text.parse().map_err(|_| {
+            command_error(
+                line,
+                token.start,
+                format!("expected {}{argument}, got {:?}", std::any::type_name::<T>(), token.text),
+            )
+        })
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($fn_name:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $fn_name<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> Deserializer<'de> for &mut DeserializerCommand<'a>
+{
+    type Error = EncodeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(&self.next_token()?.text)
+    }
+
+    deserialize_parse!(
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    );
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_completing()
+        {
+            return Err(self.complete(&["false", "true"]));
+        }
+        visitor.visit_bool(self.parse()?)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(self.next_token()?.text.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.index >= self.tokens.len() && !self.is_completing()
+        {
+            visitor.visit_none()
+        }
+        else
+        {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(CommandSeq::new(self, None, &[]))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(CommandSeq::new(self, Some(len), &[]))
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(command_error(self.line, self.last_start, "maps are not supported in commands"))
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(CommandSeq::new(self, Some(fields.len()), fields))
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_completing()
+        {
+            return Err(self.complete(variants));
+        }
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.next_token()?;
+        visitor.visit_unit()
+    }
+}
+
+/// The positional arguments of a struct or a tuple, or all the remaining arguments for a sequence
+struct CommandSeq<'d, 'a>
+{
+    de: &'d mut DeserializerCommand<'a>,
+    /// `None` for a sequence
+    remaining: Option<usize>,
+    fields: &'static [&'static str],
+    index: usize,
+}
+impl<'d, 'a> CommandSeq<'d, 'a>
+{
+    fn new(de: &'d mut DeserializerCommand<'a>, remaining: Option<usize>, fields: &'static [&'static str]) -> Self
+    {
+        Self {
+            de,
+            remaining,
+            fields,
+            index: 0,
+        }
+    }
+}
+
+impl<'d, 'a, 'de> SeqAccess<'de> for CommandSeq<'d, 'a>
+{
+    type Error = EncodeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match &mut self.remaining
+        {
+            Some(0) => return Ok(None),
+            Some(remaining) => *remaining -= 1,
+            // The missing struct fields are still deserialized, so the options become `None`
+            None if self.de.index >= self.de.tokens.len() && !self.de.is_completing() => return Ok(None),
+            None =>
+            {}
+        }
+        if let Some(field) = self.fields.get(self.index)
+        {
+            self.de.argument = Some(field);
+        }
+        self.index += 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> { self.remaining }
+}
+
+impl<'a, 'de> EnumAccess<'de> for &mut DeserializerCommand<'a>
+{
+    type Error = EncodeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name = self.next_token()?.text.to_string();
+        let variant = seed.deserialize(IntoDeserializer::<EncodeError>::into_deserializer(name))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de> VariantAccess<'de> for &mut DeserializerCommand<'a>
+{
+    type Error = EncodeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> { Ok(()) }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(CommandSeq::new(self, Some(len), &[]))
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(CommandSeq::new(self, Some(fields.len()), fields))
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+mod tokenizer;
+pub(crate) use tokenizer::*;
+
+mod relative;
+pub use relative::*;
+
+mod deserializer_command;
+pub(crate) use deserializer_command::*;
+
+/// The "extension" of the [`EncodeError::Markup`] errors raised while parsing a command
+pub(crate) const COMMAND: &str = "command";
+
+/// Parse a command line typed by the user, ex: `/setblock ~10 ~5 8 grass`.
+///
+/// The arguments are separated by whitespaces and mapped in order:
+/// the enum variants are the command names, the struct fields are the positional arguments.
+/// Arguments with whitespaces can be quoted: `/say "hello world"`.
+///
+/// ```
+/// use hexga_encoding::prelude::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Command
+/// {
+///     Tp { x: Relative<i32>, y: Relative<i32> },
+///     Say { message: String },
+/// }
+///
+/// assert_eq!(
+///     Command::from_command("/tp ~10 4").unwrap(),
+///     Command::Tp { x: Relative::offset(10), y: Relative::absolute(4) }
+/// );
+/// assert_eq!(Command::command_completions("/s").candidates, ["say"]);
+/// ```
+pub trait FromCommand: for<'de> Deserialize<'de>
+{
+    fn from_command(command: &str) -> EncodeResult<Self>
+    {
+        let mut de = DeserializerCommand::new(command)?;
+        let value = Self::deserialize(&mut de).map_err(|err| de.map_err(err))?;
+        de.end()?;
+        Ok(value)
+    }
+
+    /// The possible values of the argument being typed at the end of `partial`
+    fn command_completions(partial: &str) -> Completions
+    {
+        let mut de = DeserializerCommand::new_partial(partial);
+        let _ = Self::deserialize(&mut de);
+        de.completions.unwrap_or(Completions {
+            start: partial.len(),
+            candidates: Vec::new(),
+            argument: None,
+        })
+    }
+}
+impl<T> FromCommand for T where T: for<'de> Deserialize<'de> {}
+
+/// The completions of a partial command line, see [`FromCommand::command_completions`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Completions
+{
+    /// Byte offset of the argument being typed, the candidates replace `partial[start..]`
+    pub start: usize,
+    /// The known values starting like the argument being typed (command names, `true`/`false`...)
+    pub candidates: Vec<String>,
+    /// The name of the struct field being typed, if any
+    pub argument: Option<&'static str>,
+}
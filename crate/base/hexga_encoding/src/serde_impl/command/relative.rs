@@ -0,0 +1,126 @@
+use super::*;
+use serde::de::IntoDeserializer;
+use std::str::FromStr;
+
+/// A number in a command that can be relative to the caller, like in `/setblock ~10 ~ 8 grass`:
+///
+/// - `8` is absolute,
+/// - `~10` is 10 more than the caller,
+/// - `~` is the caller value.
+///
+/// Use [`Relative::resolve`] to get the final value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Relative<T>
+{
+    pub value: T,
+    pub relative: bool,
+}
+
+impl<T> Relative<T>
+{
+    pub const fn absolute(value: T) -> Self { Self { value, relative: false } }
+    pub const fn offset(value: T) -> Self { Self { value, relative: true } }
+
+    pub fn resolve(self, origin: T) -> T
+    where
+        T: std::ops::Add<Output = T>,
+    {
+        if self.relative { origin + self.value } else { self.value }
+    }
+}
+
+impl<T> FromStr for Relative<T>
+where
+    T: FromStr + Default,
+{
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.strip_prefix('~')
+        {
+            Some("") => Ok(Self::offset(T::default())),
+            Some(offset) => offset.parse().map(Self::offset),
+            None => s.parse().map(Self::absolute),
+        }
+    }
+}
+
+impl<T> Display for Relative<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        if self.relative
+        {
+            write!(f, "~")?;
+        }
+        write!(f, "{}", self.value)
+    }
+}
+
+/// The absolute values are saved as `T`, the relative ones as a string: `"~10"`
+impl<T> Serialize for Relative<T>
+where
+    T: Serialize + Display,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.relative
+        {
+            serializer.collect_str(self)
+        }
+        else
+        {
+            self.value.serialize(serializer)
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Relative<T>
+where
+    T: Deserialize<'de> + FromStr + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(RelativeVisitor(std::marker::PhantomData))
+    }
+}
+
+struct RelativeVisitor<T>(std::marker::PhantomData<T>);
+
+macro_rules! visit_absolute {
+    ($($fn_name:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $fn_name<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                T::deserialize(v.into_deserializer()).map(Relative::absolute)
+            }
+        )*
+    };
+}
+
+impl<'de, T> Visitor<'de> for RelativeVisitor<T>
+where
+    T: Deserialize<'de> + FromStr + Default,
+{
+    type Value = Relative<T>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result { write!(formatter, "a number, optionally prefixed by `~` to be relative") }
+
+    visit_absolute!(visit_i64(i64), visit_i128(i128), visit_u64(u64), visit_u128(u128), visit_f64(f64));
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse().map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+    }
+}
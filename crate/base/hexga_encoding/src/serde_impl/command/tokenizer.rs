@@ -0,0 +1,89 @@
+use super::*;
+
+/// A whitespace separated argument of a command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token<'a>
+{
+    pub text: Cow<'a, str>,
+    /// Byte offset in the command line
+    pub start: usize,
+}
+
+/// Split a command line on the whitespaces, keeping the quoted strings (`"..."` or `'...'`) in one token.
+///
+/// The leading `/` is ignored. Inside quotes, `\` escape the next character.
+/// When `partial` is true (completions), an unterminated quote is not an error.
+pub(crate) fn tokenize(line: &str, partial: bool) -> EncodeResult<Vec<Token<'_>>>
+{
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    if let Some((_, '/')) = chars.peek()
+    {
+        chars.next();
+    }
+
+    while let Some(&(start, c)) = chars.peek()
+    {
+        if c.is_whitespace()
+        {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' || c == '\''
+        {
+            chars.next();
+            let mut text = String::new();
+            let mut closed = false;
+            while let Some((_, next)) = chars.next()
+            {
+                match next
+                {
+                    '\\' => text.extend(chars.next().map(|(_, escaped)| escaped)),
+                    _ if next == c =>
+                    {
+                        closed = true;
+                        break;
+                    }
+                    _ => text.push(next),
+                }
+            }
+            if !closed && !partial
+            {
+                return Err(command_error(line, start, "unterminated quoted string"));
+            }
+            tokens.push(Token { text: Cow::Owned(text), start });
+            continue;
+        }
+
+        let mut end = line.len();
+        while let Some(&(i, next)) = chars.peek()
+        {
+            if next.is_whitespace()
+            {
+                end = i;
+                break;
+            }
+            chars.next();
+        }
+        tokens.push(Token {
+            text: Cow::Borrowed(&line[start..end]),
+            start,
+        });
+    }
+    Ok(tokens)
+}
+
+/// An error pointing at the `offset` of the command line
+pub(crate) fn command_error(line: &str, offset: usize, reason: impl Display) -> EncodeError
+{
+    EncodeError::markup_at::<()>(
+        COMMAND,
+        reason,
+        ErrorLocation {
+            path: String::new(),
+            position: SourcePosition::from_offset(line.as_bytes(), offset),
+        },
+    )
+}
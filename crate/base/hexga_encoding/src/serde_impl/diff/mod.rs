@@ -0,0 +1,10 @@
+use super::*;
+
+mod value;
+pub use value::*;
+
+mod serializer_value;
+pub(crate) use serializer_value::*;
+
+mod patch;
+pub use patch::*;
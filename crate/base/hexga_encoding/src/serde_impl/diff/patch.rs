@@ -0,0 +1,255 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// The changes that turn a [`Value`] into another, see [`Diff`].
+///
+/// Sequences, maps, structs, options and enum variants are diffed element by element,
+/// so a single changed tile in a big grid only store this tile.
+///
+/// A patch can be saved like any other value, ex: with [`ToRon::to_ron`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum Patch
+{
+    /// Nothing changed
+    #[default]
+    Keep,
+    /// Replace the whole value
+    Replace(Value),
+    /// Truncate or extend the sequence to `len`, then change some elements by index.
+    ///
+    /// The appended elements are [`Patch::Replace`] with an index equal to the current length.
+    Seq
+    {
+        len: usize, changes: Vec<(usize, Patch)>
+    },
+    /// Change or add some entries by key, and remove the `removed` keys. Also used for the structs fields.
+    Map
+    {
+        changes: Vec<(Value, Patch)>, removed: Vec<Value>
+    },
+    /// Change the content of a `Some`
+    Some(Box<Patch>),
+    /// Change the content of an enum variant, the variant stay the same
+    Variant(Box<Patch>),
+}
+
+impl SaveExtension for Patch {}
+impl LoadExtension for Patch {}
+
+impl Patch
+{
+    pub const fn is_keep(&self) -> bool { matches!(self, Self::Keep) }
+
+    /// The patch that turn `old` into `new`
+    pub fn between(old: &Value, new: &Value) -> Self
+    {
+        if old == new
+        {
+            return Self::Keep;
+        }
+        match (old, new)
+        {
+            (Value::Seq(old), Value::Seq(new)) => Self::between_seq(old, new),
+            (Value::Map(old), Value::Map(new)) => Self::between_map(old, new),
+            (Value::Some(old), Value::Some(new)) => Self::Some(Box::new(Self::between(old, new))),
+            (Value::Variant(old_name, old), Value::Variant(new_name, new)) if old_name == new_name => Self::Variant(Box::new(Self::between(old, new))),
+            _ => Self::Replace(new.clone()),
+        }
+    }
+
+    fn between_seq(old: &[Value], new: &[Value]) -> Self
+    {
+        let changes = new
+            .iter()
+            .enumerate()
+            .filter_map(|(i, new)| match old.get(i)
+            {
+                Some(old) => Some((i, Self::between(old, new))).filter(|(_, patch)| !patch.is_keep()),
+                None => Some((i, Self::Replace(new.clone()))),
+            })
+            .collect();
+        Self::Seq { len: new.len(), changes }
+    }
+
+    fn between_map(old: &[(Value, Value)], new: &[(Value, Value)]) -> Self
+    {
+        let mut index = HashMap::with_capacity(old.len());
+        for (i, (key, _)) in old.iter().enumerate()
+        {
+            index.entry(Key(key)).or_insert(i);
+        }
+        let mut matched = vec![false; old.len()];
+        let mut changes = Vec::new();
+
+        for (key, new) in new
+        {
+            let patch = match index.get(&Key(key))
+            {
+                Some(&i) =>
+                {
+                    matched[i] = true;
+                    Self::between(&old[i].1, new)
+                }
+                None => Self::Replace(new.clone()),
+            };
+            if !patch.is_keep()
+            {
+                changes.push((key.clone(), patch));
+            }
+        }
+
+        let removed = old
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|((key, _), _)| key.clone())
+            .collect();
+        Self::Map { changes, removed }
+    }
+
+    pub fn apply(&self, value: &mut Value) -> EncodeResult
+    {
+        match (self, value)
+        {
+            (Self::Keep, _) => Ok(()),
+            (Self::Replace(new), value) =>
+            {
+                *value = new.clone();
+                Ok(())
+            }
+            (Self::Seq { len, changes }, Value::Seq(values)) =>
+            {
+                values.truncate(*len);
+                for (index, patch) in changes
+                {
+                    let len = values.len();
+                    match (values.get_mut(*index), patch)
+                    {
+                        (Some(value), patch) => patch.apply(value)?,
+                        (None, Self::Replace(new)) if *index == len => values.push(new.clone()),
+                        _ => return Err(EncodeError::custom(format!("patch: no element at index {index}"))),
+                    }
+                }
+                if values.len() != *len
+                {
+                    return Err(EncodeError::custom(format!("patch: expected {len} elements, got {}", values.len())));
+                }
+                Ok(())
+            }
+            (Self::Map { changes, removed }, Value::Map(entries)) =>
+            {
+                let removed = removed.iter().map(Key).collect::<HashSet<_>>();
+                entries.retain(|(key, _)| !removed.contains(&Key(key)));
+
+                // where the changed keys are, by the first change of each key
+                let mut first = HashMap::with_capacity(changes.len());
+                let same_key = changes
+                    .iter()
+                    .enumerate()
+                    .map(|(c, (key, _))| *first.entry(Key(key)).or_insert(c))
+                    .collect::<Vec<_>>();
+                let mut positions = vec![None; changes.len()];
+                for (i, (key, _)) in entries.iter().enumerate()
+                {
+                    if let Some(&c) = first.get(&Key(key))
+                    {
+                        positions[c].get_or_insert(i);
+                    }
+                }
+
+                for ((key, patch), c) in changes.iter().zip(same_key)
+                {
+                    match (positions[c], patch)
+                    {
+                        (Some(i), patch) => patch.apply(&mut entries[i].1)?,
+                        (None, Self::Replace(new)) =>
+                        {
+                            positions[c] = Some(entries.len());
+                            entries.push((key.clone(), new.clone()));
+                        }
+                        _ => return Err(EncodeError::custom(format!("patch: missing key {key:?}"))),
+                    }
+                }
+                Ok(())
+            }
+            (Self::Some(patch), Value::Some(value)) => patch.apply(value),
+            (Self::Variant(patch), Value::Variant(_, value)) => patch.apply(value),
+            (patch, value) => Err(EncodeError::custom(format!("patch: can't apply {patch:?} to {value:?}"))),
+        }
+    }
+}
+
+/// A [`Value`] usable as a key of a `HashMap`, to find the entries of a map without comparing all of them.
+///
+/// A key containing a NaN is never found, like with `==`.
+#[derive(Clone, Copy, PartialEq)]
+struct Key<'a>(&'a Value);
+
+impl<'a> Eq for Key<'a> {}
+
+impl<'a> Hash for Key<'a>
+{
+    fn hash<H: Hasher>(&self, state: &mut H) { hash_value(self.0, state) }
+}
+
+fn hash_value<H: Hasher>(value: &Value, state: &mut H)
+{
+    std::mem::discriminant(value).hash(state);
+    match value
+    {
+        Value::Unit | Value::None =>
+        {}
+        Value::Bool(v) => v.hash(state),
+        Value::I64(v) => v.hash(state),
+        Value::I128(v) => v.hash(state),
+        Value::U64(v) => v.hash(state),
+        Value::U128(v) => v.hash(state),
+        // `0.0 == -0.0`
+        Value::F32(v) => (v + 0.0).to_bits().hash(state),
+        Value::F64(v) => (v + 0.0).to_bits().hash(state),
+        Value::Char(v) => v.hash(state),
+        Value::String(v) => v.hash(state),
+        Value::Bytes(v) => v.hash(state),
+        Value::Some(v) => hash_value(v, state),
+        Value::Seq(values) =>
+        {
+            values.len().hash(state);
+            values.iter().for_each(|v| hash_value(v, state));
+        }
+        Value::Map(entries) =>
+        {
+            entries.len().hash(state);
+            for (key, value) in entries
+            {
+                hash_value(key, state);
+                hash_value(value, state);
+            }
+        }
+        Value::Variant(name, v) =>
+        {
+            name.hash(state);
+            hash_value(v, state);
+        }
+    }
+}
+
+/// Compute and apply [`Patch`]es between two values, ex: to only send the changes over the network.
+pub trait Diff: Serialize + for<'de> Deserialize<'de>
+{
+    /// The patch that turn `self` into `new`
+    fn diff(&self, new: &Self) -> EncodeResult<Patch> { Ok(Patch::between(&Value::from_serialize(self)?, &Value::from_serialize(new)?)) }
+
+    fn apply_patch(&mut self, patch: &Patch) -> EncodeResult
+    {
+        if patch.is_keep()
+        {
+            return Ok(());
+        }
+        let mut value = Value::from_serialize(self)?;
+        patch.apply(&mut value)?;
+        *self = value.deserialize_into()?;
+        Ok(())
+    }
+}
+impl<T> Diff for T where T: Serialize + for<'de> Deserialize<'de> {}
@@ -0,0 +1,262 @@
+use super::*;
+
+/// Build a [`Value`] from any [`Serialize`] type
+pub(crate) struct SerializerValue;
+
+impl Serializer for SerializerValue
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    type SerializeSeq = SerializeValueSeq;
+    type SerializeTuple = SerializeValueSeq;
+    type SerializeTupleStruct = SerializeValueSeq;
+    type SerializeTupleVariant = SerializeValueSeq;
+    type SerializeMap = SerializeValueMap;
+    type SerializeStruct = SerializeValueMap;
+    type SerializeStructVariant = SerializeValueMap;
+
+    fn serialize_bool(self, v: bool) -> EncodeResult<Value> { Ok(Value::Bool(v)) }
+
+    fn serialize_i8(self, v: i8) -> EncodeResult<Value> { Ok(Value::I64(v as _)) }
+    fn serialize_i16(self, v: i16) -> EncodeResult<Value> { Ok(Value::I64(v as _)) }
+    fn serialize_i32(self, v: i32) -> EncodeResult<Value> { Ok(Value::I64(v as _)) }
+    fn serialize_i64(self, v: i64) -> EncodeResult<Value> { Ok(Value::I64(v)) }
+    fn serialize_i128(self, v: i128) -> EncodeResult<Value> { Ok(Value::I128(v)) }
+
+    fn serialize_u8(self, v: u8) -> EncodeResult<Value> { Ok(Value::U64(v as _)) }
+    fn serialize_u16(self, v: u16) -> EncodeResult<Value> { Ok(Value::U64(v as _)) }
+    fn serialize_u32(self, v: u32) -> EncodeResult<Value> { Ok(Value::U64(v as _)) }
+    fn serialize_u64(self, v: u64) -> EncodeResult<Value> { Ok(Value::U64(v)) }
+    fn serialize_u128(self, v: u128) -> EncodeResult<Value> { Ok(Value::U128(v)) }
+
+    fn serialize_f32(self, v: f32) -> EncodeResult<Value> { Ok(Value::F32(v)) }
+    fn serialize_f64(self, v: f64) -> EncodeResult<Value> { Ok(Value::F64(v)) }
+
+    fn serialize_char(self, v: char) -> EncodeResult<Value> { Ok(Value::Char(v)) }
+    fn serialize_str(self, v: &str) -> EncodeResult<Value> { Ok(Value::String(v.to_owned())) }
+    fn serialize_bytes(self, v: &[u8]) -> EncodeResult<Value> { Ok(Value::Bytes(v.to_owned())) }
+
+    fn serialize_none(self) -> EncodeResult<Value> { Ok(Value::None) }
+    fn serialize_some<T>(self, value: &T) -> EncodeResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Value::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> EncodeResult<Value> { Ok(Value::Unit) }
+    fn serialize_unit_struct(self, _name: &'static str) -> EncodeResult<Value> { Ok(Value::Unit) }
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> EncodeResult<Value>
+    {
+        Ok(Value::Variant(variant.to_owned(), Box::new(Value::Unit)))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> EncodeResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T>(self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T) -> EncodeResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Value::Variant(variant.to_owned(), Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> EncodeResult<Self::SerializeSeq> { Ok(SerializeValueSeq::new(None, len.unwrap_or(0))) }
+    fn serialize_tuple(self, len: usize) -> EncodeResult<Self::SerializeTuple> { Ok(SerializeValueSeq::new(None, len)) }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> EncodeResult<Self::SerializeTupleStruct> { Ok(SerializeValueSeq::new(None, len)) }
+    fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize) -> EncodeResult<Self::SerializeTupleVariant>
+    {
+        Ok(SerializeValueSeq::new(Some(variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> EncodeResult<Self::SerializeMap> { Ok(SerializeValueMap::new(None, len.unwrap_or(0))) }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> EncodeResult<Self::SerializeStruct> { Ok(SerializeValueMap::new(None, len)) }
+    fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize)
+    -> EncodeResult<Self::SerializeStructVariant>
+    {
+        Ok(SerializeValueMap::new(Some(variant), len))
+    }
+}
+
+/// Sequences, tuples and tuple variants
+pub(crate) struct SerializeValueSeq
+{
+    variant: Option<&'static str>,
+    values: Vec<Value>,
+}
+impl SerializeValueSeq
+{
+    fn new(variant: Option<&'static str>, len: usize) -> Self
+    {
+        Self {
+            variant,
+            values: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.values.push(value.serialize(SerializerValue)?);
+        Ok(())
+    }
+
+    fn finish(self) -> EncodeResult<Value>
+    {
+        let seq = Value::Seq(self.values);
+        Ok(match self.variant
+        {
+            Some(variant) => Value::Variant(variant.to_owned(), Box::new(seq)),
+            None => seq,
+        })
+    }
+}
+
+impl SerializeSeq for SerializeValueSeq
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+impl SerializeTuple for SerializeValueSeq
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+impl SerializeTupleStruct for SerializeValueSeq
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+impl SerializeTupleVariant for SerializeValueSeq
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+
+/// Maps, structs and struct variants
+pub(crate) struct SerializeValueMap
+{
+    variant: Option<&'static str>,
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+}
+impl SerializeValueMap
+{
+    fn new(variant: Option<&'static str>, len: usize) -> Self
+    {
+        Self {
+            variant,
+            entries: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn field<T>(&mut self, key: &'static str, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.entries.push((Value::String(key.to_owned()), value.serialize(SerializerValue)?));
+        Ok(())
+    }
+
+    fn finish(self) -> EncodeResult<Value>
+    {
+        let map = Value::Map(self.entries);
+        Ok(match self.variant
+        {
+            Some(variant) => Value::Variant(variant.to_owned(), Box::new(map)),
+            None => map,
+        })
+    }
+}
+
+impl SerializeMap for SerializeValueMap
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(SerializerValue)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| EncodeError::custom("serialize_value called before serialize_key"))?;
+        self.entries.push((key, value.serialize(SerializerValue)?));
+        Ok(())
+    }
+
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+impl SerializeStruct for SerializeValueMap
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
+impl SerializeStructVariant for SerializeValueMap
+{
+    type Ok = Value;
+    type Error = EncodeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+    fn end(self) -> EncodeResult<Value> { self.finish() }
+}
@@ -0,0 +1,213 @@
+use super::*;
+use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, value::MapDeserializer, value::SeqDeserializer};
+
+/// A tree of the serde data model, used by [`Patch`] to compare two values.
+///
+/// Saving a [`Value`] keep the tree itself (`Map([(String("name"), String("Bob"))])`), not the value it represent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum Value
+{
+    #[default]
+    Unit,
+    Bool(bool),
+    I64(i64),
+    I128(i128),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Value>),
+    /// Also used for the tuples
+    Seq(Vec<Value>),
+    /// Also used for the structs, with the field names as keys
+    Map(Vec<(Value, Value)>),
+    /// An enum variant and its content: [`Value::Unit`], the newtype value, a [`Value::Seq`] for a tuple variant or a [`Value::Map`] for a struct variant
+    Variant(String, Box<Value>),
+}
+
+impl SaveExtension for Value {}
+impl LoadExtension for Value {}
+
+impl Value
+{
+    pub fn from_serialize<T>(value: &T) -> EncodeResult<Self>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(SerializerValue)
+    }
+
+    pub fn deserialize_into<T>(self) -> EncodeResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        T::deserialize(self)
+    }
+
+    fn unexpected(&self) -> serde::de::Unexpected<'_>
+    {
+        use serde::de::Unexpected;
+        match self
+        {
+            Value::Unit => Unexpected::Unit,
+            Value::Bool(v) => Unexpected::Bool(*v),
+            Value::I64(v) => Unexpected::Signed(*v),
+            Value::I128(_) => Unexpected::Other("i128"),
+            Value::U64(v) => Unexpected::Unsigned(*v),
+            Value::U128(_) => Unexpected::Other("u128"),
+            Value::F32(v) => Unexpected::Float(*v as f64),
+            Value::F64(v) => Unexpected::Float(*v),
+            Value::Char(v) => Unexpected::Char(*v),
+            Value::String(v) => Unexpected::Str(v),
+            Value::Bytes(v) => Unexpected::Bytes(v),
+            Value::None | Value::Some(_) => Unexpected::Option,
+            Value::Seq(_) => Unexpected::Seq,
+            Value::Map(_) => Unexpected::Map,
+            Value::Variant(_, _) => Unexpected::Enum,
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, EncodeError> for Value
+{
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer { self }
+}
+
+impl<'de> Deserializer<'de> for Value
+{
+    type Error = EncodeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self
+        {
+            Value::Unit => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::I64(v) => visitor.visit_i64(v),
+            Value::I128(v) => visitor.visit_i128(v),
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::U128(v) => visitor.visit_u128(v),
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(*v),
+            Value::Seq(v) =>
+            {
+                let mut seq = SeqDeserializer::new(v.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Map(v) =>
+            {
+                let mut map = MapDeserializer::new(v.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Value::Variant(name, value) => visitor.visit_enum(VariantValue { name, value: *value }),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self
+        {
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(*v),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self
+        {
+            Value::Variant(name, value) => visitor.visit_enum(VariantValue { name, value: *value }),
+            Value::String(name) => visitor.visit_enum(VariantValue { name, value: Value::Unit }),
+            v => Err(serde::de::Error::invalid_type(v.unexpected(), &visitor)),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct VariantValue
+{
+    name: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for VariantValue
+{
+    type Error = EncodeError;
+    type Variant = Value;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name = seed.deserialize(IntoDeserializer::<EncodeError>::into_deserializer(self.name))?;
+        Ok((name, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Value
+{
+    type Error = EncodeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error>
+    {
+        match self
+        {
+            Value::Unit => Ok(()),
+            v => Err(serde::de::Error::invalid_type(v.unexpected(), &"a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
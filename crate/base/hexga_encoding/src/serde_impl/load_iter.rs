@@ -0,0 +1,399 @@
+use super::*;
+use std::io::{BufRead, BufReader};
+
+/// The elements of a top-level sequence, decoded one at a time. See [`Load::load_iter`].
+///
+/// Ron, Json and TmpBin are read incrementally: only the current element is in memory.
+/// The other formats are loaded at once as a `Vec<T>`.
+///
+/// After the first error, the iterator always return `None`.
+pub struct LoadIter<T, R>
+where
+    R: Read,
+{
+    state: LoadIterState<T, R>,
+    limits: DecodeLimits,
+    /// Number of elements already decoded, for the [`DecodeLimit::Len`]
+    len: usize,
+}
+
+enum LoadIterState<T, R>
+where
+    R: Read,
+{
+    /// Each element is cut from the text, then parsed alone
+    Text
+    {
+        splitter: SeqSplitter<BufReader<LimitedReader<R>>>,
+        format: FormatMarkup,
+    },
+    TmpBin
+    {
+        deserializer: DeserializerTmpBin<TmpBinReader<BufReader<LimitedReader<R>>>>,
+        /// `None` if the length is unknown
+        remaining: Option<usize>,
+    },
+    /// The formats that can't be split
+    Loaded(std::vec::IntoIter<T>),
+    Done,
+}
+
+impl<T, R> LoadIter<T, R>
+where
+    T: Load,
+    R: Read,
+{
+    pub(crate) fn new(reader: R, extension: Option<&extension>, limits: DecodeLimits) -> EncodeResult<Self>
+    {
+        let format = extension
+            .filter(|extension| !T::load_custom_extensions().any(|e| e == *extension))
+            .and_then(|extension| AnyFormat::try_from(extension).ok());
+
+        let state = match format
+        {
+            Some(AnyFormat::Markup(format @ (FormatMarkup::Ron | FormatMarkup::Json))) =>
+            {
+                let mut splitter = SeqSplitter::new(BufReader::new(limits.reader(reader)), format);
+                let start = splitter.start();
+                splitter.reader.get_ref().check(start)?;
+                LoadIterState::Text { splitter, format }
+            }
+            Some(AnyFormat::Special(FormatSpecial::TmpBin)) =>
+            {
+                let mut deserializer = DeserializerTmpBin::new(BufReader::new(limits.reader(reader)));
+                let remaining = deserializer.read_header().and_then(|_| deserializer.read_seq_start());
+                let remaining = deserializer.get_ref().get_ref().check(remaining)?;
+                if let Some(len) = remaining
+                {
+                    limits.check(DecodeLimit::Len, len)?;
+                }
+                LoadIterState::TmpBin { deserializer, remaining }
+            }
+            _ => LoadIterState::Loaded(Vec::<T>::load_from_reader_with_limits(reader, extension, limits)?.into_iter()),
+        };
+        Ok(Self { state, limits, len: 0 })
+    }
+
+    fn next_value(&mut self) -> EncodeResult<Option<T>>
+    {
+        let limits = self.limits;
+        match &mut self.state
+        {
+            LoadIterState::Text { splitter, format } =>
+            {
+                let element = splitter.next_element();
+                let Some(element) = splitter.reader.get_ref().check(element)?
+                else
+                {
+                    return Ok(None);
+                };
+                self.len += 1;
+                limits.check(DecodeLimit::Len, self.len)?;
+                format.from_bytes_with_limits(&element, limits).map(Some)
+            }
+            LoadIterState::TmpBin { deserializer, remaining } =>
+            {
+                let limiter = Limiter::new(limits);
+                let value = deserializer.has_next(remaining).and_then(|next| match next
+                {
+                    true => limiter
+                        .deserialize(&mut *deserializer)
+                        .map(Some)
+                        .map_err(|e| limiter.map_err(Extension::TMP_BIN, e)),
+                    false => deserializer.end().map(|_| None),
+                });
+                let value = deserializer.get_ref().get_ref().check(value)?;
+                if value.is_some() && remaining.is_none()
+                {
+                    self.len += 1;
+                    limits.check(DecodeLimit::Len, self.len)?;
+                }
+                Ok(value)
+            }
+            LoadIterState::Loaded(values) => Ok(values.next()),
+            LoadIterState::Done => Ok(None),
+        }
+    }
+}
+
+impl<T, R> Iterator for LoadIter<T, R>
+where
+    T: Load,
+    R: Read,
+{
+    type Item = EncodeResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        match self.next_value()
+        {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) =>
+            {
+                self.state = LoadIterState::Done;
+                None
+            }
+            Err(err) =>
+            {
+                self.state = LoadIterState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        match &self.state
+        {
+            LoadIterState::TmpBin {
+                remaining: Some(remaining), ..
+            } => (0, Some(*remaining)),
+            LoadIterState::Loaded(values) => values.size_hint(),
+            LoadIterState::Done => (0, Some(0)),
+            _ => (0, None),
+        }
+    }
+}
+
+/// Cut the elements of a Ron or Json top-level sequence (`[a, b, c]`) without parsing them.
+///
+/// Only the strings, the chars and the nesting are tracked, the parser of the format check the rest.
+struct SeqSplitter<B>
+{
+    reader: B,
+    format: FormatMarkup,
+    /// The Ron extensions (`#![enable(implicit_some)]`), added in front of each element
+    header: Vec<u8>,
+    done: bool,
+}
+
+impl<B> SeqSplitter<B>
+where
+    B: BufRead,
+{
+    fn new(reader: B, format: FormatMarkup) -> Self
+    {
+        Self {
+            reader,
+            format,
+            header: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Read up to the opening `[`
+    fn start(&mut self) -> EncodeResult
+    {
+        self.skip_blank()?;
+        while self.format == FormatMarkup::Ron && self.peek()? == Some(b'#')
+        {
+            self.read_attribute()?;
+            self.skip_blank()?;
+        }
+        match self.next_byte()?
+        {
+            Some(b'[') => Ok(()),
+            _ => Err(self.error("expected a sequence")),
+        }
+    }
+
+    fn error(&self, reason: impl Display) -> EncodeError { EncodeError::markup::<Self>(self.format.extension(), reason) }
+
+    fn peek(&mut self) -> EncodeResult<Option<u8>> { Ok(self.reader.fill_buf()?.first().copied()) }
+
+    fn next_byte(&mut self) -> EncodeResult<Option<u8>>
+    {
+        let byte = self.peek()?;
+        if byte.is_some()
+        {
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn expect_byte(&mut self) -> EncodeResult<u8> { self.next_byte()?.ok_or_else(|| self.error("unexpected end of input")) }
+
+    /// Skip the whitespaces and the comments
+    fn skip_blank(&mut self) -> EncodeResult
+    {
+        loop
+        {
+            match self.peek()?
+            {
+                Some(b) if b.is_ascii_whitespace() => self.reader.consume(1),
+                Some(b'/') =>
+                {
+                    self.reader.consume(1);
+                    self.skip_comment()?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skip a comment, after its first `/`
+    fn skip_comment(&mut self) -> EncodeResult
+    {
+        match self.expect_byte()?
+        {
+            b'/' =>
+            {
+                while !matches!(self.next_byte()?, Some(b'\n') | None)
+                {}
+            }
+            b'*' =>
+            {
+                let mut star = false;
+                loop
+                {
+                    let b = self.expect_byte()?;
+                    if star && b == b'/'
+                    {
+                        return Ok(());
+                    }
+                    star = b == b'*';
+                }
+            }
+            b => return Err(self.error(format!("unexpected {:?} after '/'", b as char))),
+        }
+        Ok(())
+    }
+
+    /// Read a Ron attribute like `#![enable(implicit_some)]` into the header
+    fn read_attribute(&mut self) -> EncodeResult
+    {
+        loop
+        {
+            let b = self.expect_byte()?;
+            self.header.push(b);
+            if b == b']'
+            {
+                self.header.push(b'\n');
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read the bytes of a string or a char up to the closing `quote`, after the opening one
+    fn read_quoted(&mut self, quote: u8, element: &mut Vec<u8>) -> EncodeResult
+    {
+        loop
+        {
+            let b = self.expect_byte()?;
+            element.push(b);
+            match b
+            {
+                b'\\' => element.push(self.expect_byte()?),
+                b if b == quote => return Ok(()),
+                _ =>
+                {}
+            }
+        }
+    }
+
+    /// Read a Ron raw string (`r#"..."#`) up to the `"` followed by `hashes` `#`, after the opening `"`
+    fn read_raw(&mut self, hashes: usize, element: &mut Vec<u8>) -> EncodeResult
+    {
+        let start = element.len();
+        loop
+        {
+            element.push(self.expect_byte()?);
+            let end = element.len();
+            if end > start + hashes && element[end - 1 - hashes] == b'"' && element[end - hashes..].iter().all(|b| *b == b'#')
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The number of `#` if the `"` just after `element` open a Ron raw string (`r"`, `r#"`, `br#"`...)
+    fn raw_hashes(&self, element: &[u8]) -> Option<usize>
+    {
+        if self.format != FormatMarkup::Ron
+        {
+            return None;
+        }
+        let hashes = element.iter().rev().take_while(|b| **b == b'#').count();
+        let before = &element[..element.len() - hashes];
+        let prefix = before.strip_suffix(b"r")?;
+        let prefix = prefix.strip_suffix(b"b").unwrap_or(prefix);
+        match prefix.last()
+        {
+            Some(b) if b.is_ascii_alphanumeric() || *b == b'_' => None,
+            _ => Some(hashes),
+        }
+    }
+
+    /// Check that nothing follow the closing `]`
+    fn end(&mut self) -> EncodeResult
+    {
+        self.done = true;
+        self.skip_blank()?;
+        match self.peek()?
+        {
+            None => Ok(()),
+            Some(_) => Err(self.error("unexpected trailing characters")),
+        }
+    }
+
+    /// The bytes of the next element, or `None` after the closing `]`
+    fn next_element(&mut self) -> EncodeResult<Option<Vec<u8>>>
+    {
+        if self.done
+        {
+            return Ok(None);
+        }
+        self.skip_blank()?;
+        if self.peek()? == Some(b']')
+        {
+            self.reader.consume(1);
+            self.end()?;
+            return Ok(None);
+        }
+
+        let mut element = self.header.clone();
+        let mut depth = 0usize;
+        loop
+        {
+            let b = self.expect_byte()?;
+            match b
+            {
+                b'"' =>
+                {
+                    let raw = self.raw_hashes(&element);
+                    element.push(b);
+                    match raw
+                    {
+                        Some(hashes) => self.read_raw(hashes, &mut element)?,
+                        None => self.read_quoted(b'"', &mut element)?,
+                    }
+                    continue;
+                }
+                b'\'' if self.format == FormatMarkup::Ron =>
+                {
+                    element.push(b);
+                    self.read_quoted(b'\'', &mut element)?;
+                    continue;
+                }
+                b'/' =>
+                {
+                    self.skip_comment()?;
+                    element.push(b' ');
+                    continue;
+                }
+                b'(' | b'[' | b'{' => depth += 1,
+                b']' if depth == 0 =>
+                {
+                    self.end()?;
+                    return Ok(Some(element));
+                }
+                b',' if depth == 0 => return Ok(Some(element)),
+                b')' | b']' | b'}' if depth == 0 => return Err(self.error(format!("unexpected {:?}", b as char))),
+                b')' | b']' | b'}' => depth -= 1,
+                _ =>
+                {}
+            }
+            element.push(b);
+        }
+    }
+}
@@ -19,13 +19,25 @@ pub enum FormatMarkup
     Ron,
     Json,
     Xml,
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "yaml")]
+    Yaml,
 }
 
 impl FormatMarkup
 {
     // Todo: add a flag
     pub const PREFERED: Self = Self::Ron;
-    pub const ALL: &'static [Self] = &[Self::Ron, Self::Json, Self::Xml];
+    pub const ALL: &'static [Self] = &[
+        Self::Ron,
+        Self::Json,
+        Self::Xml,
+        #[cfg(feature = "toml")]
+        Self::Toml,
+        #[cfg(feature = "yaml")]
+        Self::Yaml,
+    ];
 
     pub const fn extension(self) -> &'static str
     {
@@ -34,6 +46,10 @@ impl FormatMarkup
             FormatMarkup::Ron => Extension::RON,
             FormatMarkup::Json => Extension::JSON,
             FormatMarkup::Xml => Extension::XML,
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => Extension::TOML,
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => Extension::YAML,
         }
     }
 
@@ -46,6 +62,10 @@ impl FormatMarkup
             FormatMarkup::Ron => value.to_ron(),
             FormatMarkup::Json => value.to_json(),
             FormatMarkup::Xml => value.to_xml(),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => value.to_toml(),
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => value.to_yaml(),
         }
     }
 
@@ -59,6 +79,30 @@ impl FormatMarkup
             FormatMarkup::Ron => value.to_ron_with_writer(writer),
             FormatMarkup::Json => value.to_json_with_writer(writer),
             FormatMarkup::Xml => value.to_xml_with_writer(writer),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => value.to_toml_with_writer(writer),
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => value.to_yaml_with_writer(writer),
+        }
+    }
+
+    /// Same as [`Self::encode_with_writer`], but follow the [`ExtensionArgs::COMPACT`] argument when the format support it.
+    pub fn encode_with_writer_and_args<T, W>(self, value: &T, writer: W, args: ExtensionArgs<'_>) -> EncodeResult
+    where
+        T: Serialize,
+        W: Write,
+    {
+        if !args.contains(ExtensionArgs::COMPACT)
+        {
+            return self.encode_with_writer(value, writer);
+        }
+        match self
+        {
+            FormatMarkup::Ron => value.to_ron_compact_with_writer(writer),
+            FormatMarkup::Json => value.to_json_compact_with_writer(writer),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => value.to_toml_compact_with_writer(writer),
+            _ => self.encode_with_writer(value, writer),
         }
     }
 
@@ -71,6 +115,10 @@ impl FormatMarkup
             FormatMarkup::Ron => T::from_ron(markup),
             FormatMarkup::Json => T::from_json(markup),
             FormatMarkup::Xml => T::from_xml(markup),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => T::from_toml(markup),
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => T::from_yaml(markup),
         }
     }
 
@@ -83,9 +131,14 @@ impl FormatMarkup
             FormatMarkup::Ron => T::from_ron_bytes(bytes),
             FormatMarkup::Json => T::from_json_bytes(bytes),
             FormatMarkup::Xml => T::from_xml_bytes(bytes),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => T::from_toml_bytes(bytes),
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => T::from_yaml_bytes(bytes),
         }
     }
 
+    /// Only Ron and Json are decoded while reading, see [`Self::from_reader_with_limits`]
     pub fn from_reader<T, R>(self, reader: R) -> EncodeResult<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -93,12 +146,152 @@ impl FormatMarkup
     {
         match self
         {
-            FormatMarkup::Ron => T::from_ron_with_reader(reader),
+            FormatMarkup::Ron => self.from_reader_with_limits(reader, DecodeLimits::UNLIMITED),
             FormatMarkup::Json => T::from_json_with_reader(reader),
             FormatMarkup::Xml => T::from_xml_with_reader(reader),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml => T::from_toml_with_reader(reader),
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => T::from_yaml_with_reader(reader),
+        }
+    }
+
+    /// Same as [`Self::from_bytes`], but fail with [`EncodeError::LimitExceeded`] when one of the `limits` is exceeded.
+    pub fn from_bytes_with_limits<'a, T>(self, bytes: &'a [u8], limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: Deserialize<'a>,
+    {
+        limits.check(DecodeLimit::Bytes, bytes.len())?;
+        let limiter = Limiter::new(limits);
+        let extension = self.extension();
+        match self
+        {
+            FormatMarkup::Ron =>
+            {
+                let mut deserializer = ron::Deserializer::from_bytes(bytes).map_err(|e| limiter.error::<T>(extension, &e.code, ron_position(bytes, &e)))?;
+                limiter
+                    .deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|_| value))
+                    .map_err(|e| {
+                        let e = deserializer.span_error(e);
+                        limiter.error::<T>(extension, &e.code, ron_position(bytes, &e))
+                    })
+            }
+            FormatMarkup::Json =>
+            {
+                let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+                limiter
+                    .deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|_| value))
+                    .map_err(|e| limiter.error::<T>(extension, without_line_column(&e, e.line(), e.column()), json_position(bytes, &e)))
+            }
+            FormatMarkup::Xml => limiter
+                .deserialize(&mut serde_xml_rs::Deserializer::new_from_reader(bytes))
+                .map_err(|e| limiter.error::<T>(extension, e, None)),
+            #[cfg(feature = "toml")]
+            FormatMarkup::Toml =>
+            {
+                let deserializer =
+                    toml::Deserializer::parse(str::from_utf8(bytes)?).map_err(|e| limiter.error::<T>(extension, e.message(), toml_position(bytes, &e)))?;
+                limiter
+                    .deserialize(deserializer)
+                    .map_err(|e| limiter.error::<T>(extension, e.message(), toml_position(bytes, &e)))
+            }
+            #[cfg(feature = "yaml")]
+            FormatMarkup::Yaml => limiter.deserialize(serde_yaml::Deserializer::from_slice(bytes)).map_err(|e| {
+                // serde_yaml already prefix the message with the path
+                let reason = match e.location()
+                {
+                    Some(location) => without_line_column(&e, location.line(), location.column()),
+                    None => e.to_string(),
+                };
+                let reason = reason
+                    .strip_prefix(&format!("{}: ", limiter.error_path()))
+                    .map(ToOwned::to_owned)
+                    .unwrap_or(reason);
+                limiter.error::<T>(extension, reason, yaml_position(bytes, &e))
+            }),
+        }
+    }
+
+    /// Same as [`Self::from_reader`], but fail with [`EncodeError::LimitExceeded`] when one of the `limits` is exceeded.
+    ///
+    /// Json and Ron are decoded while reading. The other parsers need the whole text, so it is read first.
+    /// The Ron extensions (`#![enable(implicit_some)]`) are not parsed while reading, the text is also read first for them.
+    ///
+    /// Without the whole text, the line and the column of the error are in the reason.
+    pub fn from_reader_with_limits<T, R>(self, reader: R, limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        match self
+        {
+            FormatMarkup::Ron =>
+            {
+                let mut deserializer = DeserializerRonReader::new(limits.reader(reader));
+                let extensions = deserializer.has_extensions().map_err(|e| EncodeError::markup::<T>(self.extension(), e));
+                if deserializer.get_ref().check(extensions)?
+                {
+                    let bytes = deserializer.read_to_end();
+                    let bytes = deserializer.get_ref().check(bytes)?;
+                    return self.from_bytes_with_limits(&bytes, limits);
+                }
+                let limiter = Limiter::new(limits);
+                let result = limiter
+                    .deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|_| value))
+                    .map_err(|e| {
+                        let (line, column) = deserializer.position();
+                        limiter.error::<T>(self.extension(), format!("{e} at line {line} column {column}"), None)
+                    });
+                deserializer.get_ref().check(result)
+            }
+            FormatMarkup::Json =>
+            {
+                let mut reader = limits.reader(reader);
+                let limiter = Limiter::new(limits);
+                let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(&mut reader));
+                // Without the source, the line and column stay in the reason
+                let result = limiter
+                    .deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|_| value))
+                    .map_err(|e| limiter.error::<T>(self.extension(), e, None));
+                reader.check(result)
+            }
+            _ => self.from_bytes_with_limits(&limits.read_to_end(reader)?, limits),
         }
     }
 }
+fn ron_position(source: &[u8], err: &ron::error::SpannedError) -> Option<SourcePosition>
+{
+    // The span start before the whitespaces that precede the value
+    let start = SourcePosition::from_line_column(source, err.span.start.line, err.span.start.col)?;
+    let skipped = source[start.offset..].iter().take_while(|b| **b == b' ' || **b == b'\t').count();
+    SourcePosition::from_offset(source, start.offset + skipped)
+}
+
+/// The position is already in the [`ErrorLocation`]
+fn without_line_column(err: &impl Display, line: usize, column: usize) -> String
+{
+    let reason = err.to_string();
+    reason
+        .strip_suffix(&format!(" at line {line} column {column}"))
+        .map(ToOwned::to_owned)
+        .unwrap_or(reason)
+}
+
+fn json_position(source: &[u8], err: &serde_json::Error) -> Option<SourcePosition> { SourcePosition::from_line_column(source, err.line(), err.column()) }
+
+#[cfg(feature = "toml")]
+fn toml_position(source: &[u8], err: &toml::de::Error) -> Option<SourcePosition> { err.span().and_then(|span| SourcePosition::from_offset(source, span.start)) }
+
+#[cfg(feature = "yaml")]
+fn yaml_position(source: &[u8], err: &serde_yaml::Error) -> Option<SourcePosition>
+{
+    err.location().and_then(|location| SourcePosition::from_offset(source, location.index()))
+}
+
 impl Display for FormatMarkup
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{:?}", self) }
@@ -113,6 +306,10 @@ impl<'a> TryFrom<&'a str> for FormatMarkup
             Extension::RON => Ok(Self::Ron),
             Extension::JSON => Ok(Self::Json),
             Extension::XML => Ok(Self::Xml),
+            #[cfg(feature = "toml")]
+            Extension::TOML => Ok(Self::Toml),
+            #[cfg(feature = "yaml")]
+            Extension::YAML | Extension::YML => Ok(Self::Yaml),
             _ => Err(()),
         }
     }
@@ -123,11 +320,18 @@ pub enum FormatSpecial
 {
     Txt,
     TmpBin,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
 }
 
 impl FormatSpecial
 {
-    pub const ALL: &'static [Self] = &[Self::Txt, Self::TmpBin];
+    pub const ALL: &'static [Self] = &[
+        Self::Txt,
+        Self::TmpBin,
+        #[cfg(feature = "msgpack")]
+        Self::MsgPack,
+    ];
 
     pub const fn extension(self) -> &'static str
     {
@@ -135,6 +339,8 @@ impl FormatSpecial
         {
             FormatSpecial::Txt => Extension::TXT,
             FormatSpecial::TmpBin => Extension::TMP_BIN,
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => Extension::MSGPACK,
         }
     }
 
@@ -147,15 +353,12 @@ impl FormatSpecial
             FormatSpecial::Txt =>
             {
                 let mut txt = String::with_capacity(1024);
-                value.serialize(SerializerTxt::new(&mut txt))?;
+                value.serialize(&mut SerializerTxt::new(&mut txt))?;
                 Ok(txt.into_bytes())
             }
-            FormatSpecial::TmpBin =>
-            {
-                let mut bytes = Vec::with_capacity(256);
-                value.serialize(SerializerTmpBin::new(&mut bytes))?;
-                Ok(bytes)
-            }
+            FormatSpecial::TmpBin => value.to_tmp_bin(),
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => value.to_msgpack(),
         }
     }
 
@@ -168,14 +371,12 @@ impl FormatSpecial
         {
             FormatSpecial::Txt =>
             {
-                value.serialize(SerializerTxt::new(writer.to_fmt_writer()))?;
-                Ok(())
-            }
-            FormatSpecial::TmpBin =>
-            {
-                value.serialize(SerializerTmpBin::new(writer))?;
+                value.serialize(&mut SerializerTxt::new(writer.to_fmt_writer()))?;
                 Ok(())
             }
+            FormatSpecial::TmpBin => value.to_tmp_bin_with_writer(writer),
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => value.to_msgpack_with_writer(writer),
         }
     }
 
@@ -186,7 +387,9 @@ impl FormatSpecial
         match self
         {
             FormatSpecial::Txt => T::deserialize(DeserializerTxt::new(str::from_utf8(bytes)?)),
-            FormatSpecial::TmpBin => T::deserialize(DeserializerTmpBin::new(bytes)),
+            FormatSpecial::TmpBin => T::from_tmp_bin_bytes(bytes),
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => T::from_msgpack_bytes(bytes),
         }
     }
 
@@ -203,14 +406,68 @@ impl FormatSpecial
                 reader.read_to_string(&mut txt)?;
                 T::deserialize(DeserializerTxt::new(txt))
             }
+            FormatSpecial::TmpBin => T::from_tmp_bin_with_reader(BufReader::new(reader)),
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => T::from_msgpack_with_reader(reader),
+        }
+    }
+
+    /// Same as [`Self::from_bytes`], but fail with [`EncodeError::LimitExceeded`] when one of the `limits` is exceeded.
+    pub fn from_bytes_with_limits<'a, T>(self, bytes: &'a [u8], limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: Deserialize<'a>,
+    {
+        limits.check(DecodeLimit::Bytes, bytes.len())?;
+        let limiter = Limiter::new(limits);
+        match self
+        {
+            FormatSpecial::Txt => limiter
+                .deserialize(DeserializerTxt::new(str::from_utf8(bytes)?))
+                .map_err(|e| limiter.map_err(self.extension(), e)),
             FormatSpecial::TmpBin =>
             {
-                let mut bytes = Vec::with_capacity(256);
-                reader.read_to_end(&mut bytes)?;
-                T::deserialize(DeserializerTmpBin::new(bytes))
+                let mut deserializer = DeserializerTmpBin::from_slice(bytes);
+                deserializer.read_header()?;
+                let value = limiter.deserialize(&mut deserializer).map_err(|e| limiter.map_err(self.extension(), e))?;
+                deserializer.end()?;
+                Ok(value)
             }
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => limiter
+                .deserialize(&mut rmp_serde::Deserializer::from_read_ref(bytes))
+                .map_err(|e| limiter.error::<T>(self.extension(), e, None)),
         }
     }
+
+    /// Same as [`Self::from_reader`], but fail with [`EncodeError::LimitExceeded`] when one of the `limits` is exceeded.
+    ///
+    /// TmpBin and MsgPack are decoded while reading, Txt is read first.
+    pub fn from_reader_with_limits<T, R>(self, reader: R, limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        let mut reader = limits.reader(reader);
+        let limiter = Limiter::new(limits);
+        let result = match self
+        {
+            FormatSpecial::Txt => return self.from_bytes_with_limits(&limits.read_to_end(reader.into_inner())?, limits),
+            FormatSpecial::TmpBin =>
+            {
+                let mut deserializer = DeserializerTmpBin::new(BufReader::new(&mut reader));
+                deserializer.read_header().and_then(|_| {
+                    let value = limiter.deserialize(&mut deserializer).map_err(|e| limiter.map_err(self.extension(), e))?;
+                    deserializer.end()?;
+                    Ok(value)
+                })
+            }
+            #[cfg(feature = "msgpack")]
+            FormatSpecial::MsgPack => limiter
+                .deserialize(&mut rmp_serde::Deserializer::new(BufReader::new(&mut reader)))
+                .map_err(|e| limiter.error::<T>(self.extension(), e, None)),
+        };
+        reader.check(result)
+    }
 }
 impl Display for FormatSpecial
 {
@@ -225,6 +482,8 @@ impl<'a> TryFrom<&'a str> for FormatSpecial
         {
             Extension::TXT => Ok(Self::Txt),
             Extension::TMP_BIN => Ok(Self::TmpBin),
+            #[cfg(feature = "msgpack")]
+            Extension::MSGPACK => Ok(Self::MsgPack),
             _ => Err(()),
         }
     }
@@ -247,8 +506,14 @@ impl AnyFormat
         Self::Markup(FormatMarkup::Ron),
         Self::Markup(FormatMarkup::Json),
         Self::Markup(FormatMarkup::Xml),
+        #[cfg(feature = "toml")]
+        Self::Markup(FormatMarkup::Toml),
+        #[cfg(feature = "yaml")]
+        Self::Markup(FormatMarkup::Yaml),
         Self::Special(FormatSpecial::Txt),
         Self::Special(FormatSpecial::TmpBin),
+        #[cfg(feature = "msgpack")]
+        Self::Special(FormatSpecial::MsgPack),
     ];
 
     pub const fn extension(self) -> &'static str
@@ -260,6 +525,19 @@ impl AnyFormat
         }
     }
 
+    /// True if [`Self::from_reader_with_limits`] decode while reading (Ron, Json, TmpBin and MsgPack),
+    /// instead of reading everything first.
+    pub const fn is_streaming(self) -> bool
+    {
+        match self
+        {
+            AnyFormat::Markup(v) => matches!(v, FormatMarkup::Ron | FormatMarkup::Json),
+            #[cfg(feature = "msgpack")]
+            AnyFormat::Special(FormatSpecial::MsgPack) => true,
+            AnyFormat::Special(v) => matches!(v, FormatSpecial::TmpBin),
+        }
+    }
+
     pub fn encode<T>(self, value: &T) -> EncodeResult<Vec<u8>>
     where
         T: Serialize,
@@ -283,6 +561,18 @@ impl AnyFormat
         }
     }
 
+    pub fn encode_with_writer_and_args<T, W>(self, value: &T, writer: W, args: ExtensionArgs<'_>) -> EncodeResult
+    where
+        T: Serialize,
+        W: Write,
+    {
+        match self
+        {
+            AnyFormat::Markup(v) => v.encode_with_writer_and_args(value, writer, args),
+            AnyFormat::Special(v) => v.encode_with_writer(value, writer),
+        }
+    }
+
     pub fn from_bytes<T>(self, bytes: &[u8]) -> EncodeResult<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -294,6 +584,7 @@ impl AnyFormat
         }
     }
 
+    /// Decode while reading for the [streaming formats](Self::is_streaming), otherwise read everything first
     pub fn from_reader<T, R>(self, reader: R) -> EncodeResult<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -305,6 +596,29 @@ impl AnyFormat
             AnyFormat::Special(v) => v.from_reader(reader),
         }
     }
+
+    pub fn from_bytes_with_limits<'a, T>(self, bytes: &'a [u8], limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: Deserialize<'a>,
+    {
+        match self
+        {
+            AnyFormat::Markup(v) => v.from_bytes_with_limits(bytes, limits),
+            AnyFormat::Special(v) => v.from_bytes_with_limits(bytes, limits),
+        }
+    }
+
+    pub fn from_reader_with_limits<T, R>(self, reader: R, limits: DecodeLimits) -> EncodeResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        match self
+        {
+            AnyFormat::Markup(v) => v.from_reader_with_limits(reader, limits),
+            AnyFormat::Special(v) => v.from_reader_with_limits(reader, limits),
+        }
+    }
 }
 impl Display for AnyFormat
 {
@@ -11,12 +11,19 @@ pub trait ToRon: Serialize
     {
         ron::ser::to_writer_pretty(writer.to_fmt_writer(), &self, ron::ser::PrettyConfig::default()).map_err(|e| EncodeError::markup::<Self>(Extension::RON, e))
     }
+    fn to_ron_compact(&self) -> EncodeResult<String> { ron::ser::to_string(&self).map_err(|e| EncodeError::markup::<Self>(Extension::RON, e)) }
+    #[allow(unused_mut)] // weird LSP error
+    fn to_ron_compact_with_writer<W: Write>(&self, mut writer: W) -> EncodeResult
+    {
+        ron::ser::to_writer(writer.to_fmt_writer(), &self).map_err(|e| EncodeError::markup::<Self>(Extension::RON, e))
+    }
 }
 impl<T> ToRon for T where T: Serialize {}
 
 pub trait FromRon: for<'de> Deserialize<'de>
 {
     fn from_ron_bytes(bytes: &[u8]) -> EncodeResult<Self> { Self::from_ron_with_reader(BufReader::new(bytes)) }
+    /// Read the whole text first, `ron` can't parse while reading
     fn from_ron_with_reader<R: Read>(reader: R) -> EncodeResult<Self>
     {
         ron::de::from_reader(reader).map_err(|e| EncodeError::markup::<Self>(Extension::RON, e))
@@ -32,6 +39,11 @@ pub trait ToJson: Serialize
     {
         serde_json::ser::to_writer_pretty(writer, &self).map_err(|e| EncodeError::markup::<Self>(Extension::JSON, e))
     }
+    fn to_json_compact(&self) -> EncodeResult<String> { serde_json::ser::to_string(&self).map_err(|e| EncodeError::markup::<Self>(Extension::JSON, e)) }
+    fn to_json_compact_with_writer<W: Write>(&self, writer: W) -> EncodeResult
+    {
+        serde_json::ser::to_writer(writer, &self).map_err(|e| EncodeError::markup::<Self>(Extension::JSON, e))
+    }
 }
 impl<T> ToJson for T where T: Serialize {}
 
@@ -67,7 +79,91 @@ pub trait FromXml: for<'de> Deserialize<'de>
 }
 impl<T> FromXml for T where T: for<'de> Deserialize<'de> {}
 
-/// TODO: replace bincode by [postcard](https://docs.rs/postcard/latest/postcard/) ?
+#[cfg(feature = "toml")]
+pub trait ToToml: Serialize
+{
+    fn to_toml(&self) -> EncodeResult<String> { toml::to_string_pretty(&self).map_err(|e| EncodeError::markup::<Self>(Extension::TOML, e)) }
+    fn to_toml_with_writer<W: Write>(&self, mut writer: W) -> EncodeResult
+    {
+        writer.write_all(self.to_toml()?.as_bytes())?;
+        Ok(())
+    }
+    fn to_toml_compact(&self) -> EncodeResult<String> { toml::to_string(&self).map_err(|e| EncodeError::markup::<Self>(Extension::TOML, e)) }
+    fn to_toml_compact_with_writer<W: Write>(&self, mut writer: W) -> EncodeResult
+    {
+        writer.write_all(self.to_toml_compact()?.as_bytes())?;
+        Ok(())
+    }
+}
+#[cfg(feature = "toml")]
+impl<T> ToToml for T where T: Serialize {}
+
+#[cfg(feature = "toml")]
+pub trait FromToml: for<'de> Deserialize<'de>
+{
+    fn from_toml_bytes(bytes: &[u8]) -> EncodeResult<Self> { Self::from_toml(str::from_utf8(bytes)?) }
+    fn from_toml_with_reader<R: Read>(mut reader: R) -> EncodeResult<Self>
+    {
+        let mut toml = String::with_capacity(1024);
+        reader.read_to_string(&mut toml)?;
+        Self::from_toml(&toml)
+    }
+    fn from_toml(toml: &str) -> EncodeResult<Self> { toml::from_str(toml).map_err(|e| EncodeError::markup::<Self>(Extension::TOML, e)) }
+}
+#[cfg(feature = "toml")]
+impl<T> FromToml for T where T: for<'de> Deserialize<'de> {}
+
+#[cfg(feature = "yaml")]
+pub trait ToYaml: Serialize
+{
+    fn to_yaml(&self) -> EncodeResult<String> { serde_yaml::to_string(&self).map_err(|e| EncodeError::markup::<Self>(Extension::YAML, e)) }
+    fn to_yaml_with_writer<W: Write>(&self, writer: W) -> EncodeResult
+    {
+        serde_yaml::to_writer(writer, &self).map_err(|e| EncodeError::markup::<Self>(Extension::YAML, e))
+    }
+}
+#[cfg(feature = "yaml")]
+impl<T> ToYaml for T where T: Serialize {}
+
+#[cfg(feature = "yaml")]
+pub trait FromYaml: for<'de> Deserialize<'de>
+{
+    fn from_yaml_bytes(bytes: &[u8]) -> EncodeResult<Self> { serde_yaml::from_slice(bytes).map_err(|e| EncodeError::markup::<Self>(Extension::YAML, e)) }
+    fn from_yaml_with_reader<R: Read>(reader: R) -> EncodeResult<Self>
+    {
+        serde_yaml::from_reader(reader).map_err(|e| EncodeError::markup::<Self>(Extension::YAML, e))
+    }
+    fn from_yaml(yaml: &str) -> EncodeResult<Self> { serde_yaml::from_str(yaml).map_err(|e| EncodeError::markup::<Self>(Extension::YAML, e)) }
+}
+#[cfg(feature = "yaml")]
+impl<T> FromYaml for T where T: for<'de> Deserialize<'de> {}
+
+/// [MessagePack](https://msgpack.org/) binary format.
+///
+/// Structs are encoded as maps with their field names to stay readable by other MessagePack tools.
+#[cfg(feature = "msgpack")]
+pub trait ToMsgPack: Serialize
+{
+    fn to_msgpack(&self) -> EncodeResult<Vec<u8>> { rmp_serde::to_vec_named(&self).map_err(|e| EncodeError::markup::<Self>(Extension::MSGPACK, e)) }
+    fn to_msgpack_with_writer<W: Write>(&self, mut writer: W) -> EncodeResult
+    {
+        rmp_serde::encode::write_named(&mut writer, &self).map_err(|e| EncodeError::markup::<Self>(Extension::MSGPACK, e))
+    }
+}
+#[cfg(feature = "msgpack")]
+impl<T> ToMsgPack for T where T: Serialize {}
+
+#[cfg(feature = "msgpack")]
+pub trait FromMsgPack: for<'de> Deserialize<'de>
+{
+    fn from_msgpack_bytes(bytes: &[u8]) -> EncodeResult<Self> { rmp_serde::from_slice(bytes).map_err(|e| EncodeError::markup::<Self>(Extension::MSGPACK, e)) }
+    fn from_msgpack_with_reader<R: Read>(reader: R) -> EncodeResult<Self>
+    {
+        rmp_serde::from_read(reader).map_err(|e| EncodeError::markup::<Self>(Extension::MSGPACK, e))
+    }
+}
+#[cfg(feature = "msgpack")]
+impl<T> FromMsgPack for T where T: for<'de> Deserialize<'de> {}
 
 /// Intended for short-term storage of data in a binary format, such as during data transfer.
 ///
@@ -77,23 +173,42 @@ pub trait ToTmpBin: Serialize
     /// Intended for short-term storage of data in a binary format, such as during data transfer.
     ///
     /// Not suitable for long-term storage, as the implementation or encoding may change at any time.
-    fn to_tmp_bin(&self) -> EncodeResult<Vec<u8>> { bincode::serialize(self).map_err(|e| EncodeError::markup::<Self>(Extension::TMP_BIN, e)) }
+    fn to_tmp_bin(&self) -> EncodeResult<Vec<u8>>
+    {
+        let mut bytes = Vec::with_capacity(256);
+        self.to_tmp_bin_with_writer(&mut bytes)?;
+        Ok(bytes)
+    }
     /// Intended for short-term storage of data in a binary format, such as during data transfer.
     ///
     /// Not suitable for long-term storage, as the implementation or encoding may change at any time.
     fn to_tmp_bin_with_writer<W: Write>(&self, writer: W) -> EncodeResult
     {
-        bincode::serialize_into(writer, self).map_err(|e| EncodeError::markup::<Self>(Extension::TMP_BIN, e))
+        let mut serializer = SerializerTmpBin::new(writer);
+        serializer.write_header()?;
+        self.serialize(&mut serializer)
     }
 }
 impl<T> ToTmpBin for T where T: Serialize {}
 
 pub trait FromTmpBin: for<'de> Deserialize<'de>
 {
-    fn from_tmp_bin_bytes(bytes: &[u8]) -> EncodeResult<Self> { bincode::deserialize(bytes).map_err(|e| EncodeError::markup::<Self>(Extension::TMP_BIN, e)) }
+    fn from_tmp_bin_bytes(bytes: &[u8]) -> EncodeResult<Self>
+    {
+        let mut deserializer = DeserializerTmpBin::from_slice(bytes);
+        deserializer.read_header()?;
+        let value = Self::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
+    }
+    /// Decode while reading, without reading everything first
     fn from_tmp_bin_with_reader<R: Read>(reader: R) -> EncodeResult<Self>
     {
-        bincode::deserialize_from(reader).map_err(|e| EncodeError::markup::<Self>(Extension::TMP_BIN, e))
+        let mut deserializer = DeserializerTmpBin::new(reader);
+        deserializer.read_header()?;
+        let value = Self::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
     }
 }
 impl<T> FromTmpBin for T where T: for<'de> Deserialize<'de> {}
@@ -5,6 +5,16 @@ pub(crate) use save_load::*;
 
 pub mod markup;
 
+mod versioned;
+pub use versioned::*;
+
+mod load_iter;
+pub use load_iter::LoadIter;
+
+pub mod diff;
+
+pub mod command;
+
 pub mod de;
 pub mod ser;
 
@@ -12,6 +22,10 @@ pub(crate) use prelude::*;
 pub(crate) mod prelude
 {
     //pub use super::{markup::*};
+    pub use super::command::{Completions, FromCommand, Relative};
+    pub use super::diff::{Diff, Patch};
+    pub use super::load_iter::LoadIter;
     pub use super::markup::to_markup::*;
     pub(crate) use super::markup::*;
+    pub use super::versioned::{Migrate, Versioned};
 }
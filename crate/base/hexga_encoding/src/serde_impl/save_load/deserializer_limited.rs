@@ -0,0 +1,574 @@
+use super::*;
+use serde::de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess};
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// Enforce the [`DecodeLimits`] depth and length on top of any other deserializer,
+/// and track the path of the value being decoded (`player.inventory[3].id`) for the [`ErrorLocation`].
+///
+/// The inner deserializer errors are kept, except when a limit was exceeded: the error is then [`EncodeError::LimitExceeded`].
+pub(crate) struct Limiter
+{
+    limits: DecodeLimits,
+    depth: Cell<usize>,
+    exceeded: Cell<Option<DecodeLimit>>,
+    path: RefCell<Vec<PathSegment>>,
+    /// The names of the map keys in the path, one after the other. Reused to not allocate for each key.
+    keys: RefCell<String>,
+    /// Where the last map key start in `keys`, used as the path of the next value
+    key: Cell<Option<usize>>,
+    /// The path when the first error happened
+    error_path: RefCell<Option<String>>,
+}
+
+/// The path is only turned into a string for the first error
+enum PathSegment
+{
+    Field(&'static str),
+    Index(usize),
+    /// The name of a map key, in [`Limiter::keys`]
+    Key(Range<usize>),
+}
+impl Limiter
+{
+    pub fn new(limits: DecodeLimits) -> Self
+    {
+        Self {
+            limits,
+            depth: Cell::new(0),
+            exceeded: Cell::new(None),
+            path: RefCell::new(Vec::new()),
+            keys: RefCell::new(String::new()),
+            key: Cell::new(None),
+            error_path: RefCell::new(None),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(&self, deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(DeserializerLimited {
+            inner: deserializer,
+            limiter: self,
+            key: false,
+            fields: &[],
+        })
+    }
+
+    /// The path of the value that failed
+    pub fn error_path(&self) -> String { self.error_path.borrow().clone().unwrap_or_default() }
+
+    /// Convert the error of the inner deserializer
+    pub fn error<T: ?Sized>(&self, extension: impl Into<CowExtensionStatic>, reason: impl Display, position: Option<SourcePosition>) -> EncodeError
+    {
+        match self.exceeded.get()
+        {
+            Some(limit) => self.limits.error(limit),
+            None => EncodeError::markup_at::<T>(
+                extension,
+                reason,
+                ErrorLocation {
+                    path: self.error_path(),
+                    position,
+                },
+            ),
+        }
+    }
+
+    /// Same as [`Self::error`] for the deserializers that already use [`EncodeError`]
+    pub fn map_err(&self, extension: impl Into<CowExtensionStatic>, err: EncodeError) -> EncodeError
+    {
+        let path = self.error_path();
+        match (self.exceeded.get(), err)
+        {
+            (Some(limit), _) => self.limits.error(limit),
+            (None, EncodeError::Custom(reason)) if !path.is_empty() => EncodeError::markup_at::<()>(extension, reason, ErrorLocation { path, position: None }),
+            (None, err) => err,
+        }
+    }
+
+    fn enter(&self, segment: PathSegment) { self.path.borrow_mut().push(segment); }
+
+    fn leave<R, E>(&self, result: Result<R, E>) -> Result<R, E>
+    {
+        if result.is_err() && self.error_path.borrow().is_none()
+        {
+            *self.error_path.borrow_mut() = Some(self.path_to_string());
+        }
+        if let Some(PathSegment::Key(range)) = self.path.borrow_mut().pop()
+        {
+            self.keys.borrow_mut().truncate(range.start);
+        }
+        result
+    }
+
+    /// Save the name of a visited map key, replacing the previous one if no value used it
+    fn set_key(&self, write_name: impl FnOnce(&mut String))
+    {
+        let mut keys = self.keys.borrow_mut();
+        if let Some(start) = self.key.take()
+        {
+            keys.truncate(start);
+        }
+        self.key.set(Some(keys.len()));
+        write_name(&mut keys);
+    }
+
+    /// Forget the last map key if no value used it
+    fn clear_key(&self)
+    {
+        if let Some(start) = self.key.take()
+        {
+            self.keys.borrow_mut().truncate(start);
+        }
+    }
+
+    fn path_to_string(&self) -> String
+    {
+        let keys = self.keys.borrow();
+        let mut path = String::new();
+        for segment in self.path.borrow().iter()
+        {
+            let name = match segment
+            {
+                PathSegment::Field(name) => *name,
+                PathSegment::Key(range) => &keys[range.clone()],
+                PathSegment::Index(index) =>
+                {
+                    let _ = write!(path, "[{index}]");
+                    continue;
+                }
+            };
+            if !path.is_empty()
+            {
+                path.push('.');
+            }
+            path.push_str(name);
+        }
+        path
+    }
+
+    fn exceed<E: serde::de::Error>(&self, limit: DecodeLimit) -> E
+    {
+        self.exceeded.set(Some(limit));
+        E::custom(self.limits.error(limit))
+    }
+
+    fn nested<E, R>(&self, f: impl FnOnce() -> Result<R, E>) -> Result<R, E>
+    where
+        E: serde::de::Error,
+    {
+        let depth = self.depth.get();
+        if depth >= self.limits.max_depth
+        {
+            return Err(self.exceed(DecodeLimit::Depth));
+        }
+        self.depth.set(depth + 1);
+        let result = f();
+        self.depth.set(depth);
+        result
+    }
+
+    fn wrap<V>(&self, inner: V) -> DeserializerLimited<'_, V>
+    {
+        DeserializerLimited {
+            inner,
+            limiter: self,
+            key: false,
+            fields: &[],
+        }
+    }
+}
+
+/// Wrap a deserializer, a visitor, a seed or an enum/variant access
+struct DeserializerLimited<'l, D>
+{
+    inner: D,
+    limiter: &'l Limiter,
+    /// Deserializing a map key, the visited value is saved in the path
+    key: bool,
+    /// The struct fields, to name the elements when the struct is encoded as a sequence
+    fields: &'static [&'static str],
+}
+impl<'l, D> DeserializerLimited<'l, D>
+{
+    fn with<V>(&self, inner: V) -> DeserializerLimited<'l, V>
+    {
+        DeserializerLimited {
+            inner,
+            limiter: self.limiter,
+            key: self.key,
+            fields: self.fields,
+        }
+    }
+}
+
+/// Wrap a sequence or a map access and count the elements
+struct AccessLimited<'l, A>
+{
+    inner: A,
+    limiter: &'l Limiter,
+    len: usize,
+    fields: &'static [&'static str],
+}
+impl<'l, A> AccessLimited<'l, A>
+{
+    fn next<E: serde::de::Error>(&mut self) -> Result<(), E>
+    {
+        self.len += 1;
+        if self.len > self.limiter.limits.max_len
+        {
+            return Err(self.limiter.exceed(DecodeLimit::Len));
+        }
+        Ok(())
+    }
+
+    fn limit_hint(&self, hint: Option<usize>) -> Option<usize> { hint.map(|hint| hint.min(self.limiter.limits.max_len)) }
+}
+
+macro_rules! forward_deserialize {
+    ($($fn_name:ident($($arg:ident : $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $fn_name<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = self.with(visitor);
+                self.inner.$fn_name($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'l, 'de, D> Deserializer<'de> for DeserializerLimited<'l, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    );
+
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = DeserializerLimited { fields, ..self.with(visitor) };
+        self.inner.deserialize_struct(name, fields, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool { self.inner.is_human_readable() }
+}
+
+/// The name of a map key in the path
+trait KeyName
+{
+    fn write_key_name(&self, out: &mut String);
+}
+macro_rules! impl_key_name {
+    ($($ty:ty),*) => {
+        $(
+            impl KeyName for $ty
+            {
+                fn write_key_name(&self, out: &mut String) { let _ = write!(out, "{self}"); }
+            }
+        )*
+    };
+}
+impl_key_name!(bool, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64, char, str, String);
+impl KeyName for [u8]
+{
+    fn write_key_name(&self, out: &mut String) { out.push_str(&String::from_utf8_lossy(self)); }
+}
+impl KeyName for Vec<u8>
+{
+    fn write_key_name(&self, out: &mut String) { self.as_slice().write_key_name(out); }
+}
+
+macro_rules! forward_visit {
+    ($($fn_name:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $fn_name<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if self.key
+                {
+                    self.limiter.set_key(|out| v.write_key_name(out));
+                }
+                self.inner.$fn_name(v)
+            }
+        )*
+    };
+}
+
+impl<'l, 'de, V> Visitor<'de> for DeserializerLimited<'l, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result { self.inner.expecting(formatter) }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    );
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let limiter = self.limiter;
+        limiter.nested(|| self.inner.visit_some(limiter.wrap(deserializer)))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let limiter = self.limiter;
+        limiter.nested(|| self.inner.visit_newtype_struct(limiter.wrap(deserializer)))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let limiter = self.limiter;
+        let fields = self.fields;
+        limiter.nested(|| {
+            self.inner.visit_seq(AccessLimited {
+                inner: seq,
+                limiter,
+                len: 0,
+                fields,
+            })
+        })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let limiter = self.limiter;
+        limiter.nested(|| {
+            self.inner.visit_map(AccessLimited {
+                inner: map,
+                limiter,
+                len: 0,
+                fields: &[],
+            })
+        })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let limiter = self.limiter;
+        limiter.nested(|| self.inner.visit_enum(limiter.wrap(data)))
+    }
+}
+
+impl<'l, 'de, S> DeserializeSeed<'de> for DeserializerLimited<'l, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.with(deserializer);
+        self.inner.deserialize(deserializer)
+    }
+}
+
+impl<'l, 'de, A> SeqAccess<'de> for AccessLimited<'l, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let segment = match self.fields.get(self.len)
+        {
+            Some(field) => PathSegment::Field(field),
+            None => PathSegment::Index(self.len),
+        };
+        self.limiter.enter(segment);
+        match self.limiter.leave(self.inner.next_element_seed(self.limiter.wrap(seed)))?
+        {
+            Some(value) =>
+            {
+                self.next()?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> { self.limit_hint(self.inner.size_hint()) }
+}
+
+impl<'l, 'de, A> MapAccess<'de> for AccessLimited<'l, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.limiter.clear_key();
+        let seed = DeserializerLimited {
+            key: true,
+            ..self.limiter.wrap(seed)
+        };
+        match self.inner.next_key_seed(seed)?
+        {
+            Some(key) =>
+            {
+                self.next()?;
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let segment = match self.limiter.key.take()
+        {
+            Some(start) => PathSegment::Key(start..self.limiter.keys.borrow().len()),
+            None => PathSegment::Index(self.len.saturating_sub(1)),
+        };
+        self.limiter.enter(segment);
+        self.limiter.leave(self.inner.next_value_seed(self.limiter.wrap(seed)))
+    }
+
+    fn size_hint(&self) -> Option<usize> { self.limit_hint(self.inner.size_hint()) }
+}
+
+impl<'l, 'de, A> EnumAccess<'de> for DeserializerLimited<'l, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = DeserializerLimited<'l, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((value, self.limiter.wrap(variant)))
+    }
+}
+
+impl<'l, 'de, A> VariantAccess<'de> for DeserializerLimited<'l, A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> { self.inner.unit_variant() }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.newtype_variant_seed(self.limiter.wrap(seed))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.tuple_variant(len, self.limiter.wrap(visitor))
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = DeserializerLimited {
+            fields,
+            ..self.limiter.wrap(visitor)
+        };
+        self.inner.struct_variant(fields, visitor)
+    }
+}
@@ -0,0 +1,809 @@
+use super::*;
+use serde::de::{DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess};
+
+type RonResult<T = ()> = Result<T, ron::Error>;
+
+/// Read by chunk of this size
+const RON_CHUNK: usize = 8 * 1024;
+
+/// The identifiers that are scalars in Ron
+const RON_SCALAR_IDENTS: [&str; 8] = ["true", "false", "inf", "inff32", "inff64", "NaN", "NaNf32", "NaNf64"];
+
+/// Decode Ron while reading, see [`FormatMarkup::from_reader_with_limits`].
+///
+/// `ron` only parse a whole text. So the nesting (sequences, maps, structs, tuples, options and enums) is parsed here,
+/// and each scalar (number, string, char...) is cut from the input, then parsed alone by `ron`.
+/// Only the current scalar is kept in memory, not the text.
+///
+/// The Ron extensions (`#![enable(implicit_some)]`) change the syntax, they are not supported here: see [`Self::has_extensions`].
+pub(crate) struct DeserializerRonReader<R>
+{
+    reader: R,
+    /// The bytes read and not consumed yet start at `pos`
+    buf: Vec<u8>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    /// The line and the column where the last token start, for the error messages
+    token: (usize, usize),
+    /// Reused buffer for the scalars
+    scratch: Vec<u8>,
+}
+
+impl<R> DeserializerRonReader<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self
+    {
+        Self {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            token: (1, 1),
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R { &self.reader }
+
+    /// The line and the column of the last token
+    pub fn position(&self) -> (usize, usize) { self.token }
+
+    /// True if the text start with an attribute like `#![enable(implicit_some)]`
+    pub fn has_extensions(&mut self) -> RonResult<bool>
+    {
+        self.skip_ws()?;
+        Ok(self.peek()? == Some(b'#'))
+    }
+
+    /// Everything not consumed yet, for the text with extensions
+    pub fn read_to_end(&mut self) -> std::io::Result<Vec<u8>>
+    {
+        let mut bytes = self.buf.split_off(self.pos);
+        self.pos = 0;
+        self.reader.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Check that only whitespaces and comments are left
+    pub fn end(&mut self) -> RonResult
+    {
+        self.skip_ws()?;
+        match self.peek()?
+        {
+            None => Ok(()),
+            Some(_) => Err(ron::Error::TrailingCharacters),
+        }
+    }
+
+    /// The byte at `offset` from the cursor, read if needed
+    fn peek_at(&mut self, offset: usize) -> RonResult<Option<u8>>
+    {
+        while self.buf.len() <= self.pos + offset
+        {
+            if self.pos >= RON_CHUNK
+            {
+                self.buf.drain(..self.pos);
+                self.pos = 0;
+            }
+            let len = self.buf.len();
+            self.buf.resize(len + RON_CHUNK, 0);
+            let read = loop
+            {
+                match self.reader.read(&mut self.buf[len..])
+                {
+                    Ok(read) => break read,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) =>
+                    {
+                        self.buf.truncate(len);
+                        return Err(ron::Error::Io(e.to_string()));
+                    }
+                }
+            };
+            self.buf.truncate(len + read);
+            if read == 0
+            {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.buf[self.pos + offset]))
+    }
+
+    fn peek(&mut self) -> RonResult<Option<u8>> { self.peek_at(0) }
+
+    /// Consume `len` bytes already peeked
+    fn advance(&mut self, len: usize)
+    {
+        for b in &self.buf[self.pos..self.pos + len]
+        {
+            match b
+            {
+                b'\n' =>
+                {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                // Count the characters, not the continuation bytes
+                b if b & 0xC0 != 0x80 => self.column += 1,
+                _ =>
+                {}
+            }
+        }
+        self.pos += len;
+    }
+
+    fn next_byte(&mut self) -> RonResult<u8>
+    {
+        let b = self.peek()?.ok_or(ron::Error::Eof)?;
+        self.advance(1);
+        Ok(b)
+    }
+
+    /// Skip the whitespaces and the comments
+    fn skip_ws(&mut self) -> RonResult
+    {
+        loop
+        {
+            match self.peek()?
+            {
+                Some(b) if b.is_ascii_whitespace() => self.advance(1),
+                Some(b'/') => match self.peek_at(1)?
+                {
+                    Some(b'/') =>
+                    {
+                        while !matches!(self.peek()?, Some(b'\n') | None)
+                        {
+                            self.advance(1);
+                        }
+                    }
+                    Some(b'*') => self.skip_block_comment()?,
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+        self.token = (self.line, self.column);
+        Ok(())
+    }
+
+    /// Skip a `/* */` comment, they can be nested in Ron
+    fn skip_block_comment(&mut self) -> RonResult
+    {
+        self.advance(2);
+        let mut depth = 1;
+        while depth > 0
+        {
+            match (self.peek()?.ok_or(ron::Error::UnclosedBlockComment)?, self.peek_at(1)?)
+            {
+                (b'*', Some(b'/')) =>
+                {
+                    self.advance(2);
+                    depth -= 1;
+                }
+                (b'/', Some(b'*')) =>
+                {
+                    self.advance(2);
+                    depth += 1;
+                }
+                _ => self.advance(1),
+            }
+        }
+        Ok(())
+    }
+
+    /// Skip the whitespaces, then consume `c` if it is next
+    fn consume(&mut self, c: u8) -> RonResult<bool>
+    {
+        self.skip_ws()?;
+        let found = self.peek()? == Some(c);
+        if found
+        {
+            self.advance(1);
+        }
+        Ok(found)
+    }
+
+    fn expect(&mut self, c: u8, err: ron::Error) -> RonResult { if self.consume(c)? { Ok(()) } else { Err(err) } }
+
+    /// The length of the identifier at the cursor, with the `r#` of the raw identifiers. 0 if there is none.
+    fn ident_len(&mut self) -> RonResult<usize>
+    {
+        let start = match (self.peek()?, self.peek_at(1)?, self.peek_at(2)?)
+        {
+            (Some(b'r'), Some(b'#'), Some(c)) if is_ident_char(c) => 2,
+            (Some(c), _, _) if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 => 0,
+            _ => return Ok(0),
+        };
+        let mut len = start;
+        while self.peek_at(len)?.is_some_and(is_ident_char)
+        {
+            len += 1;
+        }
+        Ok(len)
+    }
+
+    /// True if the next token is the identifier `name`
+    fn is_ident(&mut self, name: &str) -> RonResult<bool>
+    {
+        self.skip_ws()?;
+        let len = self.ident_len()?;
+        Ok(&self.buf[self.pos..self.pos + len] == name.as_bytes())
+    }
+
+    fn identifier(&mut self) -> RonResult<String>
+    {
+        self.skip_ws()?;
+        let len = self.ident_len()?;
+        if len == 0 || self.is_quoted_prefix()?
+        {
+            return Err(ron::Error::ExpectedIdentifier);
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        let ident = str::from_utf8(bytes.strip_prefix(b"r#").unwrap_or(bytes))
+            .map_err(ron::Error::Utf8Error)?
+            .to_owned();
+        self.advance(len);
+        Ok(ident)
+    }
+
+    /// True if a byte, a byte string or a raw string start at the cursor: `b'`, `b"`, `br"`, `r"`, `r#"`...
+    fn is_quoted_prefix(&mut self) -> RonResult<bool>
+    {
+        let mut i = 0;
+        if self.peek()? == Some(b'b')
+        {
+            i += 1;
+            if self.peek_at(i)? == Some(b'\'')
+            {
+                return Ok(true);
+            }
+        }
+        if self.peek_at(i)? == Some(b'r')
+        {
+            i += 1;
+            while self.peek_at(i)? == Some(b'#')
+            {
+                i += 1;
+            }
+        }
+        Ok(i > 0 && self.peek_at(i)? == Some(b'"'))
+    }
+
+    /// True if a struct field (`name:`) start at the cursor
+    fn is_field(&mut self) -> RonResult<bool>
+    {
+        let mut i = self.ident_len()?;
+        if i == 0 || self.is_quoted_prefix()?
+        {
+            return Ok(false);
+        }
+        while self.peek_at(i)?.is_some_and(|b| b.is_ascii_whitespace())
+        {
+            i += 1;
+        }
+        Ok(self.peek_at(i)? == Some(b':'))
+    }
+
+    /// Consume the optional struct name, it must be `name`
+    fn struct_name(&mut self, name: &'static str) -> RonResult
+    {
+        self.skip_ws()?;
+        if self.ident_len()? == 0 || self.is_quoted_prefix()?
+        {
+            return Ok(());
+        }
+        let found = self.identifier()?;
+        match name.is_empty()
+        {
+            true => Err(ron::Error::ExpectedNamedStructLike(name)),
+            false if found != name => Err(ron::Error::ExpectedDifferentStructName { expected: name, found }),
+            false => Ok(()),
+        }
+    }
+
+    /// Cut the next scalar into the scratch buffer
+    fn read_scalar(&mut self) -> RonResult<&str>
+    {
+        self.skip_ws()?;
+        self.scratch.clear();
+        if self.is_quoted_prefix()?
+        {
+            while let Some(b @ (b'b' | b'r' | b'#')) = self.peek()?
+            {
+                self.scratch.push(b);
+                self.advance(1);
+            }
+            let raw = self.scratch.contains(&b'r');
+            let hashes = self.scratch.iter().filter(|b| **b == b'#').count();
+            let quote = self.next_byte()?;
+            self.scratch.push(quote);
+            match raw
+            {
+                true => self.read_raw(hashes)?,
+                false => self.read_quoted(quote)?,
+            }
+        }
+        else
+        {
+            match self.peek()?
+            {
+                Some(quote @ (b'"' | b'\'')) =>
+                {
+                    self.advance(1);
+                    self.scratch.push(quote);
+                    self.read_quoted(quote)?;
+                }
+                Some(_) =>
+                {
+                    while let Some(b) = self.peek()?
+                        && (b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'+' | b'-'))
+                    {
+                        self.scratch.push(b);
+                        self.advance(1);
+                    }
+                }
+                None => return Err(ron::Error::Eof),
+            }
+        }
+        if self.scratch.is_empty()
+        {
+            let c = self.peek()?.ok_or(ron::Error::Eof)?;
+            return Err(ron::Error::UnexpectedChar(c as char));
+        }
+        str::from_utf8(&self.scratch).map_err(ron::Error::Utf8Error)
+    }
+
+    /// Read up to the closing `quote`, after the opening one
+    fn read_quoted(&mut self, quote: u8) -> RonResult
+    {
+        loop
+        {
+            let b = self.peek()?.ok_or(ron::Error::ExpectedStringEnd)?;
+            self.advance(1);
+            self.scratch.push(b);
+            match b
+            {
+                b'\\' =>
+                {
+                    let escaped = self.next_byte()?;
+                    self.scratch.push(escaped);
+                }
+                b if b == quote => return Ok(()),
+                _ =>
+                {}
+            }
+        }
+    }
+
+    /// Read up to the `"` followed by `hashes` `#`, after the opening `"`
+    fn read_raw(&mut self, hashes: usize) -> RonResult
+    {
+        let start = self.scratch.len();
+        loop
+        {
+            let b = self.peek()?.ok_or(ron::Error::ExpectedStringEnd)?;
+            self.advance(1);
+            self.scratch.push(b);
+            let end = self.scratch.len();
+            if end > start + hashes && self.scratch[end - 1 - hashes] == b'"' && self.scratch[end - hashes..].iter().all(|b| *b == b'#')
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parse the next scalar with `ron`
+    fn scalar<T>(&mut self) -> RonResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let text = self.read_scalar()?;
+        ron::from_str(text).map_err(|e| e.code)
+    }
+
+    /// A struct, a tuple or `()` when the type is unknown, after the optional name
+    fn any_struct<'de, V>(&mut self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(b'(', ron::Error::ExpectedStructLike)?;
+        if self.consume(b')')?
+        {
+            return visitor.visit_unit();
+        }
+        let value = match self.is_field()?
+        {
+            true => visitor.visit_map(RonCompound::new(self, b')', true))?,
+            false => visitor.visit_seq(RonCompound::new(self, b')', false))?,
+        };
+        self.expect(b')', ron::Error::ExpectedStructLikeEnd)?;
+        Ok(value)
+    }
+
+    /// The fields in parentheses, after the optional name
+    fn struct_body<'de, V>(&mut self, name: &'static str, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(b'(', struct_like(name))?;
+        let value = visitor.visit_map(RonCompound::new(self, b')', true))?;
+        self.expect(b')', ron::Error::ExpectedStructLikeEnd)?;
+        Ok(value)
+    }
+}
+
+fn is_ident_char(b: u8) -> bool { b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 }
+
+fn struct_like(name: &'static str) -> ron::Error
+{
+    match name.is_empty()
+    {
+        true => ron::Error::ExpectedStructLike,
+        false => ron::Error::ExpectedNamedStructLike(name),
+    }
+}
+
+macro_rules! deserialize_ron_scalar {
+    ($($fn_name:ident => $visit:ident($ty:ty),)*) => {
+        $(
+            fn $fn_name<V>(self, visitor: V) -> RonResult<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.scalar::<$ty>()?)
+            }
+        )*
+    };
+}
+
+impl<'de, R> Deserializer<'de> for &mut DeserializerRonReader<R>
+where
+    R: Read,
+{
+    type Error = ron::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_ws()?;
+        match self.peek()?
+        {
+            None => Err(ron::Error::Eof),
+            Some(b'[') => self.deserialize_seq(visitor),
+            Some(b'{') => self.deserialize_map(visitor),
+            Some(b'(') => self.any_struct(visitor),
+            Some(_) if self.is_quoted_prefix()? || self.ident_len()? == 0 => self.scalar::<ron::Value>()?.deserialize_any(visitor),
+            Some(_) =>
+            {
+                for ident in RON_SCALAR_IDENTS
+                {
+                    if self.is_ident(ident)?
+                    {
+                        return self.scalar::<ron::Value>()?.deserialize_any(visitor);
+                    }
+                }
+                if self.is_ident("Some")? || self.is_ident("None")?
+                {
+                    return self.deserialize_option(visitor);
+                }
+                // The name of a struct or of an enum variant
+                self.identifier()?;
+                self.skip_ws()?;
+                match self.peek()?
+                {
+                    Some(b'(') => self.any_struct(visitor),
+                    _ => visitor.visit_unit(),
+                }
+            }
+        }
+    }
+
+    deserialize_ron_scalar! {
+        deserialize_bool => visit_bool(bool),
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+        deserialize_char => visit_char(char),
+        deserialize_str => visit_string(String),
+        deserialize_string => visit_string(String),
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_ws()?;
+        match self.peek()?
+        {
+            Some(b'[') => visitor.visit_byte_buf(Vec::<u8>::deserialize(self)?),
+            _ => self.scalar::<ron::Value>()?.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_ident("None")?
+        {
+            self.advance("None".len());
+            return visitor.visit_none();
+        }
+        if !self.is_ident("Some")?
+        {
+            return Err(ron::Error::ExpectedOption);
+        }
+        self.advance("Some".len());
+        self.expect(b'(', ron::Error::ExpectedOption)?;
+        let value = visitor.visit_some(&mut *self)?;
+        self.consume(b',')?;
+        self.expect(b')', ron::Error::ExpectedOptionEnd)?;
+        Ok(value)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.consume(b'(')? && self.consume(b')')?
+        {
+            true => visitor.visit_unit(),
+            false => Err(ron::Error::ExpectedUnit),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if !name.is_empty() && self.is_ident(name)?
+        {
+            self.advance(name.len());
+            return visitor.visit_unit();
+        }
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.struct_name(name)?;
+        self.expect(b'(', struct_like(name))?;
+        let value = visitor.visit_newtype_struct(&mut *self)?;
+        self.consume(b',')?;
+        self.expect(b')', ron::Error::ExpectedStructLikeEnd)?;
+        Ok(value)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(b'[', ron::Error::ExpectedArray)?;
+        let value = visitor.visit_seq(RonCompound::new(self, b']', false))?;
+        self.expect(b']', ron::Error::ExpectedArrayEnd)?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(b'(', ron::Error::ExpectedStructLike)?;
+        let value = visitor.visit_seq(RonCompound::new(self, b')', false))?;
+        self.expect(b')', ron::Error::ExpectedStructLikeEnd)?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V>(self, name: &'static str, len: usize, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.struct_name(name)?;
+        self.deserialize_tuple(len, visitor).map_err(|e| match e
+        {
+            ron::Error::ExpectedStructLike => struct_like(name),
+            e => e,
+        })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(b'{', ron::Error::ExpectedMap)?;
+        let value = visitor.visit_map(RonCompound::new(self, b'}', false))?;
+        self.expect(b'}', ron::Error::ExpectedMapEnd)?;
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(self, name: &'static str, _fields: &'static [&'static str], visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.struct_name(name)?;
+        self.struct_body(name, visitor)
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(RonVariant { deserializer: self })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.identifier()?)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// The elements of a sequence, a tuple, a map or a struct, separated by commas
+struct RonCompound<'b, R>
+{
+    deserializer: &'b mut DeserializerRonReader<R>,
+    terminator: u8,
+    /// The keys are the identifiers of the struct fields
+    fields: bool,
+    /// A comma was read after the previous element
+    had_comma: bool,
+}
+impl<'b, R> RonCompound<'b, R>
+where
+    R: Read,
+{
+    fn new(deserializer: &'b mut DeserializerRonReader<R>, terminator: u8, fields: bool) -> Self
+    {
+        Self {
+            deserializer,
+            terminator,
+            fields,
+            had_comma: true,
+        }
+    }
+
+    fn has_element(&mut self) -> RonResult<bool>
+    {
+        self.deserializer.skip_ws()?;
+        let end = self.deserializer.peek()? == Some(self.terminator);
+        match (self.had_comma, end)
+        {
+            (true, end) => Ok(!end),
+            (false, true) => Ok(false),
+            (false, false) => Err(ron::Error::ExpectedComma),
+        }
+    }
+}
+impl<'b, 'de, R> SeqAccess<'de> for RonCompound<'b, R>
+where
+    R: Read,
+{
+    type Error = ron::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> RonResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.has_element()?
+        {
+            return Ok(None);
+        }
+        let value = seed.deserialize(&mut *self.deserializer)?;
+        self.had_comma = self.deserializer.consume(b',')?;
+        Ok(Some(value))
+    }
+}
+impl<'b, 'de, R> MapAccess<'de> for RonCompound<'b, R>
+where
+    R: Read,
+{
+    type Error = ron::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> RonResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if !self.has_element()?
+        {
+            return Ok(None);
+        }
+        match self.fields
+        {
+            true => seed.deserialize(IntoDeserializer::<ron::Error>::into_deserializer(self.deserializer.identifier()?)),
+            false => seed.deserialize(&mut *self.deserializer),
+        }
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> RonResult<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.deserializer.expect(b':', ron::Error::ExpectedMapColon)?;
+        let value = seed.deserialize(&mut *self.deserializer)?;
+        self.had_comma = self.deserializer.consume(b',')?;
+        Ok(value)
+    }
+}
+
+struct RonVariant<'b, R>
+{
+    deserializer: &'b mut DeserializerRonReader<R>,
+}
+impl<'b, 'de, R> EnumAccess<'de> for RonVariant<'b, R>
+where
+    R: Read,
+{
+    type Error = ron::Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> RonResult<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(IntoDeserializer::<ron::Error>::into_deserializer(self.deserializer.identifier()?))?;
+        Ok((variant, self))
+    }
+}
+impl<'b, 'de, R> VariantAccess<'de> for RonVariant<'b, R>
+where
+    R: Read,
+{
+    type Error = ron::Error;
+
+    fn unit_variant(self) -> RonResult { Ok(()) }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> RonResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.deserializer.expect(b'(', ron::Error::ExpectedStructLike)?;
+        let value = seed.deserialize(&mut *self.deserializer)?;
+        self.deserializer.consume(b',')?;
+        self.deserializer.expect(b')', ron::Error::ExpectedStructLikeEnd)?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> RonResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.struct_body("", visitor)
+    }
+}
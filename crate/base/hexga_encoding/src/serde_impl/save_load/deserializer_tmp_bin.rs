@@ -1,219 +1,484 @@
 use super::*;
+use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess};
 
-pub(crate) struct DeserializerTmpBin<'a>
+/// Where [`DeserializerTmpBin`] read its bytes from: a [`TmpBinReader`] or a [`TmpBinSlice`]
+pub(crate) trait TmpBinSource<'de>
 {
-    pub(crate) bytes: Cow<'a, [u8]>,
+    fn peek_byte(&mut self) -> EncodeResult<Option<u8>>;
+    fn read_byte(&mut self) -> EncodeResult<Option<u8>>;
+    fn read_exact(&mut self, buf: &mut [u8]) -> EncodeResult;
+    /// Read `len` bytes, borrowed from the input when possible
+    fn read_slice<'s>(&'s mut self, len: usize) -> EncodeResult<TmpBinBytes<'de, 's>>;
 }
-impl<'a> DeserializerTmpBin<'a>
+
+pub(crate) enum TmpBinBytes<'de, 's>
 {
-    pub fn new(bytes: impl Into<Cow<'a, [u8]>>) -> Self { Self { bytes: bytes.into() } }
+    /// Live as long as the input, can be given to the [`Visitor::visit_borrowed_str`]
+    Borrowed(&'de [u8]),
+    /// Live until the next read
+    Copied(&'s [u8]),
 }
-impl<'a, 'de> Deserializer<'de> for DeserializerTmpBin<'a>
-{
-    type Error = EncodeError;
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+/// Read from any reader, without reading everything first.
+///
+/// The bytes are read one value at a time, so loading a big sequence only need the memory of the decoded value.
+pub(crate) struct TmpBinReader<R>
+{
+    reader: R,
+    /// A byte read by [`TmpBinSource::peek_byte`] and not consumed yet
+    peeked: Option<u8>,
+    /// Reused buffer for the strings and the bytes
+    scratch: Vec<u8>,
+}
+impl<'de, R> TmpBinSource<'de> for TmpBinReader<R>
+where
+    R: Read,
+{
+    fn peek_byte(&mut self) -> EncodeResult<Option<u8>>
     {
-        self.deserialize_bytes(visitor)
+        if self.peeked.is_none()
+        {
+            self.peeked = self.read_byte()?;
+        }
+        Ok(self.peeked)
     }
 
-    fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_byte(&mut self) -> EncodeResult<Option<u8>>
     {
-        Err(Default::default())
+        if let Some(byte) = self.peeked.take()
+        {
+            return Ok(Some(byte));
+        }
+        let mut byte = [0];
+        loop
+        {
+            match self.reader.read(&mut byte)
+            {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_exact(&mut self, buf: &mut [u8]) -> EncodeResult
     {
-        Err(Default::default())
+        let mut start = 0;
+        if !buf.is_empty()
+            && let Some(byte) = self.peeked.take()
+        {
+            buf[0] = byte;
+            start = 1;
+        }
+        self.reader.read_exact(&mut buf[start..]).map_err(|e| match e.kind()
+        {
+            std::io::ErrorKind::UnexpectedEof => unexpected_end(),
+            _ => e.into(),
+        })
     }
 
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    /// The scratch buffer grow with the read bytes, not with the untrusted `len`.
+    fn read_slice<'s>(&'s mut self, len: usize) -> EncodeResult<TmpBinBytes<'de, 's>>
     {
-        Err(Default::default())
+        self.scratch.clear();
+        if len > 0
+            && let Some(byte) = self.peeked.take()
+        {
+            self.scratch.push(byte);
+        }
+        let missing = (len - self.scratch.len()) as u64;
+        (&mut self.reader).take(missing).read_to_end(&mut self.scratch)?;
+        if self.scratch.len() != len
+        {
+            return Err(unexpected_end());
+        }
+        Ok(TmpBinBytes::Copied(&self.scratch))
     }
+}
 
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+/// Read from bytes in memory. The strings and the bytes are borrowed from them.
+pub(crate) struct TmpBinSlice<'de>
+{
+    bytes: &'de [u8],
+}
+impl<'de> TmpBinSource<'de> for TmpBinSlice<'de>
+{
+    fn peek_byte(&mut self) -> EncodeResult<Option<u8>> { Ok(self.bytes.first().copied()) }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_byte(&mut self) -> EncodeResult<Option<u8>>
     {
-        Err(Default::default())
+        let byte = self.bytes.first().copied();
+        self.bytes = self.bytes.get(1..).unwrap_or_default();
+        Ok(byte)
     }
 
-    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_exact(&mut self, buf: &mut [u8]) -> EncodeResult
     {
-        Err(Default::default())
+        buf.copy_from_slice(self.read_borrowed(buf.len())?);
+        Ok(())
     }
 
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_slice<'s>(&'s mut self, len: usize) -> EncodeResult<TmpBinBytes<'de, 's>> { self.read_borrowed(len).map(TmpBinBytes::Borrowed) }
+}
+impl<'de> TmpBinSlice<'de>
+{
+    fn read_borrowed(&mut self, len: usize) -> EncodeResult<&'de [u8]>
     {
-        Err(Default::default())
+        let (bytes, remaining) = self.bytes.split_at_checked(len).ok_or_else(unexpected_end)?;
+        self.bytes = remaining;
+        Ok(bytes)
     }
+}
 
-    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+fn tmp_bin_error(reason: impl Display) -> EncodeError { EncodeError::markup::<TmpBinTag>(Extension::TMP_BIN, reason) }
+fn unexpected_end() -> EncodeError { tmp_bin_error("unexpected end of input") }
 
-    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+/// Decode a TmpBin payload from a [`TmpBinSource`].
+pub(crate) struct DeserializerTmpBin<S>
+{
+    source: S,
+}
+impl<R> DeserializerTmpBin<TmpBinReader<R>>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self
     {
-        Err(Default::default())
+        Self {
+            source: TmpBinReader {
+                reader,
+                peeked: None,
+                scratch: Vec::new(),
+            },
+        }
     }
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    pub fn get_ref(&self) -> &R { &self.source.reader }
+}
+impl<'de> DeserializerTmpBin<TmpBinSlice<'de>>
+{
+    /// The strings and the bytes are borrowed from `bytes`
+    pub fn from_slice(bytes: &'de [u8]) -> Self { Self { source: TmpBinSlice { bytes } } }
+}
+impl<'de, S> DeserializerTmpBin<S>
+where
+    S: TmpBinSource<'de>,
+{
+    /// Check the magic and the version. Must be called once before deserializing the root value.
+    pub fn read_header(&mut self) -> EncodeResult
     {
-        Err(Default::default())
+        if &self.read_array::<4>()? != TMP_BIN_MAGIC
+        {
+            return Err(tmp_bin_error("missing header"));
+        }
+        let [version] = self.read_array::<1>()?;
+        if version != TMP_BIN_VERSION
+        {
+            return Err(tmp_bin_error(format!("unsupported version {version}, expected {TMP_BIN_VERSION}")));
+        }
+        Ok(())
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    /// Check that all the bytes were consumed.
+    pub fn end(&mut self) -> EncodeResult
     {
-        Err(Default::default())
+        match self.source.read_byte()?
+        {
+            None => Ok(()),
+            Some(_) => Err(tmp_bin_error("unexpected trailing bytes")),
+        }
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    /// Read the start of a sequence, and return its length if known. See [`Self::has_next`].
+    pub fn read_seq_start(&mut self) -> EncodeResult<Option<usize>>
     {
-        Err(Default::default())
+        match self.read_tag()?
+        {
+            TmpBinTag::Seq => self.read_len().map(Some),
+            TmpBinTag::SeqUnsized => Ok(None),
+            tag => Err(tmp_bin_error(format!("expected a sequence, got {tag:?}"))),
+        }
     }
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    /// True if there is an other element in the sequence or map. `remaining` is `None` if the length is unknown.
+    pub fn has_next(&mut self, remaining: &mut Option<usize>) -> EncodeResult<bool>
     {
-        Err(Default::default())
+        match remaining
+        {
+            Some(0) => Ok(false),
+            Some(remaining) =>
+            {
+                *remaining -= 1;
+                Ok(true)
+            }
+            None =>
+            {
+                if self.peek_tag()? == TmpBinTag::End
+                {
+                    self.read_tag()?;
+                    Ok(false)
+                }
+                else
+                {
+                    Ok(true)
+                }
+            }
+        }
     }
 
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_array<const N: usize>(&mut self) -> EncodeResult<[u8; N]>
     {
-        Err(Default::default())
+        let mut array = [0; N];
+        self.source.read_exact(&mut array)?;
+        Ok(array)
     }
 
-    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn read_len(&mut self) -> EncodeResult<usize>
     {
-        visitor.visit_bytes(self.bytes.as_ref())
+        let mut len: usize = 0;
+        let mut shift = 0;
+        loop
+        {
+            let [byte] = self.read_array::<1>()?;
+            let bits = ((byte & 0x7F) as usize).checked_shl(shift).filter(|b| b >> shift == (byte & 0x7F) as usize);
+            len |= bits.ok_or_else(|| tmp_bin_error("length overflow"))?;
+            if byte & 0x80 == 0
+            {
+                return Ok(len);
+            }
+            shift += 7;
+        }
     }
 
-    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_byte_buf(self.bytes.into_owned())
-    }
+    fn peek_tag(&mut self) -> EncodeResult<TmpBinTag> { TmpBinTag::try_from(self.source.peek_byte()?.ok_or_else(unexpected_end)?) }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+    fn read_tag(&mut self) -> EncodeResult<TmpBinTag> { TmpBinTag::try_from(self.source.read_byte()?.ok_or_else(unexpected_end)?) }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    fn expect_tag(&mut self, expected: TmpBinTag) -> EncodeResult
     {
-        Err(Default::default())
+        let tag = self.read_tag()?;
+        if tag == expected
+        {
+            Ok(())
+        }
+        else
+        {
+            Err(tmp_bin_error(format!("expected {expected:?}, got {tag:?}")))
+        }
     }
+}
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value, Self::Error>
+impl<'de, S> Deserializer<'de> for &mut DeserializerTmpBin<S>
+where
+    S: TmpBinSource<'de>,
+{
+    type Error = EncodeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        match self.read_tag()?
+        {
+            TmpBinTag::Unit => visitor.visit_unit(),
+            TmpBinTag::None => visitor.visit_none(),
+            TmpBinTag::Some => visitor.visit_some(self),
+            TmpBinTag::False => visitor.visit_bool(false),
+            TmpBinTag::True => visitor.visit_bool(true),
+            TmpBinTag::I8 => visitor.visit_i8(i8::from_le_bytes(self.read_array()?)),
+            TmpBinTag::I16 => visitor.visit_i16(i16::from_le_bytes(self.read_array()?)),
+            TmpBinTag::I32 => visitor.visit_i32(i32::from_le_bytes(self.read_array()?)),
+            TmpBinTag::I64 => visitor.visit_i64(i64::from_le_bytes(self.read_array()?)),
+            TmpBinTag::I128 => visitor.visit_i128(i128::from_le_bytes(self.read_array()?)),
+            TmpBinTag::U8 => visitor.visit_u8(u8::from_le_bytes(self.read_array()?)),
+            TmpBinTag::U16 => visitor.visit_u16(u16::from_le_bytes(self.read_array()?)),
+            TmpBinTag::U32 => visitor.visit_u32(u32::from_le_bytes(self.read_array()?)),
+            TmpBinTag::U64 => visitor.visit_u64(u64::from_le_bytes(self.read_array()?)),
+            TmpBinTag::U128 => visitor.visit_u128(u128::from_le_bytes(self.read_array()?)),
+            TmpBinTag::F32 => visitor.visit_f32(f32::from_le_bytes(self.read_array()?)),
+            TmpBinTag::F64 => visitor.visit_f64(f64::from_le_bytes(self.read_array()?)),
+            TmpBinTag::Char =>
+            {
+                let code = u32::from_le_bytes(self.read_array()?);
+                let c = char::from_u32(code).ok_or_else(|| tmp_bin_error(format!("invalid char {code:#x}")))?;
+                visitor.visit_char(c)
+            }
+            TmpBinTag::Str =>
+            {
+                let len = self.read_len()?;
+                match self.source.read_slice(len)?
+                {
+                    TmpBinBytes::Borrowed(bytes) => visitor.visit_borrowed_str(str::from_utf8(bytes)?),
+                    TmpBinBytes::Copied(bytes) => visitor.visit_str(str::from_utf8(bytes)?),
+                }
+            }
+            TmpBinTag::Bytes =>
+            {
+                let len = self.read_len()?;
+                match self.source.read_slice(len)?
+                {
+                    TmpBinBytes::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+                    TmpBinBytes::Copied(bytes) => visitor.visit_bytes(bytes),
+                }
+            }
+            TmpBinTag::Seq =>
+            {
+                let len = self.read_len()?;
+                visitor.visit_seq(DeserializerTmpBinCompound {
+                    deserializer: self,
+                    remaining: Some(len),
+                })
+            }
+            TmpBinTag::SeqUnsized => visitor.visit_seq(DeserializerTmpBinCompound {
+                deserializer: self,
+                remaining: None,
+            }),
+            TmpBinTag::Map =>
+            {
+                let len = self.read_len()?;
+                visitor.visit_map(DeserializerTmpBinCompound {
+                    deserializer: self,
+                    remaining: Some(len),
+                })
+            }
+            TmpBinTag::MapUnsized => visitor.visit_map(DeserializerTmpBinCompound {
+                deserializer: self,
+                remaining: None,
+            }),
+            TmpBinTag::Variant =>
+            {
+                let variant_index = self.read_len()?;
+                let variant_index = u32::try_from(variant_index).map_err(|_| tmp_bin_error("variant index overflow"))?;
+                visitor.visit_enum(DeserializerTmpBinVariant {
+                    deserializer: self,
+                    variant_index,
+                })
+            }
+            TmpBinTag::End => Err(tmp_bin_error("unexpected end tag")),
+        }
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn is_human_readable(&self) -> bool { false }
+}
+
+struct DeserializerTmpBinCompound<'b, S>
+{
+    deserializer: &'b mut DeserializerTmpBin<S>,
+    /// `None` if the length is unknown, the compound is then terminated by an `End` tag.
+    remaining: Option<usize>,
+}
+impl<'b, 'de, S> SeqAccess<'de> for DeserializerTmpBinCompound<'b, S>
+where
+    S: TmpBinSource<'de>,
+{
+    type Error = EncodeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
-        V: Visitor<'de>,
+        T: DeserializeSeed<'de>,
     {
-        Err(Default::default())
+        if self.deserializer.has_next(&mut self.remaining)?
+        {
+            seed.deserialize(&mut *self.deserializer).map(Some)
+        }
+        else
+        {
+            Ok(None)
+        }
     }
 
-    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn size_hint(&self) -> Option<usize> { self.remaining }
+}
+impl<'b, 'de, S> MapAccess<'de> for DeserializerTmpBinCompound<'b, S>
+where
+    S: TmpBinSource<'de>,
+{
+    type Error = EncodeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        V: Visitor<'de>,
+        K: DeserializeSeed<'de>,
     {
-        Err(Default::default())
+        if self.deserializer.has_next(&mut self.remaining)?
+        {
+            seed.deserialize(&mut *self.deserializer).map(Some)
+        }
+        else
+        {
+            Ok(None)
+        }
     }
 
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        Err(Default::default())
+        seed.deserialize(&mut *self.deserializer)
     }
 
-    fn deserialize_struct<V>(self, _name: &'static str, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
+    fn size_hint(&self) -> Option<usize> { self.remaining }
+}
+
+struct DeserializerTmpBinVariant<'b, S>
+{
+    deserializer: &'b mut DeserializerTmpBin<S>,
+    variant_index: u32,
+}
+impl<'b, 'de, S> EnumAccess<'de> for DeserializerTmpBinVariant<'b, S>
+where
+    S: TmpBinSource<'de>,
+{
+    type Error = EncodeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        Err(Default::default())
+        let variant = seed.deserialize(IntoDeserializer::<EncodeError>::into_deserializer(self.variant_index))?;
+        Ok((variant, self))
     }
+}
+impl<'b, 'de, S> VariantAccess<'de> for DeserializerTmpBinVariant<'b, S>
+where
+    S: TmpBinSource<'de>,
+{
+    type Error = EncodeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> { self.deserializer.expect_tag(TmpBinTag::Unit) }
 
-    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where
-        V: Visitor<'de>,
+        T: DeserializeSeed<'de>,
     {
-        Err(Default::default())
+        seed.deserialize(self.deserializer)
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserializer.deserialize_any(visitor)
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserializer.deserialize_any(visitor)
     }
-
-    fn is_human_readable(&self) -> bool { false }
 }
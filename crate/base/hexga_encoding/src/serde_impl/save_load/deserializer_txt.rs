@@ -1,120 +1,85 @@
 use super::*;
+use serde::de::{DeserializeSeed, IntoDeserializer, SeqAccess};
 
+/// Read what [`SerializerTxt`] write: scalars as raw text, and sequences of scalars one element per line.
 pub(crate) struct DeserializerTxt<'a>
 {
     pub(crate) txt: Cow<'a, str>,
+    /// True when deserializing one line of a sequence
+    pub(crate) in_seq: bool,
 }
 impl<'a> DeserializerTxt<'a>
 {
-    pub fn new(txt: impl Into<Cow<'a, str>>) -> Self { Self { txt: txt.into() } }
-}
-impl<'a, 'de> Deserializer<'de> for DeserializerTxt<'a>
-{
-    type Error = EncodeError;
-
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_string(visitor)
-    }
-
-    fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
-
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
-
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
-
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
+    pub fn new(txt: impl Into<Cow<'a, str>>) -> Self
     {
-        Err(Default::default())
+        Self {
+            txt: txt.into(),
+            in_seq: false,
+        }
     }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+    fn error(reason: impl Display) -> EncodeError { EncodeError::markup::<Self>(Extension::TXT, reason) }
 
-    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn parse<T>(&self) -> EncodeResult<T>
     where
-        V: Visitor<'de>,
+        T: std::str::FromStr,
     {
-        Err(Default::default())
-    }
-
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
+        let txt = self.txt.trim();
+        txt.parse()
+            .map_err(|_| Self::error(format!("expected {}, got {txt:?}", std::any::type_name::<T>())))
     }
+}
 
-    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+macro_rules! deserialize_parse {
+    ($($fn_name:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $fn_name<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
 
-    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+impl<'a, 'de> Deserializer<'de> for DeserializerTxt<'a>
+{
+    type Error = EncodeError;
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserialize_string(visitor)
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        Err(Default::default())
-    }
+    deserialize_parse!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    );
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let mut chars = self.txt.chars();
-        if let Some(c) = chars.next()
+        let txt = self.txt.trim();
+        let mut chars = txt.chars();
+        match (chars.next(), chars.next())
         {
-            if chars.next().is_none()
-            {
-                visitor.visit_char(c)
-            }
-            else
-            {
-                Err(EncodeError::custom("expected exactly one character"))
-            }
-        }
-        else
-        {
-            Err(EncodeError::custom("expected exactly one character, got empty string"))
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Self::error(format!("expected exactly one character, got {txt:?}"))),
         }
     }
 
@@ -136,99 +101,143 @@ impl<'a, 'de> Deserializer<'de> for DeserializerTxt<'a>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<String>::unsupported("bytes"))
     }
 
     fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<String>::unsupported("bytes"))
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        if self.txt.trim().is_empty()
+        {
+            visitor.visit_none()
+        }
+        else
+        {
+            visitor.visit_some(self)
+        }
     }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        if self.txt.trim().is_empty()
+        {
+            visitor.visit_unit()
+        }
+        else
+        {
+            Err(Self::error(format!("expected nothing, got {:?}", self.txt)))
+        }
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        if self.in_seq
+        {
+            return Err(SerializerTxt::<String>::unsupported("nested sequences"));
+        }
+        visitor.visit_seq(DeserializerTxtLines { lines: self.txt.lines() })
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserialize_seq(visitor)
     }
 
-    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<String>::unsupported("maps"))
     }
 
     fn deserialize_struct<V>(self, _name: &'static str, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<String>::unsupported("structs"))
     }
 
-    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        // Only unit variants, the deserializer will complain for the other variants
+        visitor.visit_enum(IntoDeserializer::<EncodeError>::into_deserializer(self.txt.trim()))
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        self.deserialize_str(visitor)
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Default::default())
+        visitor.visit_unit()
     }
 
     fn is_human_readable(&self) -> bool { false }
 }
+
+struct DeserializerTxtLines<'a>
+{
+    lines: std::str::Lines<'a>,
+}
+impl<'a, 'de> SeqAccess<'de> for DeserializerTxtLines<'a>
+{
+    type Error = EncodeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.lines.next()
+        {
+            Some(line) => seed
+                .deserialize(DeserializerTxt {
+                    txt: line.into(),
+                    in_seq: true,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
@@ -14,3 +14,9 @@ pub(crate) use deserializer_txt::*;
 
 mod deserializer_tmp_bin;
 pub(crate) use deserializer_tmp_bin::*;
+
+mod deserializer_ron_reader;
+pub(crate) use deserializer_ron_reader::*;
+
+mod deserializer_limited;
+pub(crate) use deserializer_limited::*;
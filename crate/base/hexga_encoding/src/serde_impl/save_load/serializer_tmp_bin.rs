@@ -1,5 +1,92 @@
 use super::*;
 
+/// Written at the start of every TmpBin payload, followed by [`TMP_BIN_VERSION`].
+pub(crate) const TMP_BIN_MAGIC: &[u8; 4] = b"HXTB";
+/// Bumped each time the encoding change. Payload with a different version are rejected.
+pub(crate) const TMP_BIN_VERSION: u8 = 0;
+
+/// Every value is prefixed by a tag, so the format is self describing enough for `deserialize_any`.
+///
+/// - Scalars are stored in little endian after their tag.
+/// - `Str`, `Bytes`, `Seq` and `Map` are followed by a length encoded as a LEB128 varint.
+/// - `SeqUnsized` and `MapUnsized` are used when the length is unknown, and are terminated by an `End` tag.
+/// - `Variant` is followed by the variant index (varint), then by the content of the variant.
+/// - Structs and tuples are stored like sequences (the fields names are not saved).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TmpBinTag
+{
+    Unit,
+    None,
+    Some,
+    False,
+    True,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bytes,
+    Seq,
+    SeqUnsized,
+    Map,
+    MapUnsized,
+    Variant,
+    End,
+}
+impl TmpBinTag
+{
+    pub const ALL: &'static [Self] = &[
+        Self::Unit,
+        Self::None,
+        Self::Some,
+        Self::False,
+        Self::True,
+        Self::I8,
+        Self::I16,
+        Self::I32,
+        Self::I64,
+        Self::I128,
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::U128,
+        Self::F32,
+        Self::F64,
+        Self::Char,
+        Self::Str,
+        Self::Bytes,
+        Self::Seq,
+        Self::SeqUnsized,
+        Self::Map,
+        Self::MapUnsized,
+        Self::Variant,
+        Self::End,
+    ];
+}
+impl TryFrom<u8> for TmpBinTag
+{
+    type Error = EncodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error>
+    {
+        Self::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| EncodeError::markup::<Self>(Extension::TMP_BIN, format!("invalid tag {value}")))
+    }
+}
+
 pub(crate) struct SerializerTmpBin<W>
 where
     W: std::io::Write,
@@ -11,237 +98,349 @@ where
     W: std::io::Write,
 {
     pub fn new(writer: W) -> Self { Self { writer } }
+
+    /// Write the magic and the version. Must be called once before serializing the root value.
+    pub fn write_header(&mut self) -> EncodeResult
+    {
+        self.writer.write_all(TMP_BIN_MAGIC)?;
+        self.writer.write_all(&[TMP_BIN_VERSION])?;
+        Ok(())
+    }
+
+    fn write_tag(&mut self, tag: TmpBinTag) -> EncodeResult
+    {
+        self.writer.write_all(&[tag as u8])?;
+        Ok(())
+    }
+
+    fn write_tagged(&mut self, tag: TmpBinTag, bytes: &[u8]) -> EncodeResult
+    {
+        self.write_tag(tag)?;
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn write_len(&mut self, mut len: usize) -> EncodeResult
+    {
+        let mut buf = [0u8; 10];
+        let mut i = 0;
+        loop
+        {
+            let byte = (len & 0x7F) as u8;
+            len >>= 7;
+            if len == 0
+            {
+                buf[i] = byte;
+                i += 1;
+                break;
+            }
+            buf[i] = byte | 0x80;
+            i += 1;
+        }
+        self.writer.write_all(&buf[..i])?;
+        Ok(())
+    }
+
+    fn write_tagged_len(&mut self, tag: TmpBinTag, len: usize) -> EncodeResult
+    {
+        self.write_tag(tag)?;
+        self.write_len(len)
+    }
+
+    fn begin_compound(&mut self, sized: TmpBinTag, unsized_tag: TmpBinTag, len: Option<usize>) -> EncodeResult<SerializerTmpBinCompound<'_, W>>
+    {
+        match len
+        {
+            Some(len) => self.write_tagged_len(sized, len)?,
+            None => self.write_tag(unsized_tag)?,
+        }
+        Ok(SerializerTmpBinCompound {
+            serializer: self,
+            remaining: len,
+        })
+    }
+}
+
+pub(crate) struct SerializerTmpBinCompound<'a, W>
+where
+    W: std::io::Write,
+{
+    serializer: &'a mut SerializerTmpBin<W>,
+    /// `None` if the length is unknown
+    remaining: Option<usize>,
+}
+impl<'a, W> SerializerTmpBinCompound<'a, W>
+where
+    W: std::io::Write,
+{
+    fn element<T>(&mut self, value: &T) -> EncodeResult
+    where
+        T: ?Sized + Serialize,
+    {
+        if let Some(remaining) = &mut self.remaining
+        {
+            *remaining = remaining
+                .checked_sub(1)
+                .ok_or_else(|| EncodeError::markup::<T>(Extension::TMP_BIN, "more elements than the announced length"))?;
+        }
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn finish(self) -> EncodeResult
+    {
+        match self.remaining
+        {
+            Some(0) => Ok(()),
+            Some(_) => Err(EncodeError::markup::<Self>(Extension::TMP_BIN, "less elements than the announced length")),
+            None => self.serializer.write_tag(TmpBinTag::End),
+        }
+    }
 }
 
-impl<W> SerializeSeq for SerializerTmpBin<W>
+impl<'a, W> SerializeSeq for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeTuple for SerializerTmpBin<W>
+impl<'a, W> SerializeTuple for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeTupleStruct for SerializerTmpBin<W>
+impl<'a, W> SerializeTupleStruct for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_field<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeTupleVariant for SerializerTmpBin<W>
+impl<'a, W> SerializeTupleVariant for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeMap for SerializerTmpBin<W>
+impl<'a, W> SerializeMap for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_key<T>(&mut self, _key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(key)
     }
 
-    fn serialize_value<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        value.serialize(&mut *self.serializer)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeStruct for SerializerTmpBin<W>
+impl<'a, W> SerializeStruct for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
-impl<W> SerializeStructVariant for SerializerTmpBin<W>
+impl<'a, W> SerializeStructVariant for SerializerTmpBinCompound<'a, W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.element(value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
 }
 
-impl<W> Serializer for SerializerTmpBin<W>
+impl<'a, W> Serializer for &'a mut SerializerTmpBin<W>
 where
     W: std::io::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    type SerializeSeq = SerializerTmpBin<W>;
-    type SerializeTuple = SerializerTmpBin<W>;
-    type SerializeTupleStruct = SerializerTmpBin<W>;
-    type SerializeTupleVariant = SerializerTmpBin<W>;
-    type SerializeMap = SerializerTmpBin<W>;
-    type SerializeStruct = SerializerTmpBin<W>;
-    type SerializeStructVariant = SerializerTmpBin<W>;
+    type SerializeSeq = SerializerTmpBinCompound<'a, W>;
+    type SerializeTuple = SerializerTmpBinCompound<'a, W>;
+    type SerializeTupleStruct = SerializerTmpBinCompound<'a, W>;
+    type SerializeTupleVariant = SerializerTmpBinCompound<'a, W>;
+    type SerializeMap = SerializerTmpBinCompound<'a, W>;
+    type SerializeStruct = SerializerTmpBinCompound<'a, W>;
+    type SerializeStructVariant = SerializerTmpBinCompound<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.write_tag(if v { TmpBinTag::True } else { TmpBinTag::False }) }
 
-    fn serialize_bool(self, _: bool) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::I8, &v.to_le_bytes()) }
 
-    fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::I16, &v.to_le_bytes()) }
 
-    fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::I32, &v.to_le_bytes()) }
 
-    fn serialize_i32(self, _: i32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::I64, &v.to_le_bytes()) }
 
-    fn serialize_i64(self, _: i64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::I128, &v.to_le_bytes()) }
 
-    fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::U8, &v.to_le_bytes()) }
 
-    fn serialize_u16(self, _: u16) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::U16, &v.to_le_bytes()) }
 
-    fn serialize_u32(self, _: u32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::U32, &v.to_le_bytes()) }
 
-    fn serialize_u64(self, _: u64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::U64, &v.to_le_bytes()) }
 
-    fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::U128, &v.to_le_bytes()) }
 
-    fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::F32, &v.to_le_bytes()) }
 
-    fn serialize_char(self, _: char) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::F64, &v.to_le_bytes()) }
 
-    fn serialize_str(self, _: &str) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { self.write_tagged(TmpBinTag::Char, &(v as u32).to_le_bytes()) }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error>
+    {
+        self.write_tagged_len(TmpBinTag::Str, v.len())?;
+        self.writer.write_all(v.as_bytes())?;
+        Ok(())
+    }
 
-    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error>
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error>
     {
-        self.writer.write(v)?;
-        Ok(self.writer)
+        self.write_tagged_len(TmpBinTag::Bytes, v.len())?;
+        self.writer.write_all(v)?;
+        Ok(())
     }
 
-    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { self.write_tag(TmpBinTag::None) }
 
-    fn serialize_some<T>(self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.write_tag(TmpBinTag::Some)?;
+        value.serialize(self)
     }
 
-    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { self.write_tag(TmpBinTag::Unit) }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { self.serialize_unit() }
 
-    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str) -> Result<Self::Ok, Self::Error>
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<Self::Ok, Self::Error>
     {
-        Err(Default::default())
+        self.write_tagged_len(TmpBinTag::Variant, variant_index as usize)?;
+        self.serialize_unit()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T>(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_newtype_variant<T>(self, _name: &'static str, variant_index: u32, _variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        self.write_tagged_len(TmpBinTag::Variant, variant_index as usize)?;
+        value.serialize(self)
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { Err(Default::default()) }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { self.begin_compound(TmpBinTag::Seq, TmpBinTag::SeqUnsized, len) }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> { Err(Default::default()) }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> { self.serialize_seq(Some(len)) }
 
-    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { Err(Default::default()) }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { self.serialize_seq(Some(len)) }
 
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error>
     {
-        Err(Default::default())
+        self.write_tagged_len(TmpBinTag::Variant, variant_index as usize)?;
+        self.serialize_seq(Some(len))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { Err(Default::default()) }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { self.begin_compound(TmpBinTag::Map, TmpBinTag::MapUnsized, len) }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> { Err(Default::default()) }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> { self.serialize_seq(Some(len)) }
 
     fn serialize_struct_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error>
     {
-        Err(Default::default())
+        self.write_tagged_len(TmpBinTag::Variant, variant_index as usize)?;
+        self.serialize_seq(Some(len))
     }
 
     fn is_human_readable(&self) -> bool { false }
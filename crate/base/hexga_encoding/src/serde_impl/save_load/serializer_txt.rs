@@ -1,226 +1,273 @@
 use super::*;
 
+/// Write scalars (strings, numbers, bools, chars, unit variants) as raw text.
+///
+/// Sequences and tuples of scalars are written one element per line, each line ending with `\n`.
+/// Anything more structural (maps, structs, nested sequences, enums with data, bytes) is rejected.
 pub(crate) struct SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
     writer: W,
+    /// True when serializing the elements of a sequence
+    in_seq: bool,
 }
 impl<W> SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    pub fn new(writer: W) -> Self { Self { writer } }
+    pub fn new(writer: W) -> Self { Self { writer, in_seq: false } }
+
+    pub(crate) fn unsupported(what: &str) -> EncodeError
+    {
+        EncodeError::markup::<Self>(Extension::TXT, format!("{what} are not supported, only scalars and sequences of scalars are"))
+    }
+
+    fn write_display(&mut self, value: impl Display) -> EncodeResult
+    {
+        write!(self.writer, "{value}")?;
+        Ok(())
+    }
+
+    fn begin_seq(&mut self) -> EncodeResult<&mut Self>
+    {
+        if self.in_seq
+        {
+            return Err(Self::unsupported("nested sequences"));
+        }
+        self.in_seq = true;
+        Ok(self)
+    }
 }
 
-impl<W> SerializeSeq for SerializerTxt<W>
+impl<W> SerializeSeq for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        value.serialize(&mut **self)?;
+        self.writer.write_char('\n')?;
+        Ok(())
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error>
+    {
+        self.in_seq = false;
+        Ok(())
+    }
 }
-impl<W> SerializeTuple for SerializerTxt<W>
+impl<W> SerializeTuple for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        SerializeSeq::serialize_element(self, value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { SerializeSeq::end(self) }
 }
-impl<W> SerializeTupleStruct for SerializerTxt<W>
+impl<W> SerializeTupleStruct for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    fn serialize_field<T>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        SerializeSeq::serialize_element(self, value)
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { SerializeSeq::end(self) }
 }
-impl<W> SerializeTupleVariant for SerializerTxt<W>
+impl<W> SerializeTupleVariant for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
     fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("enums with data"))
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { Err(SerializerTxt::<W>::unsupported("enums with data")) }
 }
-impl<W> SerializeMap for SerializerTxt<W>
+impl<W> SerializeMap for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
     fn serialize_key<T>(&mut self, _key: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("maps"))
     }
 
     fn serialize_value<T>(&mut self, _value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("maps"))
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { Err(SerializerTxt::<W>::unsupported("maps")) }
 }
-impl<W> SerializeStruct for SerializerTxt<W>
+impl<W> SerializeStruct for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
     fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("structs"))
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { Err(SerializerTxt::<W>::unsupported("structs")) }
 }
-impl<W> SerializeStructVariant for SerializerTxt<W>
+impl<W> SerializeStructVariant for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
     fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("enums with data"))
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn end(self) -> Result<Self::Ok, Self::Error> { Err(SerializerTxt::<W>::unsupported("enums with data")) }
 }
 
-impl<W> Serializer for SerializerTxt<W>
+impl<W> Serializer for &mut SerializerTxt<W>
 where
     W: std::fmt::Write,
 {
-    type Ok = W;
+    type Ok = ();
     type Error = EncodeError;
 
-    type SerializeSeq = SerializerTxt<W>;
-    type SerializeTuple = SerializerTxt<W>;
-    type SerializeTupleStruct = SerializerTxt<W>;
-    type SerializeTupleVariant = SerializerTxt<W>;
-    type SerializeMap = SerializerTxt<W>;
-    type SerializeStruct = SerializerTxt<W>;
-    type SerializeStructVariant = SerializerTxt<W>;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_bool(self, _: bool) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_i32(self, _: i32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_i64(self, _: i64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_u16(self, _: u16) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_u32(self, _: u32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_u64(self, _: u64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
 
-    fn serialize_char(mut self, c: char) -> Result<Self::Ok, Self::Error>
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.write_display(v) }
+
+    fn serialize_char(self, c: char) -> Result<Self::Ok, Self::Error>
     {
+        // trimmed when loading
+        if c.is_whitespace()
+        {
+            return Err(SerializerTxt::<W>::unsupported("whitespace characters"));
+        }
         self.writer.write_char(c)?;
-        Ok(self.writer)
+        Ok(())
     }
 
-    fn serialize_str(mut self, s: &str) -> Result<Self::Ok, Self::Error>
+    fn serialize_str(self, s: &str) -> Result<Self::Ok, Self::Error>
     {
+        if self.in_seq && s.contains(['\n', '\r'])
+        {
+            return Err(SerializerTxt::<W>::unsupported("line breaks inside sequences"));
+        }
         self.writer.write_str(s)?;
-        Ok(self.writer)
+        Ok(())
     }
 
-    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> { Err(SerializerTxt::<W>::unsupported("bytes")) }
 
-    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Ok(()) }
 
-    fn serialize_some<T>(self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        value.serialize(self)
     }
 
-    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Ok(()) }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { Err(Default::default()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { Ok(()) }
 
-    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str) -> Result<Self::Ok, Self::Error>
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error>
     {
-        Err(Default::default())
+        self.writer.write_str(variant)?;
+        Ok(())
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("enums with data"))
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { Err(Default::default()) }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { self.begin_seq() }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> { Err(Default::default()) }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> { self.begin_seq() }
 
-    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { Err(Default::default()) }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { self.begin_seq() }
 
     fn serialize_tuple_variant(
         self,
@@ -230,12 +277,15 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error>
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("enums with data"))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { Err(Default::default()) }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { Err(SerializerTxt::<W>::unsupported("maps")) }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> { Err(Default::default()) }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error>
+    {
+        Err(SerializerTxt::<W>::unsupported("structs"))
+    }
 
     fn serialize_struct_variant(
         self,
@@ -245,7 +295,7 @@ where
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error>
     {
-        Err(Default::default())
+        Err(SerializerTxt::<W>::unsupported("enums with data"))
     }
 
     fn is_human_readable(&self) -> bool { false }
@@ -0,0 +1,208 @@
+use super::*;
+use serde::de::{DeserializeSeed, Error, MapAccess, SeqAccess};
+
+/// A type whose saves are wrapped in an envelope with its [name](Self::TYPE_NAME) and [schema version](Self::VERSION).
+///
+/// Usually implemented with `#[derive(Save, Load)]` and `#[io(version = N)]`.
+/// When `N > 1`, the type must also implement [`Migrate`] to load the saves of the previous versions.
+pub trait Versioned: Sized
+{
+    /// Written in the envelope, and checked when loading
+    const TYPE_NAME: &'static str;
+    const VERSION: u32;
+
+    /// Deserialize the representation of the given `version`, upgrading it to the current one if needed.
+    ///
+    /// Only the current version is supported by default.
+    fn deserialize_version<'de, D>(version: u32, deserializer: D) -> Result<Self, D::Error>
+    where
+        Self: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserialize_current_version(version, deserializer)
+    }
+}
+
+fn deserialize_current_version<'de, T, D>(version: u32, deserializer: D) -> Result<T, D::Error>
+where
+    T: Versioned + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    match version.cmp(&T::VERSION)
+    {
+        std::cmp::Ordering::Equal => T::deserialize(deserializer),
+        std::cmp::Ordering::Less => Err(D::Error::custom(format_args!(
+            "unsupported old version {version} of {}, expected {}",
+            T::TYPE_NAME,
+            T::VERSION
+        ))),
+        std::cmp::Ordering::Greater => Err(D::Error::custom(format_args!(
+            "version {version} of {} is newer than the supported version {}",
+            T::TYPE_NAME,
+            T::VERSION
+        ))),
+    }
+}
+
+/// Upgrade a [`Versioned`] type from its previous representation.
+///
+/// Migrations are chained: a save of version `1` is loaded as `Previous::Previous`, then migrated twice to reach version `3`.
+pub trait Migrate: Versioned
+{
+    /// The representation at the previous version
+    type Previous: Versioned + for<'de> Deserialize<'de>;
+
+    fn migrate(previous: Self::Previous) -> Self;
+
+    /// Used by `#[io(version = N)]` when `N > 1`
+    fn deserialize_version_or_migrate<'de, D>(version: u32, deserializer: D) -> Result<Self, D::Error>
+    where
+        Self: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        if version < Self::VERSION
+        {
+            Self::Previous::deserialize_version(version, deserializer).map(Self::migrate)
+        }
+        else
+        {
+            deserialize_current_version(version, deserializer)
+        }
+    }
+}
+
+const VERSIONED_NAME: &str = "Versioned";
+const VERSIONED_FIELDS: &[&str] = &["type", "version", "value"];
+
+/// The envelope of a [`Versioned`] value: `(type: "Config", version: 2, value: (...))`
+pub struct VersionedSave<'a, T: ?Sized>
+{
+    pub type_name: &'a str,
+    pub version: u32,
+    pub value: &'a T,
+}
+impl<'a, T: ?Sized> VersionedSave<'a, T>
+{
+    pub const fn new(type_name: &'a str, version: u32, value: &'a T) -> Self { Self { type_name, version, value } }
+}
+impl<'a, T> Serialize for VersionedSave<'a, T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct(VERSIONED_NAME, VERSIONED_FIELDS.len())?;
+        s.serialize_field(VERSIONED_FIELDS[0], self.type_name)?;
+        s.serialize_field(VERSIONED_FIELDS[1], &self.version)?;
+        s.serialize_field(VERSIONED_FIELDS[2], self.value)?;
+        s.end()
+    }
+}
+
+/// Read a [`VersionedSave`] envelope, and [migrate](Migrate) the value if it was saved with an older version
+pub struct VersionedLoad<T>(pub T);
+
+impl<'de, T> Deserialize<'de> for VersionedLoad<T>
+where
+    T: Versioned + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(VERSIONED_NAME, VERSIONED_FIELDS, VersionedVisitor(std::marker::PhantomData))
+    }
+}
+
+struct VersionedVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> VersionedVisitor<T>
+where
+    T: Versioned,
+{
+    fn check_type_name<E: Error>(type_name: &str) -> Result<(), E>
+    {
+        if type_name == T::TYPE_NAME
+        {
+            Ok(())
+        }
+        else
+        {
+            Err(E::custom(format_args!("expected a save of {}, got {type_name}", T::TYPE_NAME)))
+        }
+    }
+}
+
+impl<'de, T> Visitor<'de> for VersionedVisitor<T>
+where
+    T: Versioned + Deserialize<'de>,
+{
+    type Value = VersionedLoad<T>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result { write!(formatter, "a versioned {}", T::TYPE_NAME) }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let type_name: Cow<'de, str> = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        Self::check_type_name(&type_name)?;
+        let version: u32 = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        seq.next_element_seed(VersionSeed::<T>(version, std::marker::PhantomData))?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))
+            .map(VersionedLoad)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut has_type = false;
+        let mut version = None;
+        let mut value = None;
+        while let Some(key) = map.next_key::<Cow<'de, str>>()?
+        {
+            match key.as_ref()
+            {
+                "type" if has_type => return Err(A::Error::duplicate_field("type")),
+                "type" =>
+                {
+                    Self::check_type_name(&map.next_value::<Cow<'de, str>>()?)?;
+                    has_type = true;
+                }
+                "version" if version.is_some() => return Err(A::Error::duplicate_field("version")),
+                "version" => version = Some(map.next_value::<u32>()?),
+                "value" if value.is_some() => return Err(A::Error::duplicate_field("value")),
+                "value" => match version
+                {
+                    Some(version) => value = Some(map.next_value_seed(VersionSeed::<T>(version, std::marker::PhantomData))?),
+                    None => return Err(A::Error::custom("the version must be before the value")),
+                },
+                _ => return Err(A::Error::unknown_field(&key, VERSIONED_FIELDS)),
+            }
+        }
+        if !has_type
+        {
+            return Err(A::Error::missing_field("type"));
+        }
+        value.map(VersionedLoad).ok_or_else(|| A::Error::missing_field("value"))
+    }
+}
+
+struct VersionSeed<T>(u32, std::marker::PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for VersionSeed<T>
+where
+    T: Versioned + Deserialize<'de>,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_version(self.0, deserializer)
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+/// Guess the extension of some encoded bytes by looking at their first bytes.
+///
+/// Binary formats are recognized by their magic number, text formats by their first meaningful character.
+/// Return `None` if nothing matched (ex: a JSON string or number, a TOML file starting with a comment...).
+pub fn sniff_extension(bytes: &[u8]) -> Option<&'static extension> { sniff_magic(bytes).or_else(|| sniff_text(bytes)) }
+
+const MAGICS: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", Extension::PNG),
+    (b"\xFF\xD8\xFF", Extension::JPEG),
+    (b"GIF87a", Extension::GIF),
+    (b"GIF89a", Extension::GIF),
+    (b"\x1F\x8B", Extension::GZIP),
+    (b"\x28\xB5\x2F\xFD", Extension::ZSTD),
+    (b"HXTB", Extension::TMP_BIN),
+    (b"HXCK\x01", Extension::CRC32),
+    (b"HXCK\x02", Extension::HMAC),
+    (b"HXPK", Extension::PACK),
+];
+
+/// Wrap other bytes, so they don't take the extension arguments
+const ENVELOPES: &[&str] = &[Extension::GZIP, Extension::ZSTD, Extension::CRC32, Extension::HMAC];
+
+/// The binary formats, their magic number leave no doubt
+fn sniff_magic(bytes: &[u8]) -> Option<&'static extension> { MAGICS.iter().find(|(magic, _)| bytes.starts_with(magic)).map(|(_, ext)| *ext) }
+
+fn sniff_text(bytes: &[u8]) -> Option<&'static extension>
+{
+    let text = bytes.strip_prefix("\u{FEFF}".as_bytes()).unwrap_or(bytes).trim_ascii_start();
+    let is_ident = |c: &u8| c.is_ascii_alphanumeric() || *c == b'_';
+
+    match text.first()?
+    {
+        b'<' => Some(Extension::XML),
+        b'(' => Some(Extension::RON),
+        b'#' if text.starts_with(b"#![") => Some(Extension::RON),
+        b'{' => Some(Extension::JSON),
+        b'[' =>
+        {
+            // `[section]` is a TOML table, `[1, 2]` or `["a"]` a JSON array
+            let name = &text[1..];
+            let len = name.iter().take_while(|c| is_ident(c) || **c == b'.' || **c == b'-').count();
+            if len > 0 && name.get(len) == Some(&b']') && !name[0].is_ascii_digit()
+            {
+                Some(Extension::TOML)
+            }
+            else
+            {
+                Some(Extension::JSON)
+            }
+        }
+        c if c.is_ascii_alphabetic() || *c == b'_' =>
+        {
+            // `Name(...)` is a RON struct, `name = ...` a TOML key and `name: ...` a YAML key
+            let len = text.iter().take_while(|c| is_ident(c)).count();
+            match text[len..].trim_ascii_start().first()
+            {
+                Some(b'(') => Some(Extension::RON),
+                Some(b'=') => Some(Extension::TOML),
+                Some(b':') => Some(Extension::YAML),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The last part of the extension, without the extension arguments and aliases
+fn main_extension(extension: &extension) -> &extension
+{
+    match extension.rsplit('.').next().unwrap_or(extension)
+    {
+        "jpg" => Extension::JPEG,
+        "yml" => Extension::YAML,
+        ext => ext,
+    }
+}
+
+/// Called when decoding `bytes` as `extension` failed: retry with the sniffed extension when it is supported,
+/// or explain the mismatch with [`EncodeError::ExtensionMismatch`].
+///
+/// The `args` of the caller are kept, ex: `nested.ron` is retried as `nested.json`,
+/// but not from or to the compressed and checked bytes, their content is sniffed again once opened.
+///
+/// A binary format is never replaced by a text format guess, ex: a `.ron.gz` that is not compressed is an error.
+pub(crate) fn retry_sniffed<T>(
+    bytes: &[u8],
+    extension: &extension,
+    args: ExtensionArgs<'_>,
+    err: EncodeError,
+    is_supported: impl Fn(&extension) -> bool,
+    retry: impl FnOnce(&extension) -> EncodeResult<T>,
+) -> EncodeResult<T>
+{
+    let Some(detected) = sniff_extension(bytes).filter(|detected| main_extension(detected) != main_extension(extension))
+    else
+    {
+        return Err(err);
+    };
+    let certain = sniff_magic(bytes).is_some();
+    let expect_binary = MAGICS.iter().any(|(_, ext)| *ext == main_extension(extension));
+
+    if is_supported(detected) && (certain || !expect_binary)
+    {
+        // Text detection is only an heuristic, the original error is more relevant if the retry fail
+        let detected = match ENVELOPES.contains(&detected) || ENVELOPES.contains(&main_extension(extension))
+        {
+            true => detected.to_owned(),
+            false => args.join_extension(detected),
+        };
+        return retry(&detected).map_err(|retry_err| if certain { retry_err } else { err });
+    }
+    if certain || expect_binary
+    {
+        return Err(EncodeError::ExtensionMismatch {
+            extension: extension.to_owned().into(),
+            detected: detected.into(),
+        });
+    }
+    Err(err)
+}
@@ -22,6 +22,8 @@ pub mod prelude
 /// data:image/png;base64,
 /// ```
 ///
+/// The media type can be omitted (`data:,Hello`), it default to `text/plain`.
+///
 /// # Usage
 ///
 /// ```rust
@@ -32,6 +34,10 @@ pub mod prelude
 /// assert_eq!(url.media_type, "image");
 /// assert_eq!(url.extension, "png");
 /// assert_eq!(url.encoding, Some("base64"));
+///
+/// let url = UrlDataMeta::try_from("data:text/plain;charset=UTF-8,").unwrap();
+/// assert_eq!(url.charset(), Some("UTF-8"));
+/// assert_eq!(url.encoding, None);
 /// ```
 #[derive(Debug, PartialEq, Eq)]
 pub struct UrlDataMeta<'a>
@@ -45,6 +51,9 @@ pub struct UrlDataMeta<'a>
     /// The file extension/subtype, e.g., "png"
     pub extension: &'a str,
 
+    /// The `;` separated `name=value` parameters between the media type and the encoding, e.g., "charset=utf-8"
+    pub parameters: &'a str,
+
     /// Base64 marker if present, usually "base64"
     pub encoding: Option<&'a str>,
 }
@@ -60,24 +69,63 @@ impl<'a> TryFrom<&'a str> for UrlDataMeta<'a>
 
         let meta = rest.split(',').next().unwrap_or(rest);
 
-        let (media_type_and_ext, encoding) = match meta.split_once(';')
+        let (media_type_and_ext, parameters) = meta.split_once(';').unwrap_or((meta, ""));
+        let (parameters, encoding) = match parameters.rsplit_once(';')
         {
-            Some((m, e)) => (m, Some(e)),
-            None => (meta, None),
+            Some((p, e)) if e.eq_ignore_ascii_case("base64") => (p, Some(e)),
+            None if parameters.eq_ignore_ascii_case("base64") => ("", Some(parameters)),
+            _ => (parameters, None),
         };
 
-        let (media_type, extension) = media_type_and_ext
-            .split_once('/')
-            .ok_or_else(|| EncodeError::custom("Invalid media type in URL"))?;
+        let (media_type, extension) = match media_type_and_ext
+        {
+            "" => ("text", "plain"),
+            m => m.split_once('/').ok_or_else(|| EncodeError::custom("Invalid media type in URL"))?,
+        };
 
         Ok(UrlDataMeta {
             scheme,
             media_type,
             extension,
+            parameters,
             encoding,
         })
     }
 }
+impl<'a> UrlDataMeta<'a>
+{
+    /// The value of a parameter, e.g., `parameter("charset")` give "utf-8" for `data:text/plain;charset=utf-8,`
+    pub fn parameter(&self, name: &str) -> Option<&'a str>
+    {
+        self.parameters
+            .split(';')
+            .filter_map(|p| p.split_once('='))
+            .find(|(n, _)| n.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().trim_matches('"'))
+    }
+
+    pub fn charset(&self) -> Option<&'a str> { self.parameter("charset") }
+
+    pub fn is_base64(&self) -> bool { self.encoding.is_some() }
+
+    /// The extension used to [`Load`] the data, deduced from the media subtype:
+    ///
+    /// - `ron`, `png`, `json.gz`: same as the subtype,
+    /// - `ld+json`, `x-yaml`: the structured syntax suffix or the subtype without `x-`,
+    /// - `plain`: `txt`,
+    /// - `octet-stream`: `None`, the format is detected with [`sniff_extension`].
+    pub fn load_extension(&self) -> Option<&'a extension>
+    {
+        let subtype = self.extension.rsplit('+').next().unwrap_or(self.extension);
+        let subtype = subtype.strip_prefix("x-").unwrap_or(subtype);
+        match subtype
+        {
+            "" | "octet-stream" => None,
+            "plain" => Some(Extension::TXT),
+            subtype => Some(subtype),
+        }
+    }
+}
 
 /// Represents a parsed [data url](https://developer.mozilla.org/en-US/docs/Web/URI/Reference/Schemes/data) (Data URL, RFC 2397).
 ///
@@ -100,6 +148,58 @@ impl<'a> Deref for UrlData<'a>
     type Target = UrlDataMeta<'a>;
     fn deref(&self) -> &Self::Target { &self.meta }
 }
+impl<'a> UrlData<'a>
+{
+    /// The decoded payload: base64, or percent-encoded (`%20`) when there is no `;base64`.
+    ///
+    /// A text payload with a `charset` other than UTF-8 or US-ASCII is converted to UTF-8 (only ISO-8859-1 is supported).
+    pub fn decode(&self) -> EncodeResult<Vec<u8>>
+    {
+        let bytes = if self.is_base64()
+        {
+            Vec::<u8>::from_base64(self.data)?
+        }
+        else
+        {
+            percent_decode(self.data)?
+        };
+
+        match self.charset()
+        {
+            None => Ok(bytes),
+            Some(charset) if ["utf-8", "utf8", "us-ascii"].iter().any(|c| charset.eq_ignore_ascii_case(c)) => Ok(bytes),
+            Some(charset) if ["iso-8859-1", "latin1"].iter().any(|c| charset.eq_ignore_ascii_case(c)) =>
+            {
+                Ok(bytes.into_iter().map(char::from).collect::<String>().into_bytes())
+            }
+            Some(charset) => Err(EncodeError::custom(format!("Unsupported charset {charset:?} in URL"))),
+        }
+    }
+}
+
+/// Decode the `%XX` escapes of a non base64 data url
+fn percent_decode(data: &str) -> EncodeResult<Vec<u8>>
+{
+    let mut bytes = Vec::with_capacity(data.len());
+    let mut it = data.bytes();
+    while let Some(b) = it.next()
+    {
+        if b != b'%'
+        {
+            bytes.push(b);
+            continue;
+        }
+        let hex = [it.next(), it.next()];
+        let digit = |d: Option<u8>| d.and_then(|d| (d as char).to_digit(16));
+        match hex.map(digit)
+        {
+            [Some(high), Some(low)] => bytes.push((high * 16 + low) as u8),
+            _ => return Err(EncodeError::custom("Invalid percent-encoding in URL")),
+        }
+    }
+    Ok(bytes)
+}
+
 impl<'a> TryFrom<&'a str> for UrlData<'a>
 {
     type Error = EncodeError;
@@ -107,11 +207,13 @@ impl<'a> TryFrom<&'a str> for UrlData<'a>
     fn try_from(value: &'a str) -> Result<Self, Self::Error>
     {
         // Limit the search to the first MIN_BYTE_SEPARATOR_SEARCH bytes
+        // (on the bytes: a raw text payload can have a multi-byte character at the limit)
         let search_len = value.len().min(MIN_BYTE_SEPARATOR_SEARCH);
-        let prefix = &value[..search_len];
+        let prefix = &value.as_bytes()[..search_len];
 
         let comma_pos = prefix
-            .find(',')
+            .iter()
+            .position(|&b| b == b',')
             .ok_or_else(|| EncodeError::custom(format!("Missing ',' separator in URL (first {MIN_BYTE_SEPARATOR_SEARCH} bytes)")))?;
 
         let (meta_str, data) = value.split_at(comma_pos);
@@ -219,6 +321,18 @@ pub trait ToUrl: MediaType + SaveExtension
         Ok(url)
     }
 
+    /// Save the value and encode it into a Data URL (RFC 2397): `data:<media_type>/<extension>;base64,<base64_encoded_data>`.
+    ///
+    /// Unlike [`ToUrl::to_url`], the extension is the one deduced by [`Save`] (ex: `ron` for an unknown extension),
+    /// so [`FromUrl::from_data_url`] can load it back.
+    fn to_data_url(&self, extension: &extension) -> EncodeResult<String>
+    {
+        let (bytes, deduced_extension) = self.save_to_bytes(Some(extension))?;
+        let extension = deduced_extension.as_deref().unwrap_or(extension);
+        let media = Self::media_type();
+        Ok(bytes.to_base64_in(format!("data:{media}/{extension};base64,")))
+    }
+
     /// Converts the encoded image into a binary url.
     ///
     /// Similar to [`Encode::to_url`], except the `<base64_encoded_data>` is in binary
@@ -251,8 +365,19 @@ pub trait FromUrl: LoadExtension
         Self: Sized,
     {
         let url = UrlData::try_from(url)?;
-        let bytes = Vec::<u8>::from_base64(url.data)?;
-        Self::load_from_bytes_with_custom_extension(&bytes, Some(url.extension))
+        Self::from_url_bytes(&url.decode()?, url.extension)
+    }
+
+    /// Loads an instance from a Data URL (RFC 2397) with [`Load`], using the extension embedded in the media type,
+    /// see [`UrlDataMeta::load_extension`].
+    ///
+    /// Both the base64 and the percent-encoded payloads are supported: `data:text/plain;charset=utf-8,Hello%20World`.
+    fn from_data_url(url: &str) -> EncodeResult<Self>
+    where
+        Self: Sized + Load,
+    {
+        let url = UrlData::try_from(url)?;
+        Self::load_from_bytes(&url.decode()?, url.load_extension())
     }
 
     /// Loads an instance from a **binary URL** (custom `bin_data:` scheme).
@@ -267,7 +392,24 @@ pub trait FromUrl: LoadExtension
         Self: Sized,
     {
         let url = BinUrlData::try_from(url)?;
-        Self::load_from_bytes_with_custom_extension(&url.data, Some(url.extension))
+        Self::from_url_bytes(url.data, url.extension)
+    }
+
+    /// Decode the data of an url, the media type can be wrong (ex: `image/octet-stream`), so the format is also detected with [`sniff_extension`].
+    fn from_url_bytes(bytes: &[u8], extension: &extension) -> EncodeResult<Self>
+    where
+        Self: Sized,
+    {
+        Self::load_from_bytes_with_custom_extension(bytes, Some(extension)).or_else(|err| {
+            retry_sniffed(
+                bytes,
+                extension,
+                ExtensionArgs::NONE,
+                err,
+                |detected| Self::load_custom_extensions().any(|e| e == detected),
+                |detected| Self::load_from_bytes_with_custom_extension(bytes, Some(detected)),
+            )
+        })
     }
 
     /// Loads an instance from a **binary URL** (custom `bin_data:` scheme), falling back to raw bytes if parsing fails.
@@ -1,20 +1,19 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{DeriveInput, GenericParam, Generics, Item, parse_macro_input, parse_quote};
+use syn::{
+    Attribute, DeriveInput, Expr, ExprArray, ExprLit, GenericParam, Generics, Ident, Item, Lit, LitInt, LitStr, Path, Type, meta::ParseNestedMeta,
+    parse_macro_input, parse_quote,
+};
 
-#[proc_macro_derive(Save)]
+#[proc_macro_derive(Save, attributes(io))]
 pub fn derive_save(input: TokenStream) -> TokenStream
 {
     let ast = parse_macro_input!(input as DeriveInput);
-    let ident = &ast.ident;
-    let generics = add_save_trait_bounds(ast.generics);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    let expanded = quote! {
-        impl #impl_generics ::hexga_encoding::SaveExtension for #ident #ty_generics #where_clause {}
-    };
-
-    expanded.into()
+    IoAttributes::from_attributes(&ast.attrs)
+        .and_then(|attributes| attributes.save_impl(&ast.ident, &add_save_trait_bounds(ast.generics.clone())))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 // Adds `T: ::hexga_encoding::SaveExtension` for each generic type parameter `T`
@@ -30,19 +29,14 @@ fn add_save_trait_bounds(mut generics: Generics) -> Generics
     generics
 }
 
-#[proc_macro_derive(Load)]
+#[proc_macro_derive(Load, attributes(io))]
 pub fn derive_load(input: TokenStream) -> TokenStream
 {
     let ast = parse_macro_input!(input as DeriveInput);
-    let ident = &ast.ident;
-    let generics = add_load_trait_bounds(ast.generics);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    let expanded = quote! {
-        impl #impl_generics ::hexga_encoding::LoadExtension for #ident #ty_generics #where_clause {}
-    };
-
-    expanded.into()
+    IoAttributes::from_attributes(&ast.attrs)
+        .and_then(|attributes| attributes.load_impl(&ast.ident, &add_load_trait_bounds(ast.generics.clone())))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 // Adds `T: ::hexga_encoding::LoadExtension` for each generic type parameter `T`
@@ -59,14 +53,17 @@ fn add_load_trait_bounds(mut generics: Generics) -> Generics
 }
 
 #[proc_macro_attribute]
-pub fn io(_args: TokenStream, input: TokenStream) -> TokenStream
+pub fn io(args: TokenStream, input: TokenStream) -> TokenStream
 {
+    let mut attributes = IoAttributes::default();
+    let parser = syn::meta::parser(|meta| attributes.parse(meta));
+    parse_macro_input!(args with parser);
     let item = parse_macro_input!(input as Item);
 
-    let (ident, item_tokens) = match item
+    let (ident, generics, item_tokens) = match item
     {
-        Item::Struct(s) => (&s.ident.clone(), quote! { #s }),
-        Item::Enum(e) => (&e.ident.clone(), quote! { #e }),
+        Item::Struct(s) => (s.ident.clone(), s.generics.clone(), quote! { #s }),
+        Item::Enum(e) => (e.ident.clone(), e.generics.clone(), quote! { #e }),
         _ =>
         {
             return syn::Error::new_spanned(item, "#[io] can only be used on structs or enums")
@@ -75,18 +72,336 @@ pub fn io(_args: TokenStream, input: TokenStream) -> TokenStream
         }
     };
 
+    let impls = attributes
+        .save_impl(&ident, &add_save_trait_bounds(generics.clone()))
+        .and_then(|save| Ok((save, attributes.load_impl(&ident, &add_load_trait_bounds(generics))?)));
+    let (save_impl, load_impl) = match impls
+    {
+        Ok(impls) => impls,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
     let expanded = quote! {
         #[derive(::serde::Serialize, ::serde::Deserialize)]
         #item_tokens
 
-        impl ::hexga_encoding::SaveExtension for #ident
-        where
-            #ident: ::hexga_encoding::cfg::CfgSerialize
-        {}
+        #save_impl
 
-        impl ::hexga_encoding::LoadExtension for #ident
-        {}
+        #load_impl
     };
 
     expanded.into()
 }
+
+/// The `#[io(...)]` attributes:
+///
+/// - `version = N`, `name = "OldName"`: see `Versioned`
+/// - `extensions = ["lvl", "level"]`, `args = ["flat"]`: the custom extensions and their arguments
+/// - `save_with = path::to_fn`, `load_with = path::to_fn`: called for the custom extensions,
+///   with the same parameters as `SaveExtension::save_to_writer_with_custom_extension` / `LoadExtension::load_from_reader_with_custom_extension`
+/// - `save_as = OtherType`, `load_from = OtherType`: implement `SaveAs` / `LoadFrom` instead
+#[derive(Default)]
+struct IoAttributes
+{
+    version: Option<LitInt>,
+    /// Written in the envelope instead of the type name, to keep loading the saves after a rename
+    name: Option<LitStr>,
+    extensions: Vec<LitStr>,
+    args: Vec<LitStr>,
+    save_with: Option<Path>,
+    load_with: Option<Path>,
+    save_as: Option<Type>,
+    load_from: Option<Type>,
+}
+impl IoAttributes
+{
+    fn from_attributes(attrs: &[Attribute]) -> syn::Result<Self>
+    {
+        let mut attributes = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("io"))
+        {
+            attr.parse_nested_meta(|meta| attributes.parse(meta))?;
+        }
+        Ok(attributes)
+    }
+
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()>
+    {
+        if meta.path.is_ident("version")
+        {
+            let version: LitInt = meta.value()?.parse()?;
+            if version.base10_parse::<u32>()? == 0
+            {
+                return Err(syn::Error::new_spanned(version, "the version start at 1"));
+            }
+            self.version = Some(version);
+        }
+        else if meta.path.is_ident("name")
+        {
+            self.name = Some(meta.value()?.parse()?);
+        }
+        else if meta.path.is_ident("extensions")
+        {
+            self.extensions = Self::parse_str_array(&meta)?;
+        }
+        else if meta.path.is_ident("args")
+        {
+            self.args = Self::parse_str_array(&meta)?;
+        }
+        else if meta.path.is_ident("save_with")
+        {
+            self.save_with = Some(meta.value()?.parse()?);
+        }
+        else if meta.path.is_ident("load_with")
+        {
+            self.load_with = Some(meta.value()?.parse()?);
+        }
+        else if meta.path.is_ident("save_as")
+        {
+            self.save_as = Some(meta.value()?.parse()?);
+        }
+        else if meta.path.is_ident("load_from")
+        {
+            self.load_from = Some(meta.value()?.parse()?);
+        }
+        else
+        {
+            return Err(meta
+                .error("unsupported io attribute, expected one of `version`, `name`, `extensions`, `args`, `save_with`, `load_with`, `save_as`, `load_from`"));
+        }
+        Ok(())
+    }
+
+    /// `["a", "b"]`
+    fn parse_str_array(meta: &ParseNestedMeta) -> syn::Result<Vec<LitStr>>
+    {
+        let array: ExprArray = meta.value()?.parse()?;
+        array
+            .elems
+            .iter()
+            .map(|elem| match elem
+            {
+                Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Ok(s.clone()),
+                _ => Err(syn::Error::new_spanned(elem, "expected a string literal")),
+            })
+            .collect()
+    }
+
+    /// Check that the custom extensions are used with their function
+    fn check_custom_extensions(&self, with: &Option<Path>, with_name: &str) -> syn::Result<()>
+    {
+        match (self.extensions.is_empty(), with)
+        {
+            (false, None) => Err(syn::Error::new_spanned(&self.extensions[0], format!("`extensions` require `{with_name}`"))),
+            (true, Some(with)) => Err(syn::Error::new_spanned(with, format!("`{with_name}` require `extensions`"))),
+            _ if self.extensions.is_empty() && !self.args.is_empty() => Err(syn::Error::new_spanned(&self.args[0], "`args` require `extensions`")),
+            _ => Ok(()),
+        }
+    }
+
+    /// `save_as`/`load_from` replace the whole impl, so they can't be combined with the other attributes
+    fn check_conversion(&self, conversion: &Option<Type>, with: &Option<Path>) -> syn::Result<()>
+    {
+        match conversion
+        {
+            Some(conversion) if self.version.is_some() || !self.extensions.is_empty() || with.is_some() => Err(syn::Error::new_spanned(
+                conversion,
+                "`save_as`/`load_from` can't be combined with `version`, `extensions` or the custom functions",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn save_impl(&self, ident: &Ident, generics: &Generics) -> syn::Result<TokenStream2>
+    {
+        self.check_conversion(&self.save_as, &self.save_with)?;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        if let Some(output) = &self.save_as
+        {
+            return Ok(quote! {
+                impl #impl_generics ::hexga_encoding::SaveAs for #ident #ty_generics #where_clause
+                {
+                    type Output = #output;
+                }
+            });
+        }
+        self.check_custom_extensions(&self.save_with, "save_with")?;
+
+        let custom = self.save_with.as_ref().map(|save_with| {
+            let (extensions, args) = (&self.extensions, &self.args);
+            quote! {
+                fn save_custom_extensions() -> impl Iterator<Item = &'static ::hexga_encoding::extension> { [#(#extensions),*].into_iter() }
+                fn save_custom_extension_args() -> impl Iterator<Item = &'static str> { [#(#args),*].into_iter() }
+                fn save_to_writer_with_custom_extension<W>(
+                    &self,
+                    writer: W,
+                    extension: Option<&::hexga_encoding::extension>,
+                    args: ::hexga_encoding::ExtensionArgs<'_>,
+                ) -> ::hexga_encoding::EncodeResult
+                where
+                    W: ::std::io::Write,
+                {
+                    #save_with(self, writer, extension, args)
+                }
+            }
+        });
+        let versioned = self.save_versioned(ident);
+
+        Ok(quote! {
+            impl #impl_generics ::hexga_encoding::SaveExtension for #ident #ty_generics #where_clause
+            {
+                #custom
+                #versioned
+            }
+        })
+    }
+
+    fn load_impl(&self, ident: &Ident, generics: &Generics) -> syn::Result<TokenStream2>
+    {
+        self.check_conversion(&self.load_from, &self.load_with)?;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        if let Some(source) = &self.load_from
+        {
+            return Ok(quote! {
+                impl #impl_generics ::hexga_encoding::LoadFrom for #ident #ty_generics #where_clause
+                {
+                    type Source = #source;
+                }
+            });
+        }
+        self.check_custom_extensions(&self.load_with, "load_with")?;
+
+        let custom = self.load_with.as_ref().map(|load_with| {
+            let (extensions, args) = (&self.extensions, &self.args);
+            quote! {
+                fn load_custom_extensions() -> impl Iterator<Item = &'static ::hexga_encoding::extension> { [#(#extensions),*].into_iter() }
+                fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { [#(#args),*].into_iter() }
+                fn load_from_reader_with_custom_extension<R>(
+                    reader: R,
+                    extension: Option<&::hexga_encoding::extension>,
+                    args: ::hexga_encoding::ExtensionArgs<'_>,
+                ) -> ::hexga_encoding::EncodeResult<Self>
+                where
+                    Self: Sized,
+                    R: ::std::io::Read,
+                {
+                    #load_with(reader, extension, args)
+                }
+            }
+        });
+        let versioned = self.load_versioned();
+        let versioned_impl = self.versioned_body(ident).map(|body| {
+            quote! {
+                impl #impl_generics ::hexga_encoding::Versioned for #ident #ty_generics #where_clause
+                {
+                    #body
+                }
+            }
+        });
+
+        Ok(quote! {
+            impl #impl_generics ::hexga_encoding::LoadExtension for #ident #ty_generics #where_clause
+            {
+                #custom
+                #versioned
+            }
+
+            #versioned_impl
+        })
+    }
+
+    fn type_name(&self, ident: &Ident) -> String
+    {
+        match &self.name
+        {
+            Some(name) => name.value(),
+            None => ident.to_string(),
+        }
+    }
+
+    /// Wrap the value in a `VersionedSave` envelope
+    fn save_versioned(&self, ident: &Ident) -> TokenStream2
+    {
+        let Some(version) = &self.version
+        else
+        {
+            return quote! {};
+        };
+        let name = self.type_name(ident);
+        quote! {
+            fn save_to_writer_with_format<W>(
+                &self,
+                writer: W,
+                format: ::hexga_encoding::markup::AnyFormat,
+                args: ::hexga_encoding::ExtensionArgs<'_>,
+            ) -> ::hexga_encoding::EncodeResult
+            where
+                W: ::std::io::Write,
+            {
+                format.encode_with_writer_and_args(&::hexga_encoding::VersionedSave::new(#name, #version, self), writer, args)
+            }
+        }
+    }
+
+    /// Read the `VersionedLoad` envelope
+    fn load_versioned(&self) -> TokenStream2
+    {
+        if self.version.is_none()
+        {
+            return quote! {};
+        }
+        quote! {
+            fn load_from_bytes_borrowed_with_format<'__a>(
+                bytes: &'__a [u8],
+                format: ::hexga_encoding::markup::AnyFormat,
+                limits: ::hexga_encoding::DecodeLimits,
+                _args: ::hexga_encoding::ExtensionArgs<'_>,
+            ) -> ::hexga_encoding::EncodeResult<Self>
+            where
+                Self: Sized + ::serde::Deserialize<'__a>,
+            {
+                format
+                    .from_bytes_with_limits::<::hexga_encoding::VersionedLoad<Self>>(bytes, limits)
+                    .map(|versioned| versioned.0)
+            }
+
+            fn load_from_reader_with_format<R>(
+                reader: R,
+                format: ::hexga_encoding::markup::AnyFormat,
+                limits: ::hexga_encoding::DecodeLimits,
+                _args: ::hexga_encoding::ExtensionArgs<'_>,
+            ) -> ::hexga_encoding::EncodeResult<Self>
+            where
+                Self: Sized + for<'de> ::serde::Deserialize<'de>,
+                R: ::std::io::Read,
+            {
+                format
+                    .from_reader_with_limits::<::hexga_encoding::VersionedLoad<Self>, R>(reader, limits)
+                    .map(|versioned| versioned.0)
+            }
+        }
+    }
+
+    /// The `Versioned` impl body, the older versions are loaded with `Migrate` when the version is above 1
+    fn versioned_body(&self, ident: &Ident) -> Option<TokenStream2>
+    {
+        let version = self.version.as_ref()?;
+        let name = self.type_name(ident);
+        let migrate = (version.base10_parse::<u32>().unwrap_or_default() > 1).then(|| {
+            quote! {
+                fn deserialize_version<'de, D>(version: u32, deserializer: D) -> ::std::result::Result<Self, D::Error>
+                where
+                    Self: ::serde::Deserialize<'de>,
+                    D: ::serde::Deserializer<'de>,
+                {
+                    <Self as ::hexga_encoding::Migrate>::deserialize_version_or_migrate(version, deserializer)
+                }
+            }
+        });
+        Some(quote! {
+            const TYPE_NAME: &'static str = #name;
+            const VERSION: u32 = #version;
+            #migrate
+        })
+    }
+}
@@ -599,10 +599,22 @@ where
 {
     fn save_custom_extensions() -> impl Iterator<Item = &'static extension> { ["png"].into_iter() }
 
-    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>) -> EncodeResult
+    /// Png compression hint: `fast` (the default) or `best`, ex: `image.best.png`
+    fn save_custom_extension_args() -> impl Iterator<Item = &'static str> { ["fast", "best"].into_iter() }
+
+    fn save_to_writer_with_custom_extension<W>(&self, writer: W, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult
     where
         W: Write,
     {
+        let png_compression = if args.contains("best")
+        {
+            ::image::codecs::png::CompressionType::Best
+        }
+        else
+        {
+            ::image::codecs::png::CompressionType::Fast
+        };
+        let png_encoder = |writer| ::image::codecs::png::PngEncoder::new_with_quality(writer, png_compression, ::image::codecs::png::FilterType::default());
         match extension
         {
             Some("png") => match C::Component::PRIMITIVE_TYPE
@@ -611,17 +623,17 @@ where
                 {
                     if std::mem::size_of::<C::Component>() * 8 <= 8
                     {
-                        self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension)
+                        self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension, args)
                     }
                     else
                     {
-                        self.clone().to_rgba_u16().save_to_writer_with_custom_extension(writer, extension)
+                        self.clone().to_rgba_u16().save_to_writer_with_custom_extension(writer, extension, args)
                     }
                 }
                 NumberType::IntegerUnsigned => match std::mem::size_of::<C::Component>() * 8
                 {
                     8 => ::image::ImageEncoder::write_image(
-                        ::image::codecs::png::PngEncoder::new(writer),
+                        png_encoder(writer),
                         unsafe { std::slice::from_raw_parts(self.pixels().as_ptr() as *const u8, self.pixels().len() * std::mem::size_of::<C>()) },
                         self.width().to_usize() as _,
                         self.height().to_usize() as _,
@@ -629,17 +641,17 @@ where
                     )
                     .map_err(|e| EncodeError::custom(format!("Failed to encode .png rgba8 image : {}", e))),
                     16 => ::image::ImageEncoder::write_image(
-                        ::image::codecs::png::PngEncoder::new(writer),
+                        png_encoder(writer),
                         unsafe { std::slice::from_raw_parts(self.pixels().as_ptr() as *const u8, self.pixels().len() * std::mem::size_of::<C>()) },
                         self.width().to_usize() as _,
                         self.height().to_usize() as _,
                         ::image::ExtendedColorType::Rgba16,
                     )
                     .map_err(|e| EncodeError::custom(format!("Failed to encode .png rgba16 image : {}", e))),
-                    _ => self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension),
+                    _ => self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension, args),
                 },
-                NumberType::Float => self.clone().to_rgba_u16().save_to_writer_with_custom_extension(writer, extension),
-                NumberType::Bool => self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension),
+                NumberType::Float => self.clone().to_rgba_u16().save_to_writer_with_custom_extension(writer, extension, args),
+                NumberType::Bool => self.clone().to_rgba_u8().save_to_writer_with_custom_extension(writer, extension, args),
             },
             _ => Err(EncodeError::save_unsupported_extension_with_name::<Self>(
                 extension.map(|e| e.to_owned().into()),
@@ -668,7 +680,10 @@ where
 {
     fn load_custom_extensions() -> impl Iterator<Item = &'static extension> { ["png", "jpg", "jpeg", "bmp", "gif", "webp", "ico", "tiff"].into_iter() }
 
-    fn load_from_reader_with_custom_extension<R>(mut reader: R, extension: Option<&extension>) -> EncodeResult<Self>
+    /// Accept the saving arguments, so `image.best.png` can be loaded back
+    fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { ["fast", "best"].into_iter() }
+
+    fn load_from_reader_with_custom_extension<R>(mut reader: R, extension: Option<&extension>, _args: ExtensionArgs<'_>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: std::io::Read,
@@ -0,0 +1,42 @@
+use std::time::SystemTime;
+
+/// What is known about a file or a directory, see [`Io::metadata`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileMetadata
+{
+    /// In bytes, 0 for a directory
+    pub size: u64,
+    /// `None` if the backend don't know it, ex: the entries of a [`Pack`]
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+}
+
+impl FileMetadata
+{
+    pub const fn file(size: u64, modified: Option<SystemTime>) -> Self { Self { size, modified, is_dir: false } }
+    pub const fn dir(modified: Option<SystemTime>) -> Self
+    {
+        Self {
+            size: 0,
+            modified,
+            is_dir: true,
+        }
+    }
+
+    pub const fn is_file(&self) -> bool { !self.is_dir }
+}
+
+/// What an entry of [`FileSystem::list_with_kinds`] is, known without reading its metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind
+{
+    File,
+    Dir,
+    /// A symbolic link to a directory. Not followed by the `**` of [`Io::glob`], so a link to a parent never loop.
+    DirLink,
+}
+
+impl FileKind
+{
+    pub const fn is_dir(self) -> bool { matches!(self, Self::Dir | Self::DirLink) }
+}
@@ -1,34 +1,135 @@
 use super::*;
+use std::ffi::OsString;
 use std::fs;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub(crate) fn load_bytes(path: &Path) -> IoResult<Vec<u8>> { std::fs::read(path).map_err(|e| IoError::new(path, e).when_reading()) }
 
-pub(crate) fn save_bytes(path: &Path, bytes: &[u8]) -> Result<(), IoError>
+/// Open a file to read only some parts of it, ex: the entries of a [`Pack`](hexga_encoding::Pack)
+pub(crate) fn open(path: &Path) -> IoResult<std::io::BufReader<fs::File>>
 {
-    if let Some(parent) = path.parent()
+    fs::File::open(path)
+        .map(std::io::BufReader::new)
+        .map_err(|e| IoError::new(path, e).when_reading())
+}
+
+/// The files and directories directly inside `path`, sorted. An empty path is the current directory.
+pub(crate) fn list(path: &Path) -> IoResult<Vec<PathBuf>>
+{
+    let dir = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+    let entries = fs::read_dir(dir).map_err(|e| IoError::new(path, e).when_reading())?;
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| IoError::new(path, e).when_reading())?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Like [`list`], with the kind of each entry. Only the symbolic links are read again to know their kind.
+pub(crate) fn list_with_kinds(path: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+{
+    let dir = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+    let entries = fs::read_dir(dir).map_err(|e| IoError::new(path, e).when_reading())?;
+    let mut paths = Vec::new();
+    for entry in entries
     {
-        for prefix in parent.iter()
+        let entry = entry.map_err(|e| IoError::new(path, e).when_reading())?;
+        let file_type = entry.file_type().map_err(|e| IoError::new(path, e).when_reading())?;
+        let path = entry.path();
+        let kind = match file_type.is_dir()
         {
-            let std_path = Path::new(prefix);
-
-            if std_path.exists()
-            {
-                if std_path.is_file()
-                {
-                    fs::remove_file(std_path).map_err(|e| IoError::new(std_path, e).when_writing())?;
-                    fs::create_dir(std_path).map_err(|e| IoError::new(std_path, e).when_writing())?;
-                }
-            }
-            else
-            {
-                fs::create_dir(std_path).map_err(|e| IoError::new(std_path, e).when_writing())?;
-            }
+            true => FileKind::Dir,
+            false if file_type.is_symlink() && path.is_dir() => FileKind::DirLink,
+            false => FileKind::File,
+        };
+        paths.push((path, kind));
+    }
+    paths.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(paths)
+}
+
+/// Remove a file, or a directory with all its content
+pub(crate) fn remove(path: &Path) -> IoResult
+{
+    match path.is_dir()
+    {
+        true => fs::remove_dir_all(path),
+        false => fs::remove_file(path),
+    }
+    .map_err(|e| IoError::new(path, e).when_writing())
+}
+
+pub(crate) fn metadata(path: &Path) -> IoResult<FileMetadata>
+{
+    let metadata = fs::metadata(path).map_err(|e| IoError::new(path, e).when_reading())?;
+    Ok(FileMetadata {
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata.modified().ok(),
+        is_dir: metadata.is_dir(),
+    })
+}
+
+/// Create the parents of `to` if needed
+pub(crate) fn rename(from: &Path, to: &Path) -> IoResult
+{
+    if let Some(parent) = to.parent().filter(|p| !p.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|e| IoError::new(parent, e).when_writing())?;
+    }
+    fs::rename(from, to).map_err(|e| IoError::new(from, e).when_writing())
+}
+
+/// Written in a temporary file next to `path`, then renamed, so a crash never leave a half written file.
+///
+/// Fail if a parent is a file.
+pub(crate) fn save_bytes(path: &Path, bytes: &[u8]) -> IoResult
+{
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
+    {
+        if let Some(file) = parent.ancestors().find(|p| p.is_file())
+        {
+            return Err(IoError::new(path, format!("the parent {} is a file", file.display())).when_writing());
         }
+        fs::create_dir_all(parent).map_err(|e| IoError::new(parent, e).when_writing())?;
     }
 
-    fs::write(path, bytes).map_err(|e| IoError::new(path, e).when_writing())
+    // unique, in case another thread save the same file
+    static TMP_ID: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().ok_or_else(|| IoError::new(path, "missing file name").when_writing())?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}-{}.tmp", std::process::id(), TMP_ID.fetch_add(1, Ordering::Relaxed)));
+    let tmp = path.with_file_name(tmp_name);
+
+    let saved = write_synced(&tmp, bytes).and_then(|_| fs::rename(&tmp, path)).and_then(|_| sync_parent(path));
+    if saved.is_err()
+    {
+        let _ = fs::remove_file(&tmp);
+    }
+    saved.map_err(|e| IoError::new(path, e).when_writing())
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()>
+{
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
 }
 
+/// The rename is only kept after a crash once the directory is synced too
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> std::io::Result<()>
+{
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::File::open(parent)?.sync_all()
+}
+
+/// Windows can't open a directory as a file
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> std::io::Result<()> { Ok(()) }
+
 /*
 pub(crate) fn save_bytes_async<F>(path: &Path, bytes: Vec<u8>, on_saved: F)
     where F: FnOnce(IoResult) + 'static
@@ -2,6 +2,22 @@ use super::*;
 
 pub(crate) fn load_bytes(path: &Path) -> IoResult<Vec<u8>> { Err(IoError::new(path, FileError::NotSupported).when_reading()) }
 
+pub(crate) fn open(path: &Path) -> IoResult<std::io::Cursor<Vec<u8>>> { load_bytes(path).map(std::io::Cursor::new) }
+
+pub(crate) fn list(path: &Path) -> IoResult<Vec<PathBuf>> { Err(IoError::new(path, FileError::NotSupported).when_reading()) }
+
+pub(crate) fn list_with_kinds(path: &Path) -> IoResult<Vec<(PathBuf, FileKind)>> { Err(IoError::new(path, FileError::NotSupported).when_reading()) }
+
+pub(crate) fn remove(path: &Path) -> IoResult { Err(IoError::new(path, FileError::NotSupported).when_writing()) }
+
+pub(crate) fn metadata(path: &Path) -> IoResult<FileMetadata> { Err(IoError::new(path, FileError::NotSupported).when_reading()) }
+
+pub(crate) fn rename(from: &Path, to: &Path) -> IoResult
+{
+    let _ = to;
+    Err(IoError::new(from, FileError::NotSupported).when_writing())
+}
+
 pub(crate) fn save_bytes(path: &Path, bytes: &[u8]) -> IoResult
 {
     let _ = bytes;
@@ -0,0 +1,138 @@
+use super::*;
+
+/// Match a file name with a pattern where `*` match any characters and `?` one character, ex: `*.ron`
+pub fn wildcard_match(pattern: &str, name: &str) -> bool
+{
+    let (pattern, name) = (pattern.chars().collect::<Vec<_>>(), name.chars().collect::<Vec<_>>());
+    let (mut p, mut n) = (0, 0);
+    // where to retry if the last `*` match one more character
+    let mut star = None;
+    while n < name.len()
+    {
+        match pattern.get(p)
+        {
+            Some('*') =>
+            {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] =>
+            {
+                p += 1;
+                n += 1;
+            }
+            _ => match star
+            {
+                Some((star_p, star_n)) =>
+                {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn has_wildcard(component: &str) -> bool { component.contains(['*', '?']) }
+
+impl Io
+{
+    /// All the files inside `dir` and its sub directories, sorted
+    pub fn walk_dir<P>(self, dir: P) -> IoResult<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        // unlike a glob, a missing `dir` is an error
+        let entries = Io.list_dir_with_kinds(dir)?;
+        let mut files = Vec::new();
+        glob_entries(&entries, &["**", "*"], &mut files)?;
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// The files matching the `pattern`, sorted, ex: `levels/**/*.ron` or `assets://textures/*.png`.
+    ///
+    /// `*` and `?` match inside a file name, `**` match any number of directories. The separator is `/`.
+    pub fn glob(self, pattern: &str) -> IoResult<Vec<PathBuf>>
+    {
+        // The directory before the first wildcard is read as is, it can be `assets://`
+        let wildcard = pattern.find(['*', '?']).unwrap_or(pattern.len());
+        let (dir, rest) = match pattern[..wildcard].rfind('/')
+        {
+            Some(slash) => pattern.split_at(slash + 1),
+            None => ("", pattern),
+        };
+        let components = rest.split('/').filter(|c| !c.is_empty()).collect::<Vec<_>>();
+
+        let mut files = Vec::new();
+        if components.iter().all(|c| !has_wildcard(c))
+        {
+            if Io.metadata(pattern).is_ok_and(|metadata| metadata.is_file())
+            {
+                files.push(PathBuf::from(pattern));
+            }
+            return Ok(files);
+        }
+        glob_in(Path::new(dir), &components, &mut files)?;
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+}
+
+/// Add the files of `dir` matching the `components` of the pattern. A missing `dir` has no files.
+fn glob_in(dir: &Path, components: &[&str], files: &mut Vec<PathBuf>) -> IoResult
+{
+    match Io.list_dir_with_kinds(dir)
+    {
+        Ok(entries) => glob_entries(&entries, components, files),
+        Err(err) if err.kind.is_not_found() => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Add the `entries` of a directory matching the `components` of the pattern
+fn glob_entries(entries: &[(PathBuf, FileKind)], components: &[&str], files: &mut Vec<PathBuf>) -> IoResult
+{
+    let Some((component, rest)) = components.split_first()
+    else
+    {
+        return Ok(());
+    };
+    if *component == "**"
+    {
+        // matching no directory
+        glob_entries(entries, rest, files)?;
+        // the symbolic links are not followed, they can loop
+        for (path, _) in entries.iter().filter(|(_, kind)| *kind == FileKind::Dir)
+        {
+            glob_in(path, components, files)?;
+        }
+        return Ok(());
+    }
+
+    for (path, kind) in entries
+    {
+        let Some(name) = path.file_name().and_then(|name| name.to_str())
+        else
+        {
+            continue;
+        };
+        if !wildcard_match(component, name)
+        {
+            continue;
+        }
+        match rest.is_empty()
+        {
+            true if !kind.is_dir() => files.push(path.clone()),
+            false if kind.is_dir() => glob_in(path, rest, files)?,
+            _ =>
+            {}
+        }
+    }
+    Ok(())
+}
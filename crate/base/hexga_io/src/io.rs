@@ -1,29 +1,127 @@
 use std::{
     borrow::Cow,
+    cell::RefCell,
     collections::HashMap,
-    sync::{LazyLock, RwLock},
+    sync::{Arc, LazyLock, Mutex, RwLock, Weak},
 };
 
 use super::*;
 
 pub struct Io;
 
-static BYTES_CACHE: LazyLock<RwLock<HashMap<PathBuf, Cow<'static, [u8]>>>> = LazyLock::new(|| Default::default());
+/// The files set with [`Io::set_file_cache`]
+static FILE_CACHE: LazyLock<MemoryFs> = LazyLock::new(MemoryFs::new);
+
+/// The index of the packs read by [`open_pack`], so loading many entries of a pack don't read it again
+static PACK_INDEXES: LazyLock<Mutex<HashMap<PathBuf, PackIndex>>> = LazyLock::new(Default::default);
+
+struct PackIndex
+{
+    source: PackSource,
+    /// Read again if the pack changed
+    metadata: FileMetadata,
+    pack: Pack<()>,
+}
+
+/// Where a pack was read, see [`open_pack`]
+enum PackSource
+{
+    FileCache,
+    Vfs,
+    /// Weak, so the memory of the file system is not reused by another one while the index is kept
+    FileSystem(Weak<dyn FileSystem>),
+}
+
+impl PartialEq for PackSource
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        match (self, other)
+        {
+            (Self::FileCache, Self::FileCache) | (Self::Vfs, Self::Vfs) => true,
+            (Self::FileSystem(a), Self::FileSystem(b)) => Weak::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Forget the index of the packs at `path` or inside it, when they are saved, removed or renamed
+pub(crate) fn forget_packs(path: &Path) { PACK_INDEXES.lock().unwrap().retain(|pack_path, _| !pack_path.starts_with(path)); }
+
+/// Where the paths without scheme are, the disk by default
+static FILE_SYSTEM: LazyLock<RwLock<Arc<dyn FileSystem>>> = LazyLock::new(|| RwLock::new(Arc::new(OsFs::new(""))));
+
+thread_local! {
+    /// Override [`FILE_SYSTEM`] for the current thread, see [`Io::with_file_system`]
+    static THREAD_FILE_SYSTEM: RefCell<Option<Arc<dyn FileSystem>>> = const { RefCell::new(None) };
+}
 
 impl Io
 {
+    /// The [`FileSystem`] of the paths without a scheme, see [`split_scheme`]. The disk ([`OsFs`]) by default.
+    pub fn file_system(self) -> Arc<dyn FileSystem>
+    {
+        THREAD_FILE_SYSTEM
+            .with_borrow(|fs| fs.clone())
+            .unwrap_or_else(|| FILE_SYSTEM.read().unwrap().clone())
+    }
+
+    /// Replace the [`FileSystem`] of the paths without a scheme for the whole program, and return the previous one
+    pub fn set_file_system<F>(self, fs: F) -> Arc<dyn FileSystem>
+    where
+        F: FileSystem + 'static,
+    {
+        std::mem::replace(&mut *FILE_SYSTEM.write().unwrap(), Arc::new(fs))
+    }
+
+    /// Use `fs` for the paths without a scheme while running `f`, only in the current thread.
+    ///
+    /// Useful for the tests, they run in parallel and each can have its own [`MemoryFs`]:
+    ///
+    /// ```
+    /// use hexga_io::prelude::*;
+    ///
+    /// let fs = MemoryFs::new();
+    /// Io.with_file_system(fs.clone(), || Io.save("levels/intro.json", &[1, 2, 3])).unwrap();
+    /// assert!(fs.exists("levels/intro.json".as_ref()));
+    /// assert!(!std::path::Path::new("levels/intro.json").exists());
+    /// ```
+    pub fn with_file_system<F, R, O>(self, fs: F, run: R) -> O
+    where
+        F: FileSystem + 'static,
+        R: FnOnce() -> O,
+    {
+        /// Restore the previous one, even if `run` panic
+        struct Restore(Option<Arc<dyn FileSystem>>);
+        impl Drop for Restore
+        {
+            fn drop(&mut self) { THREAD_FILE_SYSTEM.set(self.0.take()); }
+        }
+
+        let _restore = Restore(THREAD_FILE_SYSTEM.replace(Some(Arc::new(fs))));
+        run()
+    }
+
+    /// The files set with [`Self::set_file_cache`], loaded before the [`Self::file_system`] ones.
+    ///
+    /// It is shared, ex: `Io.file_cache().clear()` forget all of them.
+    pub fn file_cache(self) -> MemoryFs { FILE_CACHE.clone() }
+
     pub fn load_bytes<P>(self, path: P) -> IoResult<Cow<'static, [u8]>>
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let cache = BYTES_CACHE.read().unwrap();
-
-        match cache.get(path)
+        if let Some(value) = FILE_CACHE.get(path)
         {
-            Some(value) => Ok(value.clone()),
-            None => fs::load_bytes(path).map(|v| v.into()),
+            return Ok(value);
         }
+        let bytes = match split_scheme(path)
+        {
+            Some(_) => Io.vfs_load_bytes(path),
+            None => Io.file_system().load_bytes(path),
+        };
+        bytes.map(|v| v.into()).or_else(|err| load_pack_entry(path).unwrap_or(Err(err)))
     }
 
     pub fn load<P, T>(self, path: P) -> IoResult<T>
@@ -32,25 +130,38 @@ impl Io
         T: Load,
     {
         let path = path.as_ref();
-        let extension = path.extension().map(|e| e.to_str()).flatten();
+        let is_arg = |arg: &str| T::load_extension_args().any(|a| a == arg);
+        let extension = path.full_extension_with_args(is_arg);
 
         let (bytes, extension) = match Io.load_bytes(path)
         {
-            Ok(bytes) => (bytes, extension),
+            Ok(bytes) => (bytes, extension.map(Cow::Borrowed)),
             Err(err) =>
             {
                 let mut found = None;
 
                 for ext in T::load_extensions()
                 {
-                    if Some(ext) == extension
+                    #[allow(unused_mut)]
+                    let mut candidates = vec![Cow::Borrowed(ext)];
+                    #[cfg(feature = "compression")]
+                    candidates.extend(hexga_encoding::Compression::ALL.iter().map(|c| Cow::Owned(c.join_extension(Some(ext)))));
+
+                    for ext in candidates
                     {
-                        continue;
-                    }
+                        if Some(ext.as_ref()) == extension
+                        {
+                            continue;
+                        }
 
-                    if let Ok(bytes) = Io.load_bytes(&path.with_extension(ext))
+                        if let Ok(bytes) = Io.load_bytes(path.with_full_extension_with_args(&ext, is_arg))
+                        {
+                            found = Some((bytes, Some(ext)));
+                            break;
+                        }
+                    }
+                    if found.is_some()
                     {
-                        found = Some((bytes, Some(ext)));
                         break;
                     }
                 }
@@ -61,29 +172,207 @@ impl Io
                 }
             }
         };
-        T::load_from_bytes(&bytes, extension).map_err(|e| IoError::new(path, e).when_reading())
+        T::load_from_bytes(&bytes, extension.as_deref()).map_err(|e| IoError::new(path, e).when_reading())
+    }
+
+    pub fn exists<P>(self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let exists = FILE_CACHE.exists(path)
+            || match split_scheme(path)
+            {
+                Some(_) => Io.vfs_exists(path),
+                None => Io.file_system().exists(path),
+            };
+        exists || pack_entry_metadata(path).is_some_and(|metadata| metadata.is_ok())
+    }
+
+    /// The size, modification time and kind of a file or a directory
+    pub fn metadata<P>(self, path: P) -> IoResult<FileMetadata>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if FILE_CACHE.exists(path)
+        {
+            return FILE_CACHE.metadata(path);
+        }
+        let metadata = match split_scheme(path)
+        {
+            Some(_) => Io.vfs_metadata(path),
+            None => Io.file_system().metadata(path),
+        };
+        metadata.or_else(|err| pack_entry_metadata(path).unwrap_or(Err(err)))
+    }
+
+    /// The files and directories directly inside `dir`, sorted, ex: `levels/intro.ron` for `levels`.
+    ///
+    /// The files of the [cache](Self::file_cache) and the entries of the packs (`assets.pack/levels`) are listed too.
+    pub fn list_dir<P>(self, dir: P) -> IoResult<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        let listed = match split_scheme(dir)
+        {
+            Some(_) => Io.vfs_list(dir),
+            None => Io.file_system().list(dir),
+        };
+        let listed = listed.or_else(|err| {
+            list_pack_dir(dir)
+                .map(|listed| listed.map(|paths| paths.into_iter().map(|(path, _)| path).collect()))
+                .unwrap_or(Err(err))
+        });
+        let mut paths = match (listed, FILE_CACHE.list(dir))
+        {
+            (Ok(paths), Ok(cached)) => paths.into_iter().chain(cached).collect(),
+            (Ok(paths), Err(_)) | (Err(_), Ok(paths)) => paths,
+            (Err(err), Err(_)) => return Err(err),
+        };
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    /// Like [`Self::list_dir`], with the kind of each entry. Cheaper than calling [`Self::metadata`] on each of them.
+    pub fn list_dir_with_kinds<P>(self, dir: P) -> IoResult<Vec<(PathBuf, FileKind)>>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        let listed = match split_scheme(dir)
+        {
+            Some(_) => Io.vfs_list_with_kinds(dir),
+            None => Io.file_system().list_with_kinds(dir),
+        };
+        let listed = listed.or_else(|err| list_pack_dir(dir).unwrap_or(Err(err)));
+        let mut paths = match (listed, FILE_CACHE.list_with_kinds(dir))
+        {
+            (Ok(paths), Ok(cached)) => paths.into_iter().chain(cached).collect(),
+            (Ok(paths), Err(_)) | (Err(_), Ok(paths)) => paths,
+            (Err(err), Err(_)) => return Err(err),
+        };
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths.dedup_by(|a, b| a.0 == b.0);
+        Ok(paths)
+    }
+
+    /// Remove a file, or a directory with all its content.
+    ///
+    /// With a [`Vfs`] path, only the writable backends remove it: the version of a read-only backend is visible again, see [`Vfs::remove`].
+    pub fn remove<P>(self, path: P) -> IoResult
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        forget_packs(path);
+        let cached = FILE_CACHE.exists(path) && FILE_CACHE.remove(path).is_ok();
+        let removed = match split_scheme(path)
+        {
+            Some(_) => Io.vfs_remove(path),
+            None => Io.file_system().remove(path),
+        };
+        match removed
+        {
+            Err(err) if cached && err.kind.is_not_found() => Ok(()),
+            removed => removed,
+        }
+    }
+
+    /// Move a file or a directory, replacing the destination.
+    ///
+    /// Between two [`FileSystem`], the file is copied then removed.
+    pub fn rename<P, Q>(self, from: P, to: Q) -> IoResult
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        forget_packs(from);
+        forget_packs(to);
+        let cached = match FILE_CACHE.get(from)
+        {
+            Some(bytes) =>
+            {
+                FILE_CACHE.remove(from)?;
+                FILE_CACHE.insert(to, bytes);
+                true
+            }
+            None => false,
+        };
+        let renamed = match (split_scheme(from), split_scheme(to))
+        {
+            (None, None) => Io.file_system().rename(from, to),
+            (Some((scheme, _)), Some((to_scheme, _))) if scheme == to_scheme => Io.vfs_rename(from, to),
+            _ => Io.load_bytes(from).and_then(|bytes| Io.save_bytes(to, &bytes)).and_then(|()| Io.remove(from)),
+        };
+        // only in the cache
+        match renamed
+        {
+            Err(err) if cached && err.kind.is_not_found() => Ok(()),
+            renamed => renamed,
+        }
+    }
+
+    /// Load a value borrowing its strings and bytes from `bytes`, ex: the result of [`Self::load_bytes`]. See [`LoadBorrowed`].
+    ///
+    /// The `path` give the extension, the file is not read again.
+    #[cfg(feature = "serde")]
+    pub fn load_borrowed<'a, P, T>(self, path: P, bytes: &'a [u8]) -> IoResult<T>
+    where
+        P: AsRef<Path>,
+        T: LoadBorrowed<'a>,
+    {
+        let path = path.as_ref();
+        T::load_from_bytes_borrowed(bytes, path.full_extension()).map_err(|e| IoError::new(path, e).when_reading())
     }
 
     pub fn load_string<P>(self, path: P) -> IoResult<String>
     where
         P: AsRef<Path>,
     {
-        let bytes = fs::load_bytes(path.as_ref())?;
+        let bytes = Io.load_bytes(path.as_ref())?.into_owned();
         String::from_utf8(bytes).map_err(|e| IoError::new(path.as_ref(), e))
     }
 
+    /// Save with [`SaveOptions::DEFAULT`]: atomically, without backup
     pub fn save_bytes<P>(self, path: P, bytes: &[u8]) -> IoResult
+    where
+        P: AsRef<Path>,
+    {
+        self.save_bytes_with_options(path, bytes, SaveOptions::DEFAULT)
+    }
+
+    pub fn save_bytes_with_options<P>(self, path: P, bytes: &[u8], options: SaveOptions) -> IoResult
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let cache = BYTES_CACHE.read().unwrap();
-        if cache.contains_key(path)
+        forget_packs(path);
+        if FILE_CACHE.exists(path)
+        {
+            FILE_CACHE.insert(path, bytes.to_owned());
+        }
+        let (fs, relative) = Io.writable_file_system(path)?;
+        let at_path = |err: IoError| IoError { path: path.to_owned(), ..err };
+        if options.replace_parent_files
         {
-            drop(cache);
-            self.set_file_cache(path, bytes.to_owned());
+            remove_parent_files(fs.as_ref(), relative).map_err(at_path)?;
+        }
+        rotate_backups(fs.as_ref(), relative, options.backups).map_err(at_path)?;
+        fs.save_bytes(relative, bytes).map_err(at_path)
+    }
+
+    /// The [`FileSystem`] where `path` is saved, and the path inside it
+    pub(crate) fn writable_file_system(self, path: &Path) -> IoResult<(Arc<dyn FileSystem>, &Path)>
+    {
+        match split_scheme(path)
+        {
+            Some(_) => Io.vfs_writable(path),
+            None => Ok((Io.file_system(), path)),
         }
-        fs::save_bytes(path.as_ref(), bytes)
     }
 
     pub fn set_file_cache<P, B>(self, path: P, bytes: B)
@@ -91,7 +380,24 @@ impl Io
         P: AsRef<Path>,
         B: Into<Cow<'static, [u8]>>,
     {
-        BYTES_CACHE.write().unwrap().insert(path.as_ref().to_owned(), bytes.into());
+        forget_packs(path.as_ref());
+        FILE_CACHE.insert(path.as_ref(), bytes);
+    }
+
+    /// Bundle the entries added by `add` in one [`Pack`] file.
+    /// They can then be loaded with [`Self::load`] by their path inside the pack, ex: `assets.pack/textures/hero.png`.
+    pub fn save_pack<P, F>(self, path: P, add: F) -> IoResult
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut PackWriter<&mut Vec<u8>>) -> EncodeResult,
+    {
+        let path = path.as_ref();
+        let mut bytes = Vec::new();
+        let mut writer = PackWriter::new(&mut bytes).map_err(|e| IoError::new(path, e).when_writing())?;
+        add(&mut writer)
+            .and_then(|_| writer.finish())
+            .map_err(|e| IoError::new(path, e).when_writing())?;
+        self.save_bytes(path, &bytes)
     }
 
     pub fn save_str<P>(self, path: P, str: &str) -> IoResult
@@ -102,22 +408,31 @@ impl Io
     }
 
     pub fn save<P, T>(self, path: P, value: &T) -> IoResult
+    where
+        P: AsRef<Path>,
+        T: Save + ?Sized,
+    {
+        self.save_with_options(path, value, SaveOptions::DEFAULT)
+    }
+
+    pub fn save_with_options<P, T>(self, path: P, value: &T, options: SaveOptions) -> IoResult
     where
         P: AsRef<Path>,
         T: Save + ?Sized,
     {
         let path = path.as_ref();
+        let is_arg = |arg: &str| T::save_extension_args().any(|a| a == arg);
         let (bytes, extension) = value
-            .save_to_bytes(path.extension().map(|ex| ex.to_str()).flatten())
+            .save_to_bytes(path.full_extension_with_args(is_arg))
             .map_err(|e| IoError::new(path, e).when_writing())?;
 
         let path = match extension
         {
-            Some(ex) => path.with_extension(ex.as_ref()),
+            Some(ex) => path.with_full_extension_with_args(ex.as_ref(), is_arg),
             None => path.to_owned(),
         };
 
-        Io.save_bytes(&path, &bytes)
+        Io.save_bytes_with_options(&path, &bytes, options)
     }
 
     /*
@@ -138,3 +453,100 @@ impl Io
     }
     */
 }
+
+/// Split `assets.pack/textures/hero.png` in the pack `assets.pack` and its entry `textures/hero.png`.
+///
+/// `None` if no parent of the path is a pack.
+fn pack_entry_of(path: &Path) -> Option<(&Path, String)>
+{
+    let pack_path = path.ancestors().skip(1).find(|p| p.extension_or_empty() == Extension::PACK)?;
+    let entry = path
+        .strip_prefix(pack_path)
+        .ok()?
+        .iter()
+        .map(|c| c.to_str())
+        .collect::<Option<Vec<_>>>()?
+        .join("/");
+    Some((pack_path, entry))
+}
+
+/// Only read the index of the pack, and only once while the pack is unchanged. The errors are reported at `path`.
+fn open_pack(pack_path: &Path, path: &Path) -> IoResult<Pack<Box<dyn ReadSeek>>>
+{
+    // opening is cheap, even a `MemoryFs` don't copy the pack
+    let (source, metadata, reader) = match split_scheme(pack_path)
+    {
+        _ if FILE_CACHE.exists(pack_path) => (PackSource::FileCache, FILE_CACHE.metadata(pack_path), FILE_CACHE.open(pack_path)),
+        Some(_) => (PackSource::Vfs, Io.vfs_metadata(pack_path), Io.vfs_open(pack_path)),
+        None =>
+        {
+            let fs = Io.file_system();
+            (PackSource::FileSystem(Arc::downgrade(&fs)), fs.metadata(pack_path), fs.open(pack_path))
+        }
+    };
+    let reader = reader?;
+    let Ok(metadata) = metadata
+    else
+    {
+        return Pack::open(reader).map_err(|e| IoError::new(path, e).when_reading());
+    };
+
+    if let Some(index) = PACK_INDEXES.lock().unwrap().get(pack_path)
+        && index.source == source
+        && index.metadata == metadata
+    {
+        return Ok(index.pack.with_reader(reader));
+    }
+    // not locked while reading
+    let pack = Pack::open(reader).map_err(|e| IoError::new(path, e).when_reading())?;
+    PACK_INDEXES.lock().unwrap().insert(
+        pack_path.to_owned(),
+        PackIndex {
+            source,
+            metadata,
+            pack: pack.with_reader(()),
+        },
+    );
+    Ok(pack)
+}
+
+/// Resolve `assets.pack/textures/hero.png` to the entry `textures/hero.png` of the pack `assets.pack`.
+///
+/// `None` if no parent of the path is a pack.
+fn load_pack_entry(path: &Path) -> Option<IoResult<Cow<'static, [u8]>>>
+{
+    let (pack_path, entry) = pack_entry_of(path)?;
+    Some(open_pack(pack_path, path).and_then(|mut pack| match pack.contains(&entry)
+    {
+        true => pack.read_bytes(&entry).map(Cow::Owned).map_err(|e| IoError::new(path, e).when_reading()),
+        false => Err(IoError::new(path, FileError::NotFound).when_reading()),
+    }))
+}
+
+/// The entries inherit the modification time of the pack
+fn pack_entry_metadata(path: &Path) -> Option<IoResult<FileMetadata>>
+{
+    let (pack_path, entry) = pack_entry_of(path)?;
+    let modified = Io.metadata(pack_path).ok().and_then(|metadata| metadata.modified);
+    Some(open_pack(pack_path, path).and_then(|pack| match pack.entry(&entry)
+    {
+        Some(e) => Ok(FileMetadata::file(e.len, modified)),
+        None if !list_pack_entries(&pack, &entry, path).is_empty() => Ok(FileMetadata::dir(modified)),
+        None => Err(IoError::new(path, FileError::NotFound).when_reading()),
+    }))
+}
+
+/// List `assets.pack` or `assets.pack/levels`
+fn list_pack_dir(dir: &Path) -> Option<IoResult<Vec<(PathBuf, FileKind)>>>
+{
+    let (pack_path, prefix) = match dir.extension_or_empty() == Extension::PACK
+    {
+        true => (dir, String::new()),
+        false => pack_entry_of(dir)?,
+    };
+    Some(open_pack(pack_path, dir).and_then(|pack| match list_pack_entries(&pack, &prefix, dir)
+    {
+        paths if paths.is_empty() && !prefix.is_empty() => Err(IoError::new(dir, FileError::NotFound).when_reading()),
+        paths => Ok(paths),
+    }))
+}
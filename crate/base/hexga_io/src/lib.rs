@@ -3,7 +3,7 @@
 //!
 //! It support custom user define extension and convertion,
 //! and it's also support common markup extension (json, ron, xml...).
-use hexga_encoding::{Base64Error, EncodeError, prelude::*};
+use hexga_encoding::{Base64Error, EncodeError, Pack, PackWriter, prelude::*};
 use std::fmt::Display;
 use std::{str::Utf8Error, string::FromUtf8Error};
 
@@ -26,6 +26,21 @@ pub use std::path::{Path, PathBuf};
 mod io;
 pub use io::*;
 
+mod vfs;
+pub use vfs::*;
+
+mod save_options;
+pub use save_options::*;
+
+mod file_metadata;
+pub use file_metadata::*;
+
+mod glob;
+pub use glob::*;
+
+mod watcher;
+pub use watcher::*;
+
 mod result;
 pub use result::*;
 
@@ -36,13 +51,17 @@ pub mod prelude
 {
     pub use super::{
         FileError,
+        FileMetadata,
         IoError,
         IoLoad,
         IoResult,
         IoSave, //fs_path::*,
         //result::*,
         PathExtension,
+        SaveOptions,
         io::*,
+        vfs::{FileSystem, MemoryFs, OsFs, OverlayFs, PackFs},
+        watcher::Watcher,
     };
 }
 
@@ -3,6 +3,21 @@ use super::*;
 pub trait PathExtension
 {
     fn extension_or_empty(&self) -> &extension;
+
+    /// The extension with the stacked outer compressions and checksums, ex: `ron.gz` for `world.ron.gz`.
+    ///
+    /// Same as [`Path::extension`] when the last extension is not a compression or a checksum.
+    fn full_extension(&self) -> Option<&extension>;
+
+    /// Same as [`Self::full_extension`], but also include the [`ExtensionArgs`] before it,
+    /// ex: `flat.ron.gz` for `mygrid.flat.ron.gz` when `is_arg("flat")`.
+    fn full_extension_with_args(&self, is_arg: impl Fn(&str) -> bool) -> Option<&extension>;
+
+    /// Replace the [full extension](Self::full_extension) by the given one, ex: `world.ron.gz` with `json.zst` give `world.json.zst`.
+    fn with_full_extension(&self, extension: &extension) -> PathBuf;
+
+    /// Replace the [full extension with the arguments](Self::full_extension_with_args) by the given one.
+    fn with_full_extension_with_args(&self, extension: &extension, is_arg: impl Fn(&str) -> bool) -> PathBuf;
 }
 impl PathExtension for Path
 {
@@ -18,4 +33,67 @@ impl PathExtension for Path
             None => "",
         }
     }
+
+    fn full_extension(&self) -> Option<&extension> { self.full_extension_with_args(|_| false) }
+
+    fn full_extension_with_args(&self, is_arg: impl Fn(&str) -> bool) -> Option<&extension>
+    {
+        let name = self.file_name()?.to_str()?;
+        let mut start = name.len() - self.extension()?.len();
+        let previous_extension = |start: usize| Path::new(&name[..start - 1]).extension().and_then(|e| e.to_str());
+
+        while is_outer_extension(name[start..].split('.').next().unwrap_or_default())
+        {
+            match previous_extension(start)
+            {
+                Some(inner) => start -= inner.len() + 1,
+                None => break,
+            }
+        }
+
+        while let Some(arg) = previous_extension(start)
+            && is_arg(arg)
+        {
+            start -= arg.len() + 1;
+        }
+
+        Some(&name[start..])
+    }
+
+    fn with_full_extension(&self, extension: &extension) -> PathBuf { self.with_full_extension_with_args(extension, |_| false) }
+
+    fn with_full_extension_with_args(&self, extension: &extension, is_arg: impl Fn(&str) -> bool) -> PathBuf
+    {
+        let (Some(name), Some(full_extension)) = (self.file_name().and_then(|n| n.to_str()), self.full_extension_with_args(is_arg))
+        else
+        {
+            return self.with_extension(extension);
+        };
+        let stem = &name[..name.len() - full_extension.len() - 1];
+        if extension.is_empty()
+        {
+            self.with_file_name(stem)
+        }
+        else
+        {
+            self.with_file_name(format!("{stem}.{extension}"))
+        }
+    }
+}
+
+/// A [`Compression`](hexga_encoding::Compression) or a [`Checksum`](hexga_encoding::Checksum), applied on top of the inner extension
+fn is_outer_extension(extension: &extension) -> bool
+{
+    #[cfg(feature = "compression")]
+    if hexga_encoding::Compression::try_from(extension).is_ok()
+    {
+        return true;
+    }
+    #[cfg(feature = "checksum")]
+    if hexga_encoding::Checksum::try_from(extension).is_ok()
+    {
+        return true;
+    }
+    let _ = extension;
+    false
 }
@@ -122,6 +122,10 @@ pub enum FileError
     DownloadFailed,
     Unimplemented,
     NotFound,
+    /// Saving in a read-only [`FileSystem`](crate::FileSystem)
+    ReadOnly,
+    /// A path going out of the root of its [`FileSystem`](crate::FileSystem), with `..` or an absolute path
+    InvalidPath,
     Custom(Reason),
     Std(std::io::ErrorKind),
     Encoding(EncodeError),
@@ -170,6 +174,7 @@ impl FileError
     pub fn from_display(reason: impl Display) -> Self { Self::custom(reason.to_string()) }
 
     pub fn is_encoding(&self) -> bool { matches!(self, Self::Encoding(_)) }
+    pub fn is_not_found(&self) -> bool { matches!(self, Self::NotFound | Self::Std(std::io::ErrorKind::NotFound)) }
 }
 
 impl Display for FileError
@@ -183,6 +188,8 @@ impl Display for FileError
             FileError::NotSupported => f.write_str("not supported"),
             FileError::DownloadFailed => f.write_str("download failed"),
             FileError::NotFound => f.write_str("not found"),
+            FileError::ReadOnly => f.write_str("read only"),
+            FileError::InvalidPath => f.write_str("invalid path"),
             FileError::Custom(reason) => write!(f, "custom: {reason}"),
             FileError::Std(kind) => write!(f, "std: {kind}"),
             FileError::Encoding(encode_error) => write!(f, "encoding: {encode_error}"),
@@ -0,0 +1,115 @@
+use super::*;
+
+/// How [`Io::save_bytes_with_options`] save a file.
+///
+/// The file is always written atomically: a crash while saving keep the previous file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SaveOptions
+{
+    /// Number of previous versions to keep, ex: `save.ron.bak1` (the newest) to `save.ron.bak3` (the oldest) for 3.
+    /// See [`Io::restore_backup`].
+    pub backups: usize,
+    /// Remove the files that have the name of a parent directory, ex: a file `saves` when saving `saves/slot1.ron`.
+    /// Otherwise saving fail.
+    pub replace_parent_files: bool,
+}
+
+impl SaveOptions
+{
+    pub const DEFAULT: Self = Self {
+        backups: 0,
+        replace_parent_files: false,
+    };
+
+    pub const fn with_backups(mut self, backups: usize) -> Self
+    {
+        self.backups = backups;
+        self
+    }
+    pub const fn with_replace_parent_files(mut self, replace_parent_files: bool) -> Self
+    {
+        self.replace_parent_files = replace_parent_files;
+        self
+    }
+}
+
+impl Default for SaveOptions
+{
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// The path of a backup, ex: `save.ron.bak1` for the newest backup of `save.ron`
+pub fn backup_path(path: &Path, index: usize) -> PathBuf
+{
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".bak{index}"));
+    backup.into()
+}
+
+pub(crate) fn remove_parent_files(fs: &dyn FileSystem, path: &Path) -> IoResult
+{
+    for parent in path.ancestors().skip(1).filter(|p| !p.as_os_str().is_empty())
+    {
+        if fs.exists(parent) && !fs.is_dir(parent)
+        {
+            fs.remove(parent)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shift the backups of `path`, dropping the oldest, then copy `path` in the newest.
+///
+/// The file is copied and not moved, so `path` is never missing.
+pub(crate) fn rotate_backups(fs: &dyn FileSystem, path: &Path, backups: usize) -> IoResult
+{
+    if backups == 0 || !fs.exists(path)
+    {
+        return Ok(());
+    }
+    for index in (1..backups).rev()
+    {
+        let backup = backup_path(path, index);
+        if fs.exists(&backup)
+        {
+            fs.rename(&backup, &backup_path(path, index + 1))?;
+        }
+    }
+    fs.save_bytes(&backup_path(path, 1), &fs.load_bytes(path)?)
+}
+
+impl Io
+{
+    /// Replace `path` by its newest backup (`.bak1`), and shift the older ones.
+    /// Can be called again to go back further.
+    ///
+    /// Fail with [`FileError::NotFound`] if there is no backup.
+    pub fn restore_backup<P>(self, path: P) -> IoResult
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        forget_packs(path);
+        let (fs, relative) = Io.writable_file_system(path)?;
+        let at_path = |err: IoError| IoError { path: path.to_owned(), ..err };
+
+        let newest = backup_path(relative, 1);
+        if !fs.exists(&newest)
+        {
+            return Err(IoError::new(backup_path(path, 1), FileError::NotFound).when_reading());
+        }
+        fs.rename(&newest, relative).map_err(at_path)?;
+        let mut index = 2;
+        while fs.exists(&backup_path(relative, index))
+        {
+            fs.rename(&backup_path(relative, index), &backup_path(relative, index - 1)).map_err(at_path)?;
+            index += 1;
+        }
+
+        if Io.file_cache().exists(path)
+        {
+            Io.set_file_cache(path, fs.load_bytes(relative).map_err(at_path)?);
+        }
+        Ok(())
+    }
+}
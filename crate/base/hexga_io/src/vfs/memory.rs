@@ -0,0 +1,235 @@
+use super::*;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Files kept in memory, ex: to run the tests without touching the disk, see [`Io::with_file_system`].
+///
+/// The directories are implied by the paths of the files.
+/// The clones share the same files, so a test can keep one to inspect what was saved.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFs
+{
+    files: Arc<RwLock<BTreeMap<PathBuf, MemoryFile>>>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile
+{
+    bytes: MemoryBytes,
+    modified: SystemTime,
+}
+
+impl MemoryFile
+{
+    fn new(bytes: Cow<'static, [u8]>) -> Self
+    {
+        let bytes = match bytes
+        {
+            Cow::Borrowed(bytes) => MemoryBytes::Static(bytes),
+            Cow::Owned(bytes) => MemoryBytes::Shared(bytes.into()),
+        };
+        Self {
+            bytes,
+            modified: SystemTime::now(),
+        }
+    }
+}
+
+/// Shared by the file and its readers, so [`FileSystem::open`] don't copy it
+#[derive(Debug, Clone)]
+enum MemoryBytes
+{
+    Static(&'static [u8]),
+    Shared(Arc<[u8]>),
+}
+
+impl AsRef<[u8]> for MemoryBytes
+{
+    fn as_ref(&self) -> &[u8]
+    {
+        match self
+        {
+            MemoryBytes::Static(bytes) => bytes,
+            MemoryBytes::Shared(bytes) => bytes,
+        }
+    }
+}
+
+impl MemoryFs
+{
+    pub fn new() -> Self { Self::default() }
+
+    /// Add or replace a file, without copying `bytes` if they are `'static`
+    pub fn insert<P, B>(&self, path: P, bytes: B) -> &Self
+    where
+        P: Into<PathBuf>,
+        B: Into<Cow<'static, [u8]>>,
+    {
+        self.files.write().unwrap().insert(path.into(), MemoryFile::new(bytes.into()));
+        self
+    }
+
+    /// Only copy the bytes that are not `'static`
+    pub fn get(&self, path: &Path) -> Option<Cow<'static, [u8]>>
+    {
+        self.files.read().unwrap().get(path).map(|file| match &file.bytes
+        {
+            MemoryBytes::Static(bytes) => Cow::Borrowed(*bytes),
+            MemoryBytes::Shared(bytes) => Cow::Owned(bytes.to_vec()),
+        })
+    }
+
+    /// Remove all the files
+    pub fn clear(&self) { self.files.write().unwrap().clear(); }
+
+    /// The paths of all the files, sorted
+    pub fn paths(&self) -> Vec<PathBuf> { self.files.read().unwrap().keys().cloned().collect() }
+
+    /// The number of files
+    pub fn len(&self) -> usize { self.files.read().unwrap().len() }
+    pub fn is_empty(&self) -> bool { self.files.read().unwrap().is_empty() }
+
+    /// The files inside `path` are sorted right after it, so only the next one is checked
+    fn is_dir(files: &BTreeMap<PathBuf, MemoryFile>, path: &Path) -> bool
+    {
+        path.as_os_str().is_empty()
+            || files
+                .range(path.to_path_buf()..)
+                .map(|(p, _)| p)
+                .find(|p| *p != path)
+                .is_some_and(|p| p.starts_with(path))
+    }
+}
+
+impl FileSystem for MemoryFs
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>>
+    {
+        self.get(path)
+            .map(|bytes| bytes.into_owned())
+            .ok_or_else(|| IoError::new(path, FileError::NotFound).when_reading())
+    }
+
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult
+    {
+        let mut files = self.files.write().unwrap();
+        if Self::is_dir(&files, path)
+        {
+            return Err(IoError::new(path, "a directory already exists at this path").when_writing());
+        }
+        if let Some(parent) = path.ancestors().skip(1).find(|p| files.contains_key(*p))
+        {
+            return Err(IoError::new(path, format!("the parent {} is a file", parent.display())).when_writing());
+        }
+        files.insert(path.to_owned(), MemoryFile::new(Cow::Owned(bytes.to_owned())));
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool
+    {
+        let files = self.files.read().unwrap();
+        files.contains_key(path) || Self::is_dir(&files, path)
+    }
+
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>>
+    {
+        let files = self.files.read().unwrap();
+        if !Self::is_dir(&files, dir)
+        {
+            return Err(IoError::new(dir, FileError::NotFound).when_reading());
+        }
+        let mut paths = files
+            .keys()
+            .filter_map(|p| p.strip_prefix(dir).ok()?.iter().next())
+            .map(|name| dir.join(name))
+            .collect::<Vec<_>>();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let files = self.files.read().unwrap();
+        if !Self::is_dir(&files, dir)
+        {
+            return Err(IoError::new(dir, FileError::NotFound).when_reading());
+        }
+        let mut paths = files
+            .keys()
+            .filter_map(|p| {
+                let mut rest = p.strip_prefix(dir).ok()?.iter();
+                let name = rest.next()?;
+                let kind = if rest.next().is_some() { FileKind::Dir } else { FileKind::File };
+                Some((dir.join(name), kind))
+            })
+            .collect::<Vec<_>>();
+        paths.dedup_by(|a, b| a.0 == b.0);
+        Ok(paths)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool { Self::is_dir(&self.files.read().unwrap(), path) }
+
+    /// The directories have no modification time
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata>
+    {
+        let files = self.files.read().unwrap();
+        match files.get(path)
+        {
+            Some(file) => Ok(FileMetadata::file(file.bytes.as_ref().len() as u64, Some(file.modified))),
+            None if Self::is_dir(&files, path) => Ok(FileMetadata::dir(None)),
+            None => Err(IoError::new(path, FileError::NotFound).when_reading()),
+        }
+    }
+
+    /// Also move the directories, but not the root
+    fn rename(&self, from: &Path, to: &Path) -> IoResult
+    {
+        if from.parent().is_none()
+        {
+            return Err(IoError::new(from, FileError::InvalidPath).when_writing());
+        }
+        let mut files = self.files.write().unwrap();
+        let moved = files.keys().filter(|p| p.starts_with(from)).cloned().collect::<Vec<_>>();
+        if moved.is_empty()
+        {
+            return Err(IoError::new(from, FileError::NotFound).when_writing());
+        }
+        for path in moved
+        {
+            let file = files.remove(&path).unwrap();
+            let path = match path.strip_prefix(from)
+            {
+                Ok(rest) if !rest.as_os_str().is_empty() => to.join(rest),
+                _ => to.to_owned(),
+            };
+            files.insert(path, file);
+        }
+        Ok(())
+    }
+
+    /// Without copying the file
+    fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>>
+    {
+        self.files
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|file| Box::new(Cursor::new(file.bytes.clone())) as Box<dyn ReadSeek>)
+            .ok_or_else(|| IoError::new(path, FileError::NotFound).when_reading())
+    }
+
+    fn remove(&self, path: &Path) -> IoResult
+    {
+        let mut files = self.files.write().unwrap();
+        if files.remove(path).is_none()
+        {
+            if !Self::is_dir(&files, path)
+            {
+                return Err(IoError::new(path, FileError::NotFound).when_writing());
+            }
+            files.retain(|p, _| !p.starts_with(path));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+//! Virtual file system: [`FileSystem`] backends mounted at a scheme, ex: `assets://textures/hero.png`.
+//!
+//! The paths without a scheme are still the paths of the OS.
+use super::*;
+use std::io::{Cursor, Read, Seek};
+use std::sync::{Arc, LazyLock, RwLock};
+
+mod os;
+pub use os::*;
+
+mod memory;
+pub use memory::*;
+
+mod pack;
+pub use pack::*;
+
+mod overlay;
+pub use overlay::*;
+
+/// A storage that can be mounted in a [`Vfs`].
+///
+/// The paths given to the backend are relative to its mount point, ex: `textures/hero.png` for `assets://textures/hero.png`.
+pub trait FileSystem: Send + Sync
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>>;
+
+    /// Fail with [`FileError::ReadOnly`] by default
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult
+    {
+        let _ = bytes;
+        Err(IoError::new(path, FileError::ReadOnly).when_writing())
+    }
+
+    fn exists(&self, path: &Path) -> bool;
+
+    /// The files and directories directly inside `dir`, sorted. Not supported by default.
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>> { Err(IoError::new(dir, FileError::NotSupported).when_reading()) }
+
+    /// Like [`Self::list`], with the kind of each entry. Use [`Self::is_dir`] on each of them by default.
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let paths = self.list(dir)?;
+        Ok(paths
+            .into_iter()
+            .map(|path| {
+                let kind = if self.is_dir(&path) { FileKind::Dir } else { FileKind::File };
+                (path, kind)
+            })
+            .collect())
+    }
+
+    /// Remove a file, or a directory with all its content. Fail with [`FileError::ReadOnly`] by default
+    fn remove(&self, path: &Path) -> IoResult { Err(IoError::new(path, FileError::ReadOnly).when_writing()) }
+
+    /// Default to [`Self::list`] succeeding
+    fn is_dir(&self, path: &Path) -> bool { self.list(path).is_ok() }
+
+    /// Use [`Self::is_dir`] and [`Self::load_bytes`] by default, without modification time
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata>
+    {
+        match self.is_dir(path)
+        {
+            true => Ok(FileMetadata::dir(None)),
+            false => self.load_bytes(path).map(|bytes| FileMetadata::file(bytes.len() as u64, None)),
+        }
+    }
+
+    /// Move a file, replacing the destination if any. Copy then remove it by default.
+    fn rename(&self, from: &Path, to: &Path) -> IoResult
+    {
+        self.save_bytes(to, &self.load_bytes(from)?)?;
+        self.remove(from)
+    }
+
+    /// Open a file to read only some parts of it, ex: the entries of a [`Pack`]. Load the whole file by default
+    fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>> { self.load_bytes(path).map(|bytes| Box::new(Cursor::new(bytes)) as Box<dyn ReadSeek>) }
+
+    /// Where the file is on the disk, if it is, ex: to watch it with the OS, see [`Watcher`]
+    fn os_path(&self, path: &Path) -> Option<PathBuf>
+    {
+        let _ = path;
+        None
+    }
+
+    /// The [`Vfs`] skip the read-only backends when saving
+    fn is_read_only(&self) -> bool { false }
+}
+
+/// A shared backend, ex: [`Io::file_system`] to use it in another thread with [`Io::with_file_system`]
+impl<F> FileSystem for Arc<F>
+where
+    F: FileSystem + ?Sized,
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>> { self.as_ref().load_bytes(path) }
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult { self.as_ref().save_bytes(path, bytes) }
+    fn exists(&self, path: &Path) -> bool { self.as_ref().exists(path) }
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>> { self.as_ref().list(dir) }
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>> { self.as_ref().list_with_kinds(dir) }
+    fn remove(&self, path: &Path) -> IoResult { self.as_ref().remove(path) }
+    fn is_dir(&self, path: &Path) -> bool { self.as_ref().is_dir(path) }
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata> { self.as_ref().metadata(path) }
+    fn rename(&self, from: &Path, to: &Path) -> IoResult { self.as_ref().rename(from, to) }
+    fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>> { self.as_ref().open(path) }
+    fn os_path(&self, path: &Path) -> Option<PathBuf> { self.as_ref().os_path(path) }
+    fn is_read_only(&self) -> bool { self.as_ref().is_read_only() }
+}
+
+/// A reader returned by [`FileSystem::open`]
+pub trait ReadSeek: Read + Seek {}
+impl<T> ReadSeek for T where T: Read + Seek {}
+
+/// Split `assets://textures/hero.png` in the scheme `assets` and the path `textures/hero.png`.
+///
+/// The single letter schemes are not accepted, so the Windows drives (`C:/`) stay OS paths.
+pub fn split_scheme(path: &Path) -> Option<(&str, &Path)>
+{
+    let (scheme, rest) = path.to_str()?.split_once(':')?;
+    let valid = scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    // `PathBuf::pop` can turn `assets://a` into `assets:/`, both are accepted
+    let rest = rest.strip_prefix('/').filter(|_| valid)?;
+    Some((scheme, Path::new(rest.trim_start_matches('/'))))
+}
+
+/// Identify a mount, to [unmount](Vfs::unmount) it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MountId(u64);
+
+struct Mount
+{
+    id: MountId,
+    scheme: String,
+    priority: i32,
+    fs: Arc<dyn FileSystem>,
+}
+
+/// The [`FileSystem`] mounted at each scheme.
+///
+/// Several backends can be mounted at the same scheme: the files are searched by decreasing priority,
+/// so a mod mounted with a higher priority override the base files. With the same priority, the last mounted win.
+///
+/// The [`Io`] use a global one, see [`Io::mount`].
+#[derive(Default)]
+pub struct Vfs
+{
+    /// Sorted by decreasing priority
+    mounts: Vec<Mount>,
+    next_id: u64,
+}
+
+impl Vfs
+{
+    pub fn new() -> Self { Self::default() }
+
+    pub fn mount<F>(&mut self, scheme: &str, priority: i32, fs: F) -> MountId
+    where
+        F: FileSystem + 'static,
+    {
+        let id = MountId(self.next_id);
+        self.next_id += 1;
+        let index = self.mounts.iter().position(|m| m.priority <= priority).unwrap_or(self.mounts.len());
+        self.mounts.insert(
+            index,
+            Mount {
+                id,
+                scheme: scheme.to_owned(),
+                priority,
+                fs: Arc::new(fs),
+            },
+        );
+        id
+    }
+
+    /// Return `false` if it was not mounted
+    pub fn unmount(&mut self, id: MountId) -> bool
+    {
+        let len = self.mounts.len();
+        self.mounts.retain(|m| m.id != id);
+        self.mounts.len() != len
+    }
+
+    /// The backends mounted at `scheme`, by decreasing priority
+    fn file_systems(&self, scheme: &str) -> Vec<Arc<dyn FileSystem>> { self.mounts.iter().filter(|m| m.scheme == scheme).map(|m| m.fs.clone()).collect() }
+
+    pub fn is_mounted(&self, scheme: &str) -> bool { self.mounts.iter().any(|m| m.scheme == scheme) }
+
+    fn not_mounted(path: &Path, scheme: &str) -> IoError { IoError::new(path, format!("nothing is mounted at `{scheme}://`")) }
+
+    /// Load from the first backend having the file. `path` must have a scheme, see [`split_scheme`].
+    pub fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>> { load_bytes(self.file_systems_of(path)?, path) }
+
+    /// Save in the first backend that is not read-only
+    pub fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult
+    {
+        let (fs, relative) = self.writable_of(path)?;
+        fs.save_bytes(relative, bytes).map_err(|err| IoError { path: path.to_owned(), ..err })
+    }
+
+    /// The content of the directory in all the backends, ex: `assets://levels/intro.ron` for `assets://levels`
+    pub fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>> { list(self.file_systems_of(dir)?, dir) }
+
+    /// Remove from all the writable backends of the scheme. If a read-only backend also have the file, its version is visible again,
+    /// ex: removing a modified `config.ron` restore the one of the mounted pack. Fail with [`FileError::ReadOnly`] if only read-only backends have it.
+    pub fn remove(&self, path: &Path) -> IoResult { remove(self.file_systems_of(path)?, path) }
+
+    pub fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>> { open(self.file_systems_of(path)?, path) }
+
+    pub fn metadata(&self, path: &Path) -> IoResult<FileMetadata> { metadata(self.file_systems_of(path)?, path) }
+
+    /// Move a file inside the writable backend of the scheme. `from` and `to` must have the same scheme.
+    pub fn rename(&self, from: &Path, to: &Path) -> IoResult { rename(self.rename_of(from, to)?, from) }
+
+    pub fn exists(&self, path: &Path) -> bool { self.file_systems_of(path).is_ok_and(|file_systems| exists(file_systems, path)) }
+
+    fn file_systems_of<'p>(&self, path: &'p Path) -> IoResult<(Vec<Arc<dyn FileSystem>>, &'p Path)>
+    {
+        let (scheme, relative) = split_scheme(path).ok_or_else(|| IoError::new(path, "expected a path like `scheme://path`"))?;
+        let file_systems = self.file_systems(scheme);
+        if file_systems.is_empty()
+        {
+            return Err(Self::not_mounted(path, scheme));
+        }
+        Ok((file_systems, relative))
+    }
+
+    fn rename_of<'p>(&self, from: &'p Path, to: &'p Path) -> IoResult<(Arc<dyn FileSystem>, &'p Path, &'p Path)>
+    {
+        let (fs, relative_from) = self.writable_of(from)?;
+        match (split_scheme(from), split_scheme(to))
+        {
+            (Some((scheme, _)), Some((to_scheme, relative_to))) if scheme == to_scheme => Ok((fs, relative_from, relative_to)),
+            _ => Err(IoError::new(to, "can only rename inside the same scheme").when_writing()),
+        }
+    }
+
+    /// The first backend that is not read-only, where `path` is saved
+    fn writable_of<'p>(&self, path: &'p Path) -> IoResult<(Arc<dyn FileSystem>, &'p Path)>
+    {
+        let (file_systems, relative) = self.file_systems_of(path)?;
+        match file_systems.into_iter().find(|fs| !fs.is_read_only())
+        {
+            Some(fs) => Ok((fs, relative)),
+            None => Err(IoError::new(path, FileError::ReadOnly).when_writing()),
+        }
+    }
+}
+
+// The global vfs lock is released before calling the backends, so they can use the `Io` themselves
+
+/// The result of the first backend having the file
+fn first_found<T, F>((file_systems, relative): (Vec<Arc<dyn FileSystem>>, &Path), path: &Path, f: F) -> IoResult<T>
+where
+    F: Fn(&dyn FileSystem, &Path) -> IoResult<T>,
+{
+    let mut not_found = None;
+    for fs in file_systems
+    {
+        match f(fs.as_ref(), relative)
+        {
+            Ok(value) => return Ok(value),
+            Err(err) if err.kind.is_not_found() => not_found = not_found.or(Some(err)),
+            Err(err) => return Err(IoError { path: path.to_owned(), ..err }),
+        }
+    }
+    Err(IoError::new(path, not_found.map(|e| e.kind).unwrap_or(FileError::NotFound)).when_reading())
+}
+
+fn load_bytes(file_systems: (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult<Vec<u8>>
+{
+    first_found(file_systems, path, |fs, relative| fs.load_bytes(relative))
+}
+
+fn open(file_systems: (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult<Box<dyn ReadSeek>>
+{
+    first_found(file_systems, path, |fs, relative| fs.open(relative))
+}
+
+fn metadata(file_systems: (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult<FileMetadata>
+{
+    first_found(file_systems, path, |fs, relative| fs.metadata(relative))
+}
+
+/// Merge the content of `dir` in all the backends
+fn list((file_systems, relative): (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult<Vec<PathBuf>>
+{
+    let mut paths = None;
+    for fs in file_systems
+    {
+        match fs.list(relative)
+        {
+            Ok(list) => paths
+                .get_or_insert_with(Vec::new)
+                .extend(list.iter().filter_map(|p| p.file_name()).map(|name| path.join(name))),
+            Err(err) if err.kind.is_not_found() || err.kind == FileError::NotSupported =>
+            {}
+            Err(err) => return Err(IoError { path: path.to_owned(), ..err }),
+        }
+    }
+    let mut paths = paths.ok_or_else(|| IoError::new(path, FileError::NotFound).when_reading())?;
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Same as [`list`], with the kind of each entry
+fn list_with_kinds((file_systems, relative): (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+{
+    let mut paths = None;
+    for fs in file_systems
+    {
+        match fs.list_with_kinds(relative)
+        {
+            Ok(list) => paths
+                .get_or_insert_with(Vec::new)
+                .extend(list.iter().filter_map(|(p, kind)| Some((path.join(p.file_name()?), *kind)))),
+            Err(err) if err.kind.is_not_found() || err.kind == FileError::NotSupported =>
+            {}
+            Err(err) => return Err(IoError { path: path.to_owned(), ..err }),
+        }
+    }
+    let mut paths = paths.ok_or_else(|| IoError::new(path, FileError::NotFound).when_reading())?;
+    paths.sort_by(|a, b| a.0.cmp(&b.0));
+    paths.dedup_by(|a, b| a.0 == b.0);
+    Ok(paths)
+}
+
+/// Remove from all the writable backends. The file of a read-only backend is still there and visible after, see [`Vfs::remove`]
+fn remove((file_systems, relative): (Vec<Arc<dyn FileSystem>>, &Path), path: &Path) -> IoResult
+{
+    let mut removed = false;
+    for fs in file_systems.iter().filter(|fs| !fs.is_read_only() && fs.exists(relative))
+    {
+        fs.remove(relative).map_err(|err| IoError { path: path.to_owned(), ..err })?;
+        removed = true;
+    }
+    match removed
+    {
+        true => Ok(()),
+        false if file_systems.iter().any(|fs| fs.exists(relative)) => Err(IoError::new(path, FileError::ReadOnly).when_writing()),
+        false => Err(IoError::new(path, FileError::NotFound).when_writing()),
+    }
+}
+
+fn rename((fs, from, to): (Arc<dyn FileSystem>, &Path, &Path), path: &Path) -> IoResult
+{
+    fs.rename(from, to).map_err(|err| IoError { path: path.to_owned(), ..err })
+}
+
+fn exists((file_systems, relative): (Vec<Arc<dyn FileSystem>>, &Path), _path: &Path) -> bool { file_systems.iter().any(|fs| fs.exists(relative)) }
+
+static VFS: LazyLock<RwLock<Vfs>> = LazyLock::new(Default::default);
+
+impl Io
+{
+    /// Mount `fs` at `scheme` in the global [`Vfs`], ex: `Io.mount("assets", 0, OsFs::new("assets"))`
+    /// to load `assets://textures/hero.png`.
+    pub fn mount<F>(self, scheme: &str, priority: i32, fs: F) -> MountId
+    where
+        F: FileSystem + 'static,
+    {
+        VFS.write().unwrap().mount(scheme, priority, fs)
+    }
+
+    pub fn unmount(self, id: MountId) -> bool { VFS.write().unwrap().unmount(id) }
+
+    pub fn is_mounted(self, scheme: &str) -> bool { VFS.read().unwrap().is_mounted(scheme) }
+
+    pub(crate) fn vfs_load_bytes(self, path: &Path) -> IoResult<Vec<u8>>
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(path)?;
+        load_bytes(file_systems, path)
+    }
+
+    /// The backend where `path` is saved, and the path inside it
+    pub(crate) fn vfs_writable(self, path: &Path) -> IoResult<(Arc<dyn FileSystem>, &Path)> { VFS.read().unwrap().writable_of(path) }
+
+    pub(crate) fn vfs_exists(self, path: &Path) -> bool { VFS.read().unwrap().file_systems_of(path).is_ok_and(|file_systems| exists(file_systems, path)) }
+
+    pub(crate) fn vfs_metadata(self, path: &Path) -> IoResult<FileMetadata>
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(path)?;
+        metadata(file_systems, path)
+    }
+
+    pub(crate) fn vfs_list(self, dir: &Path) -> IoResult<Vec<PathBuf>>
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(dir)?;
+        list(file_systems, dir)
+    }
+
+    pub(crate) fn vfs_list_with_kinds(self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(dir)?;
+        list_with_kinds(file_systems, dir)
+    }
+
+    pub(crate) fn vfs_remove(self, path: &Path) -> IoResult
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(path)?;
+        remove(file_systems, path)
+    }
+
+    pub(crate) fn vfs_rename(self, from: &Path, to: &Path) -> IoResult
+    {
+        let target = VFS.read().unwrap().rename_of(from, to)?;
+        rename(target, from)
+    }
+
+    pub(crate) fn vfs_open(self, path: &Path) -> IoResult<Box<dyn ReadSeek>>
+    {
+        let file_systems = VFS.read().unwrap().file_systems_of(path)?;
+        open(file_systems, path)
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+use std::path::Component;
+
+/// A directory of the OS.
+///
+/// The paths can't go out of the root: `..` and the absolute paths are refused with [`FileError::InvalidPath`].
+/// Except for an empty root, that is the whole disk (the default [`Io::file_system`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsFs
+{
+    root: PathBuf,
+}
+
+impl OsFs
+{
+    pub fn new(root: impl Into<PathBuf>) -> Self { Self { root: root.into() } }
+
+    pub fn root(&self) -> &Path { &self.root }
+
+    /// The path on the disk, if it stays in the root
+    pub fn resolve(&self, path: &Path) -> IoResult<PathBuf>
+    {
+        if self.root.as_os_str().is_empty()
+        {
+            return Ok(path.to_path_buf());
+        }
+        let mut resolved = self.root.clone();
+        for component in path.components()
+        {
+            match component
+            {
+                Component::Normal(name) => resolved.push(name),
+                Component::CurDir =>
+                {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => return Err(IoError::new(path, FileError::InvalidPath)),
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+impl FileSystem for OsFs
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>> { fs::load_bytes(&self.resolve(path)?) }
+
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult { fs::save_bytes(&self.resolve(path)?, bytes) }
+
+    fn exists(&self, path: &Path) -> bool { self.resolve(path).is_ok_and(|path| path.exists()) }
+
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>>
+    {
+        let paths = fs::list(&self.resolve(dir)?)?;
+        Ok(paths.iter().filter_map(|p| p.file_name()).map(|name| dir.join(name)).collect())
+    }
+
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let paths = fs::list_with_kinds(&self.resolve(dir)?)?;
+        Ok(paths.into_iter().filter_map(|(p, kind)| Some((dir.join(p.file_name()?), kind))).collect())
+    }
+
+    fn remove(&self, path: &Path) -> IoResult { fs::remove(&self.resolve(path)?) }
+
+    fn is_dir(&self, path: &Path) -> bool { self.resolve(path).is_ok_and(|path| path.is_dir()) }
+
+    fn os_path(&self, path: &Path) -> Option<PathBuf> { self.resolve(path).ok() }
+
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata> { fs::metadata(&self.resolve(path)?) }
+
+    fn rename(&self, from: &Path, to: &Path) -> IoResult { fs::rename(&self.resolve(from)?, &self.resolve(to)?) }
+
+    fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>> { fs::open(&self.resolve(path)?).map(|reader| Box::new(reader) as Box<dyn ReadSeek>) }
+}
@@ -0,0 +1,124 @@
+use super::*;
+
+/// Layers of [`FileSystem`]: the files are searched from the top layer, and saved in the top writable layer.
+///
+/// Same as mounting each layer with increasing priorities, but as a single backend.
+#[derive(Default)]
+pub struct OverlayFs
+{
+    /// The last is the top
+    layers: Vec<Box<dyn FileSystem>>,
+}
+
+impl OverlayFs
+{
+    pub fn new() -> Self { Self::default() }
+
+    /// Add a layer on top of the others
+    pub fn with_layer<F>(mut self, fs: F) -> Self
+    where
+        F: FileSystem + 'static,
+    {
+        self.layers.push(Box::new(fs));
+        self
+    }
+
+    /// The result of the top layer having the file
+    fn first_found<T, F>(&self, path: &Path, f: F) -> IoResult<T>
+    where
+        F: Fn(&dyn FileSystem) -> IoResult<T>,
+    {
+        let mut not_found = None;
+        for fs in self.layers.iter().rev()
+        {
+            match f(fs.as_ref())
+            {
+                Ok(value) => return Ok(value),
+                Err(err) if err.kind.is_not_found() => not_found = not_found.or(Some(err)),
+                Err(err) => return Err(err),
+            }
+        }
+        Err(not_found.unwrap_or_else(|| IoError::new(path, FileError::NotFound).when_reading()))
+    }
+}
+
+impl FileSystem for OverlayFs
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>> { self.first_found(path, |fs| fs.load_bytes(path)) }
+
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult
+    {
+        match self.layers.iter().rev().find(|fs| !fs.is_read_only())
+        {
+            Some(fs) => fs.save_bytes(path, bytes),
+            None => Err(IoError::new(path, FileError::ReadOnly).when_writing()),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool { self.layers.iter().any(|fs| fs.exists(path)) }
+
+    fn is_dir(&self, path: &Path) -> bool { self.layers.iter().any(|fs| fs.is_dir(path)) }
+
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata> { self.first_found(path, |fs| fs.metadata(path)) }
+
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>>
+    {
+        let mut paths = None;
+        for fs in &self.layers
+        {
+            match fs.list(dir)
+            {
+                Ok(list) => paths.get_or_insert_with(Vec::new).extend(list),
+                Err(err) if err.kind.is_not_found() || err.kind == FileError::NotSupported =>
+                {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut paths = paths.ok_or_else(|| IoError::new(dir, FileError::NotFound).when_reading())?;
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let mut paths = None;
+        for fs in self.layers.iter().rev()
+        {
+            match fs.list_with_kinds(dir)
+            {
+                Ok(list) => paths.get_or_insert_with(Vec::new).extend(list),
+                Err(err) if err.kind.is_not_found() || err.kind == FileError::NotSupported =>
+                {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut paths = paths.ok_or_else(|| IoError::new(dir, FileError::NotFound).when_reading())?;
+        // the top layer first
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths.dedup_by(|a, b| a.0 == b.0);
+        Ok(paths)
+    }
+
+    /// Remove from all the writable layers. If a read-only layer also have the file, its version is visible again,
+    /// ex: removing a modified `config.ron` restore the default one. Fail with [`FileError::ReadOnly`] if only read-only layers have it.
+    fn remove(&self, path: &Path) -> IoResult
+    {
+        let mut removed = false;
+        for fs in self.layers.iter().filter(|fs| !fs.is_read_only() && fs.exists(path))
+        {
+            fs.remove(path)?;
+            removed = true;
+        }
+        match removed
+        {
+            true => Ok(()),
+            false if self.exists(path) => Err(IoError::new(path, FileError::ReadOnly).when_writing()),
+            false => Err(IoError::new(path, FileError::NotFound).when_writing()),
+        }
+    }
+
+    fn open(&self, path: &Path) -> IoResult<Box<dyn ReadSeek>> { self.first_found(path, |fs| fs.open(path)) }
+
+    fn is_read_only(&self) -> bool { self.layers.iter().all(|fs| fs.is_read_only()) }
+}
@@ -0,0 +1,98 @@
+use super::*;
+use std::io::{Cursor, Read, Seek};
+use std::sync::Mutex;
+
+/// A read-only [`Pack`] archive
+pub struct PackFs<R>
+{
+    pack: Mutex<Pack<R>>,
+}
+
+impl<R> PackFs<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(pack: Pack<R>) -> Self { Self { pack: Mutex::new(pack) } }
+
+    pub fn open(reader: R) -> EncodeResult<Self> { Pack::open(reader).map(Self::new) }
+}
+
+impl PackFs<Cursor<Vec<u8>>>
+{
+    pub fn from_bytes(bytes: Vec<u8>) -> EncodeResult<Self> { Self::open(Cursor::new(bytes)) }
+}
+
+/// The path of an entry, with `/` separators
+fn entry(path: &Path) -> Option<String> { path.iter().map(|c| c.to_str()).collect::<Option<Vec<_>>>().map(|c| c.join("/")) }
+
+/// The entries and the directories directly inside the directory `prefix` of the pack, joined to `dir`
+pub(crate) fn list_pack_entries<R>(pack: &Pack<R>, prefix: &str, dir: &Path) -> Vec<(PathBuf, FileKind)>
+{
+    let mut paths = pack
+        .paths()
+        .filter_map(|p| match prefix.is_empty()
+        {
+            true => Some(p),
+            false => p.strip_prefix(prefix)?.strip_prefix('/'),
+        })
+        .map(|p| match p.split_once('/')
+        {
+            Some((name, _)) => (dir.join(name), FileKind::Dir),
+            None => (dir.join(p), FileKind::File),
+        })
+        .collect::<Vec<_>>();
+    paths.dedup_by(|a, b| a.0 == b.0);
+    paths
+}
+
+impl<R> FileSystem for PackFs<R>
+where
+    R: Read + Seek + Send,
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>>
+    {
+        let mut pack = self.pack.lock().unwrap();
+        match entry(path).filter(|entry| pack.contains(entry))
+        {
+            Some(entry) => pack.read_bytes(&entry).map_err(|e| IoError::new(path, e).when_reading()),
+            None => Err(IoError::new(path, FileError::NotFound).when_reading()),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool
+    {
+        let Some(entry) = entry(path)
+        else
+        {
+            return false;
+        };
+        let pack = self.pack.lock().unwrap();
+        // the directories are implied by the paths of the entries
+        pack.contains(&entry) || entry.is_empty() || pack.paths().any(|p| p.strip_prefix(&entry).is_some_and(|p| p.starts_with('/')))
+    }
+
+    fn list(&self, dir: &Path) -> IoResult<Vec<PathBuf>> { Ok(self.list_with_kinds(dir)?.into_iter().map(|(path, _)| path).collect()) }
+
+    fn list_with_kinds(&self, dir: &Path) -> IoResult<Vec<(PathBuf, FileKind)>>
+    {
+        let prefix = entry(dir).ok_or_else(|| IoError::new(dir, FileError::NotFound).when_reading())?;
+        match list_pack_entries(&self.pack.lock().unwrap(), &prefix, dir)
+        {
+            paths if paths.is_empty() && !prefix.is_empty() => Err(IoError::new(dir, FileError::NotFound).when_reading()),
+            paths => Ok(paths),
+        }
+    }
+
+    /// Without reading the entry
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata>
+    {
+        match entry(path).and_then(|entry| self.pack.lock().unwrap().entry(&entry))
+        {
+            Some(entry) => Ok(FileMetadata::file(entry.len, None)),
+            None if self.is_dir(path) => Ok(FileMetadata::dir(None)),
+            None => Err(IoError::new(path, FileError::NotFound).when_reading()),
+        }
+    }
+
+    fn is_read_only(&self) -> bool { true }
+}
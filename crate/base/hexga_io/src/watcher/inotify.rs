@@ -0,0 +1,136 @@
+use super::*;
+use std::ffi::{CString, OsStr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+
+const EVENTS: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MODIFY
+    | libc::IN_CLOSE_WRITE
+    | libc::IN_ATTRIB
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF
+    | libc::IN_MOVE_SELF;
+
+/// The directories watched by inotify.
+///
+/// The watches are never removed: the events of a path that is no longer watched are ignored by the [`Watcher`].
+/// The kernel remove them when the directory is deleted ([`libc::IN_IGNORED`]), see [`Self::is_watching`].
+#[derive(Debug)]
+pub(crate) struct Inotify
+{
+    fd: OwnedFd,
+    /// The watch descriptors
+    dirs: HashMap<i32, PathBuf>,
+    wds: HashMap<PathBuf, i32>,
+}
+
+#[repr(C, align(8))]
+struct EventBuffer([u8; 4096]);
+
+impl Inotify
+{
+    pub(crate) fn new() -> Option<Self>
+    {
+        // SAFETY: no pointer
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0
+        {
+            return None;
+        }
+        Some(Self {
+            // SAFETY: the fd was just opened, and is only owned here
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            dirs: HashMap::new(),
+            wds: HashMap::new(),
+        })
+    }
+
+    /// Return `false` if the directory can't be watched, ex: it doesn't exist
+    pub(crate) fn add_dir(&mut self, dir: &Path) -> bool
+    {
+        let Ok(path) = CString::new(dir.as_os_str().as_bytes())
+        else
+        {
+            return false;
+        };
+        // SAFETY: `path` is a valid C string
+        let wd = unsafe { libc::inotify_add_watch(self.fd.as_raw_fd(), path.as_ptr(), EVENTS) };
+        if wd < 0
+        {
+            return false;
+        }
+        self.dirs.insert(wd, dir.to_owned());
+        self.wds.insert(dir.to_owned(), wd);
+        true
+    }
+
+    /// `false` once the directory was deleted, even if it was created again since
+    pub(crate) fn is_watching(&self, dir: &Path) -> bool { self.wds.contains_key(dir) }
+
+    /// Watch the directory and all its sub directories
+    pub(crate) fn add_dir_all(&mut self, dir: &Path) -> bool
+    {
+        if !self.add_dir(dir)
+        {
+            return false;
+        }
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten()
+        {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+            {
+                self.add_dir_all(&entry.path());
+            }
+        }
+        true
+    }
+
+    /// The paths of the events since the last call, `None` if some events were lost
+    pub(crate) fn read_events(&mut self) -> Option<Vec<PathBuf>>
+    {
+        let mut paths = Vec::new();
+        let mut buffer = EventBuffer([0; 4096]);
+        loop
+        {
+            // SAFETY: the buffer is valid for its length
+            let len = unsafe { libc::read(self.fd.as_raw_fd(), buffer.0.as_mut_ptr().cast(), buffer.0.len()) };
+            if len <= 0
+            {
+                // `EAGAIN`: no more event
+                return Some(paths);
+            }
+            let mut events = &buffer.0[..len as usize];
+            while events.len() >= size_of::<libc::inotify_event>()
+            {
+                // SAFETY: the kernel write whole events, and the header fit in the remaining bytes
+                let event = unsafe { std::ptr::read_unaligned(events.as_ptr().cast::<libc::inotify_event>()) };
+                let end = (size_of::<libc::inotify_event>() + event.len as usize).min(events.len());
+                let name = &events[size_of::<libc::inotify_event>()..end];
+                events = &events[end..];
+
+                if event.mask & libc::IN_Q_OVERFLOW != 0
+                {
+                    return None;
+                }
+                let Some(dir) = self.dirs.get(&event.wd)
+                else
+                {
+                    continue;
+                };
+                let name = name.split(|b| *b == 0).next().unwrap_or_default();
+                paths.push(match name.is_empty()
+                {
+                    true => dir.clone(),
+                    false => dir.join(OsStr::from_bytes(name)),
+                });
+                if event.mask & libc::IN_IGNORED != 0
+                    && let Some(dir) = self.dirs.remove(&event.wd)
+                    && self.wds.get(&dir) == Some(&event.wd)
+                {
+                    self.wds.remove(&dir);
+                }
+            }
+        }
+    }
+}
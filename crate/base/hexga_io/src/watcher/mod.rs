@@ -0,0 +1,271 @@
+//! Report the files that changed, ex: to hot reload them.
+use super::*;
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
+
+#[cfg(target_os = "linux")]
+mod inotify;
+
+/// Report the files created, modified or removed since the last call of [`Watcher::changed`].
+///
+/// The files are compared by size and modification time, using the [`Io`], so any [`FileSystem`] can be watched.
+/// On Linux, the paths on the disk are also watched with inotify: only the paths with an event are compared again.
+///
+/// ```
+/// use hexga_io::prelude::*;
+///
+/// let fs = MemoryFs::new();
+/// Io.with_file_system(fs.clone(), || {
+///     let mut watcher = Watcher::new();
+///     watcher.watch("levels");
+///     Io.save_str("levels/intro.ron", "()").unwrap();
+///     assert_eq!(watcher.changed(), [std::path::Path::new("levels/intro.ron")]);
+///     assert!(watcher.changed().is_empty());
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct Watcher
+{
+    watched: BTreeMap<PathBuf, Watched>,
+    #[cfg(target_os = "linux")]
+    inotify: Option<inotify::Inotify>,
+}
+
+#[derive(Debug)]
+struct Watched
+{
+    /// A directory is watched with all its sub directories
+    is_dir: bool,
+    /// Where it is on the disk, if it is watched by the OS
+    os_path: Option<PathBuf>,
+    /// The size and modification time of each file
+    snapshot: BTreeMap<PathBuf, (u64, Option<SystemTime>)>,
+}
+
+impl Watcher
+{
+    /// Use the OS when possible, and compare the files otherwise
+    pub fn new() -> Self
+    {
+        Self {
+            #[cfg(target_os = "linux")]
+            inotify: inotify::Inotify::new(),
+            ..Self::polling()
+        }
+    }
+
+    /// Only compare the files, without the OS
+    pub fn polling() -> Self
+    {
+        Self {
+            watched: BTreeMap::new(),
+            #[cfg(target_os = "linux")]
+            inotify: None,
+        }
+    }
+
+    /// Watch a directory with all its files, or a file with its sidecars (`hero.png.meta` for `hero.png`).
+    ///
+    /// A file without extension also watch the files with an extension, ex: `levels/intro` watch `levels/intro.ron`.
+    /// The path don't need to exist yet.
+    pub fn watch<P>(&mut self, path: P) -> &mut Self
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if self.watched.contains_key(path)
+        {
+            return self;
+        }
+        let is_dir = Io.metadata(path).is_ok_and(|metadata| metadata.is_dir);
+        let os_path = self.watch_os(path, is_dir);
+        let mut watched = Watched {
+            is_dir,
+            os_path,
+            snapshot: BTreeMap::new(),
+        };
+        watched.snapshot = watched.scan(path, &mut HashMap::new());
+        self.watched.insert(path.to_owned(), watched);
+        self
+    }
+
+    /// Return `false` if it was not watched
+    pub fn unwatch<P>(&mut self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.watched.remove(path.as_ref()).is_some()
+    }
+
+    pub fn is_watched<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.watched.contains_key(path.as_ref())
+    }
+
+    pub fn watched(&self) -> impl Iterator<Item = &Path> { self.watched.keys().map(|path| path.as_path()) }
+
+    /// Watch the path with the OS: the directory and its sub directories, or the directory containing the file
+    #[cfg(target_os = "linux")]
+    fn watch_os(&mut self, path: &Path, is_dir: bool) -> Option<PathBuf>
+    {
+        let inotify = self.inotify.as_mut()?;
+        let os_path = match split_scheme(path)
+        {
+            Some(_) => None,
+            None => Io.file_system().os_path(path),
+        }?;
+        let watched = match is_dir
+        {
+            true => inotify.add_dir_all(&os_path),
+            false => inotify.add_dir(os_parent(&os_path)),
+        };
+        watched.then_some(os_path)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn watch_os(&mut self, _path: &Path, _is_dir: bool) -> Option<PathBuf> { None }
+
+    /// The paths of the files created, modified or removed since the last call, sorted
+    pub fn changed(&mut self) -> Vec<PathBuf>
+    {
+        // `None` if everything must be compared
+        #[cfg(target_os = "linux")]
+        let events = self.inotify.as_mut().and_then(|inotify| inotify.read_events());
+
+        let mut changed = Vec::new();
+        // the directories listed by this call
+        let mut listed = HashMap::new();
+        for (path, watched) in self.watched.iter_mut()
+        {
+            #[cfg(target_os = "linux")]
+            if let (Some(os_path), Some(events), Some(inotify)) = (&watched.os_path, &events, &mut self.inotify)
+                && watched.is_watched_by(os_path, inotify)
+            {
+                let mut affected = false;
+                for event in events.iter().filter(|event| watched.is_affected_by(os_path, event))
+                {
+                    affected = true;
+                    if watched.is_dir && event.is_dir()
+                    {
+                        inotify.add_dir_all(event);
+                    }
+                }
+                if !affected
+                {
+                    continue;
+                }
+            }
+
+            let was_dir = watched.is_dir;
+            watched.is_dir = Io.metadata(path).is_ok_and(|metadata| metadata.is_dir);
+            #[cfg(target_os = "linux")]
+            if let (Some(os_path), Some(inotify), true) = (&watched.os_path, &mut self.inotify, watched.is_dir && !was_dir)
+            {
+                // created since it is watched
+                inotify.add_dir_all(os_path);
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = was_dir;
+
+            let snapshot = watched.scan(path, &mut listed);
+            let modified = snapshot.iter().filter(|(file, signature)| watched.snapshot.get(*file) != Some(signature));
+            let removed = watched.snapshot.keys().filter(|file| !snapshot.contains_key(*file));
+            changed.extend(modified.map(|(file, _)| file).chain(removed).cloned());
+            watched.snapshot = snapshot;
+        }
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+/// The directory watched by the OS for a file
+#[cfg(target_os = "linux")]
+fn os_parent(os_path: &Path) -> &Path { os_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new(".")) }
+
+/// The temporary files of the atomic saves, see [`Io::save_bytes`]
+fn is_temporary(name: &str) -> bool { name.starts_with('.') && name.ends_with(".tmp") }
+
+impl Watched
+{
+    fn scan(&self, path: &Path, listed: &mut HashMap<PathBuf, Vec<PathBuf>>) -> BTreeMap<PathBuf, (u64, Option<SystemTime>)>
+    {
+        let files = match self.is_dir
+        {
+            true => Io.walk_dir(path).unwrap_or_default(),
+            false =>
+            {
+                let Some(name) = path.file_name().and_then(|name| name.to_str())
+                else
+                {
+                    return BTreeMap::new();
+                };
+                let parent = path.parent().unwrap_or(Path::new(""));
+                let siblings = listed.entry(parent.to_owned()).or_insert_with(|| Io.list_dir(parent).unwrap_or_default());
+                siblings
+                    .iter()
+                    .filter(|sibling| {
+                        sibling
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| n == name || n.strip_prefix(name).is_some_and(|rest| rest.starts_with('.')))
+                    })
+                    .cloned()
+                    .collect()
+            }
+        };
+        files
+            .into_iter()
+            .filter(|file| !file.file_name().and_then(|name| name.to_str()).is_some_and(is_temporary))
+            .filter_map(|file| {
+                let metadata = Io.metadata(&file).ok().filter(|metadata| metadata.is_file())?;
+                Some((file, (metadata.size, metadata.modified)))
+            })
+            .collect()
+    }
+
+    /// If the OS still watch the directory of this. Otherwise, ex: the directory was deleted then created again,
+    /// watch it again and return `false`: there may be changes without event.
+    ///
+    /// If it can't be watched again, ex: it doesn't exist, it is compared at each [`Watcher::changed`] until it can.
+    #[cfg(target_os = "linux")]
+    fn is_watched_by(&self, os_path: &Path, inotify: &mut inotify::Inotify) -> bool
+    {
+        let dir = match self.is_dir
+        {
+            true => os_path,
+            false => os_parent(os_path),
+        };
+        if inotify.is_watching(dir)
+        {
+            return true;
+        }
+        match self.is_dir
+        {
+            true => inotify.add_dir_all(dir),
+            false => inotify.add_dir(dir),
+        };
+        false
+    }
+
+    /// If an event of the OS on `event` may change this
+    #[cfg(target_os = "linux")]
+    fn is_affected_by(&self, os_path: &Path, event: &Path) -> bool
+    {
+        if event.starts_with(os_path)
+        {
+            return true;
+        }
+        // the file, its sidecars, or the directory being created
+        let name = os_path.file_name().and_then(|n| n.to_str());
+        let event_name = event.file_name().and_then(|n| n.to_str());
+        event.parent() == Some(os_parent(os_path))
+            && match (name, event_name)
+            {
+                (Some(name), Some(event_name)) => event_name.strip_prefix(name).is_some_and(|rest| rest.is_empty() || rest.starts_with('.')),
+                _ => true,
+            }
+    }
+}
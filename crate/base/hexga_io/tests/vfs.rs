@@ -0,0 +1,455 @@
+mod common;
+
+use common::{Room, room};
+use hexga_encoding::prelude::*;
+use hexga_encoding::{Pack, PackWriter};
+use hexga_io::prelude::*;
+use hexga_io::{FileError, backup_path, split_scheme};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn pack_archive()
+{
+    let mut writer = PackWriter::new(Vec::new()).unwrap();
+    writer.add("rooms/entry.ron", &room("entry")).unwrap();
+    writer.add("rooms/boss.tmp.gz", &room("boss")).unwrap();
+    writer.add("level.ron", &vec![room("entry"), room("boss")]).unwrap();
+    writer.add_bytes("readme.txt", b"hello").unwrap();
+    assert!(writer.add_bytes("readme.txt", b"again").is_err());
+    assert!(writer.add_bytes("/absolute", b"").is_err());
+    let bytes = writer.finish().unwrap();
+    assert_eq!(sniff_extension(&bytes), Some(Extension::PACK));
+
+    let mut pack = Pack::open(std::io::Cursor::new(bytes.as_slice())).unwrap();
+    assert_eq!(
+        pack.paths().collect::<Vec<_>>(),
+        ["level.ron", "readme.txt", "rooms/boss.tmp.gz", "rooms/entry.ron"]
+    );
+    assert_eq!(pack.load::<Room>("rooms/entry.ron").unwrap(), room("entry"));
+    assert_eq!(pack.load::<Room>("rooms/boss.tmp.gz").unwrap(), room("boss"));
+    assert_eq!(pack.load::<Vec<Room>>("level.ron").unwrap(), [room("entry"), room("boss")]);
+    assert_eq!(pack.read_bytes("readme.txt").unwrap(), b"hello");
+    assert!(pack.read_bytes("missing.txt").is_err());
+
+    // Damaged
+    assert!(Pack::open(std::io::Cursor::new(&bytes[..bytes.len() - 1])).is_err());
+    assert!(Pack::open(std::io::Cursor::new(&bytes[..3])).is_err());
+    let mut damaged = bytes.clone();
+    let len = damaged.len();
+    damaged[len - 8..].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(Pack::open(std::io::Cursor::new(damaged)).is_err());
+
+    // Resolved by the io, from the file or from the cache
+    let dir = std::env::temp_dir().join(format!("hexga_pack_archive_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    Io.save_pack(dir.join("assets.pack"), |pack| {
+        pack.add("rooms/entry.ron", &room("entry"))?;
+        pack.add("readme.txt", &"hello".to_owned())
+    })
+    .unwrap();
+    assert_eq!(Room::load(dir.join("assets.pack/rooms/entry.ron")).unwrap(), room("entry"));
+    // Probing the extensions also work inside the pack
+    assert_eq!(Room::load(dir.join("assets.pack/rooms/entry")).unwrap(), room("entry"));
+    // A custom extension
+    assert_eq!(String::load(dir.join("assets.pack/readme.txt")).unwrap(), "hello");
+    assert!(matches!(
+        Room::load(dir.join("assets.pack/rooms/missing.ron")),
+        Err(IoError { kind: FileError::NotFound, .. })
+    ));
+
+    Io.set_file_cache(dir.join("cached.pack"), bytes);
+    assert_eq!(Room::load(dir.join("cached.pack/rooms/entry.ron")).unwrap(), room("entry"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn pack_index_cache()
+{
+    let fs = MemoryFs::new();
+    Io.with_file_system(fs.clone(), || {
+        let save = |name: &str| Io.save_pack("assets.pack", |pack| pack.add("rooms/entry.ron", &room(name))).unwrap();
+        save("first");
+        assert_eq!(Room::load("assets.pack/rooms/entry.ron").unwrap(), room("first"));
+        assert_eq!(Room::load("assets.pack/rooms/entry.ron").unwrap(), room("first"));
+
+        // Saved again: the index is read again
+        save("second");
+        assert_eq!(Room::load("assets.pack/rooms/entry.ron").unwrap(), room("second"));
+
+        // Changed without the io
+        let mut writer = PackWriter::new(Vec::new()).unwrap();
+        writer.add("rooms/entry.ron", &room("changed outside")).unwrap();
+        fs.insert("assets.pack", writer.finish().unwrap());
+        assert_eq!(Room::load("assets.pack/rooms/entry.ron").unwrap(), room("changed outside"));
+
+        Io.remove("assets.pack").unwrap();
+        assert!(matches!(
+            Room::load("assets.pack/rooms/entry.ron"),
+            Err(IoError { kind: FileError::NotFound, .. })
+        ));
+    });
+}
+
+/// The files always have the same metadata, as if they never changed
+struct SameMetadataFs(MemoryFs);
+
+impl FileSystem for SameMetadataFs
+{
+    fn load_bytes(&self, path: &Path) -> IoResult<Vec<u8>> { self.0.load_bytes(path) }
+    fn save_bytes(&self, path: &Path, bytes: &[u8]) -> IoResult { self.0.save_bytes(path, bytes) }
+    fn exists(&self, path: &Path) -> bool { self.0.exists(path) }
+    fn metadata(&self, path: &Path) -> IoResult<FileMetadata> { self.0.metadata(path).map(|_| FileMetadata::file(0, None)) }
+}
+
+#[test]
+fn pack_index_cache_file_systems()
+{
+    // Same path and metadata, but another file system, even if the previous one was dropped
+    let file_systems = ["first", "the other one"].map(|name| {
+        let fs = MemoryFs::new();
+        let mut writer = PackWriter::new(Vec::new()).unwrap();
+        writer.add("rooms/entry.ron", &room(name)).unwrap();
+        fs.insert("assets_fs.pack", writer.finish().unwrap());
+        (name, fs)
+    });
+    for (name, fs) in file_systems
+    {
+        Io.with_file_system(SameMetadataFs(fs), || {
+            assert_eq!(Room::load("assets_fs.pack/rooms/entry.ron").unwrap(), room(name));
+        });
+    }
+}
+
+#[test]
+fn vfs_mount()
+{
+    assert_eq!(
+        split_scheme(Path::new("assets://textures/hero.png")),
+        Some(("assets", Path::new("textures/hero.png")))
+    );
+    assert_eq!(split_scheme(Path::new("assets:/hero.png")), Some(("assets", Path::new("hero.png"))));
+    assert_eq!(split_scheme(Path::new("C:/hero.png")), None);
+    assert_eq!(split_scheme(Path::new("textures/hero.png")), None);
+
+    let dir = std::env::temp_dir().join(format!("hexga_vfs_mount_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("rooms")).unwrap();
+    room("base").save(dir.join("rooms/entry.ron")).unwrap();
+    room("base").save(dir.join("rooms/boss.ron")).unwrap();
+
+    // A mod override the base files
+    let base = Io.mount("vfs_test", 0, OsFs::new(&dir));
+    let modded = MemoryFs::new();
+    modded
+        .save_bytes(Path::new("rooms/boss.ron"), room("modded").to_ron().unwrap().as_bytes())
+        .unwrap();
+    let mod_id = Io.mount("vfs_test", 10, modded);
+
+    assert_eq!(Room::load("vfs_test://rooms/entry.ron").unwrap(), room("base"));
+    assert_eq!(Room::load("vfs_test://rooms/boss.ron").unwrap(), room("modded"));
+    // Probing the extensions
+    assert_eq!(Room::load("vfs_test://rooms/boss").unwrap(), room("modded"));
+    assert!(matches!(
+        Room::load("vfs_test://rooms/missing.ron"),
+        Err(IoError { kind: FileError::NotFound, .. })
+    ));
+
+    // Saved in the writable backend with the highest priority
+    room("saved").save("vfs_test://rooms/saved.ron").unwrap();
+    assert_eq!(Room::load("vfs_test://rooms/saved.ron").unwrap(), room("saved"));
+    assert!(!dir.join("rooms/saved.ron").exists());
+
+    assert!(Io.unmount(mod_id));
+    assert!(!Io.unmount(mod_id));
+    assert_eq!(Room::load("vfs_test://rooms/boss.ron").unwrap(), room("base"));
+    assert!(Room::load("vfs_test://rooms/saved.ron").is_err());
+    assert!(Io.unmount(base));
+    assert!(!Io.is_mounted("vfs_test"));
+    assert!(Room::load("vfs_test://rooms/entry.ron").is_err());
+
+    // Read-only archive under a writable overlay
+    let mut writer = PackWriter::new(Vec::new()).unwrap();
+    writer.add("rooms/entry.ron", &room("packed")).unwrap();
+    let pack = PackFs::from_bytes(writer.finish().unwrap()).unwrap();
+    let overlay = OverlayFs::new().with_layer(pack).with_layer(MemoryFs::new());
+    let id = Io.mount("vfs_overlay", 0, overlay);
+    assert_eq!(Room::load("vfs_overlay://rooms/entry.ron").unwrap(), room("packed"));
+    room("saved").save("vfs_overlay://rooms/entry.ron").unwrap();
+    assert_eq!(Room::load("vfs_overlay://rooms/entry.ron").unwrap(), room("saved"));
+    // Removing the saved file restore the packed one, which can't be removed
+    Io.remove("vfs_overlay://rooms/entry.ron").unwrap();
+    assert_eq!(Room::load("vfs_overlay://rooms/entry.ron").unwrap(), room("packed"));
+    assert!(matches!(
+        Io.remove("vfs_overlay://rooms/entry.ron"),
+        Err(IoError { kind: FileError::ReadOnly, .. })
+    ));
+    Io.unmount(id);
+
+    // Same with a read-only pack mounted under a writable backend
+    let mut writer = PackWriter::new(Vec::new()).unwrap();
+    writer.add("rooms/entry.ron", &room("packed")).unwrap();
+    let pack_id = Io.mount("vfs_remove", 0, PackFs::from_bytes(writer.finish().unwrap()).unwrap());
+    let memory_id = Io.mount("vfs_remove", 10, MemoryFs::new());
+    room("saved").save("vfs_remove://rooms/entry.ron").unwrap();
+    Io.remove("vfs_remove://rooms/entry.ron").unwrap();
+    assert_eq!(Room::load("vfs_remove://rooms/entry.ron").unwrap(), room("packed"));
+    assert!(matches!(
+        Io.remove("vfs_remove://rooms/entry.ron"),
+        Err(IoError { kind: FileError::ReadOnly, .. })
+    ));
+    Io.unmount(memory_id);
+    Io.unmount(pack_id);
+
+    let id = Io.mount(
+        "vfs_read_only",
+        0,
+        PackFs::from_bytes(PackWriter::new(Vec::new()).unwrap().finish().unwrap()).unwrap(),
+    );
+    assert!(matches!(
+        room("room").save("vfs_read_only://room.ron"),
+        Err(IoError { kind: FileError::ReadOnly, .. })
+    ));
+    Io.unmount(id);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn vfs_path_escape()
+{
+    let dir = std::env::temp_dir().join(format!("hexga_vfs_escape_{}", std::process::id()));
+    room("secret").save(dir.join("secret.ron")).unwrap();
+    let id = Io.mount("vfs_escape", 0, OsFs::new(dir.join("mounted")));
+    room("inside").save("vfs_escape://room.ron").unwrap();
+    assert_eq!(Room::load("vfs_escape://./room.ron").unwrap(), room("inside"));
+
+    let invalid = |result: IoResult<Room>| {
+        matches!(
+            result,
+            Err(IoError {
+                kind: FileError::InvalidPath,
+                ..
+            })
+        )
+    };
+    assert!(invalid(Room::load("vfs_escape://../secret.ron")));
+    assert!(invalid(Room::load("vfs_escape://nested/../../secret.ron")));
+    assert!(room("overwritten").save("vfs_escape://../secret.ron").is_err());
+    assert!(Io.list_dir("vfs_escape://..").is_err());
+    assert!(!Io.exists("vfs_escape://../secret.ron"));
+    assert_eq!(Room::load(dir.join("secret.ron")).unwrap(), room("secret"));
+
+    // An absolute path would replace the root
+    let fs = OsFs::new(dir.join("mounted"));
+    assert!(fs.load_bytes(&dir.join("secret.ron")).is_err());
+    assert_eq!(fs.resolve(Path::new("a/./b.ron")).unwrap(), dir.join("mounted/a/b.ron"));
+
+    assert!(Io.unmount(id));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn memory_fs()
+{
+    let fs = MemoryFs::new();
+    Io.with_file_system(fs.clone(), || {
+        room("a").save("memory_fs/rooms/entry.ron").unwrap();
+        assert!(!Path::new("memory_fs").exists());
+        // Probing the extensions
+        assert_eq!(Room::load("memory_fs/rooms/entry").unwrap(), room("a"));
+        assert!(matches!(Room::load("memory_fs/rooms/missing"), Err(IoError { kind: FileError::NotFound, .. })));
+
+        room("b").save("memory_fs/rooms/boss.ron").unwrap();
+        room("c").save("memory_fs/readme.ron").unwrap();
+        assert_eq!(
+            fs.list(Path::new("memory_fs")).unwrap(),
+            [Path::new("memory_fs/readme.ron"), Path::new("memory_fs/rooms")]
+        );
+        assert!(fs.exists(Path::new("memory_fs/rooms")));
+        // A file can't be a directory
+        assert!(room("d").save("memory_fs/readme.ron/room.ron").is_err());
+        assert!(Io.save_bytes("memory_fs/rooms", b"room").is_err());
+
+        fs.remove(Path::new("memory_fs/rooms/boss.ron")).unwrap();
+        assert!(Room::load("memory_fs/rooms/boss.ron").is_err());
+        assert!(fs.remove(Path::new("memory_fs/rooms/boss.ron")).is_err());
+
+        // Only the files inside, not the ones with the same prefix
+        room("e").save("memory_fs/room.ron").unwrap();
+        room("f").save("memory_fs/room.ron.d/a.ron").unwrap();
+        assert!(!fs.is_dir(Path::new("memory_fs/room")));
+        assert!(!fs.is_dir(Path::new("memory_fs/room.ron")));
+        assert!(fs.is_dir(Path::new("memory_fs/room.ron.d")));
+
+        // The root can't be moved
+        for root in ["", "/"]
+        {
+            assert!(matches!(
+                fs.rename(Path::new(root), Path::new("moved")),
+                Err(IoError {
+                    kind: FileError::InvalidPath,
+                    ..
+                })
+            ));
+        }
+        assert!(fs.exists(Path::new("memory_fs/rooms/entry.ron")));
+    });
+
+    // Only for the closure
+    assert!(Room::load("memory_fs/rooms/entry.ron").is_err());
+    assert!(fs.exists(Path::new("memory_fs/rooms/entry.ron")));
+    fs.clear();
+    assert!(fs.is_empty());
+
+    // The cache is a `MemoryFs` loaded before the file system
+    Io.set_file_cache("memory_fs_cache/room.ron", room("cached").to_ron().unwrap().into_bytes());
+    assert_eq!(Room::load("memory_fs_cache/room").unwrap(), room("cached"));
+    Io.file_cache().remove(Path::new("memory_fs_cache")).unwrap();
+    assert!(Room::load("memory_fs_cache/room").is_err());
+}
+
+#[test]
+fn save_backups()
+{
+    let dir = std::env::temp_dir().join(format!("hexga_save_backups_{}", std::process::id()));
+    let memory = MemoryFs::new();
+
+    let check = |dir: &Path| {
+        let path = dir.join("saves/slot.ron");
+        let options = SaveOptions::DEFAULT.with_backups(2);
+        for name in ["first", "second", "third"]
+        {
+            Io.save_with_options(&path, &room(name), options).unwrap();
+        }
+        assert_eq!(Room::load(&path).unwrap(), room("third"));
+        assert_eq!(Room::load(backup_path(&path, 1)).unwrap(), room("second"));
+        assert_eq!(Room::load(backup_path(&path, 2)).unwrap(), room("first"));
+        // No temporary file left, and the oldest was dropped
+        let fs = Io.file_system();
+        assert_eq!(fs.list(&dir.join("saves")).unwrap().len(), 3);
+
+        Io.restore_backup(&path).unwrap();
+        assert_eq!(Room::load(&path).unwrap(), room("second"));
+        Io.restore_backup(&path).unwrap();
+        assert_eq!(Room::load(&path).unwrap(), room("first"));
+        assert!(matches!(Io.restore_backup(&path), Err(IoError { kind: FileError::NotFound, .. })));
+
+        // A file is in the way of a parent
+        let path = dir.join("saves/slot.ron/nested.ron");
+        assert!(room("nested").save(&path).is_err());
+        assert_eq!(Room::load(dir.join("saves/slot.ron")).unwrap(), room("first"));
+        Io.save_with_options(&path, &room("nested"), SaveOptions::DEFAULT.with_replace_parent_files(true))
+            .unwrap();
+        assert_eq!(Room::load(&path).unwrap(), room("nested"));
+    };
+
+    check(&dir);
+    Io.with_file_system(memory.clone(), || check(Path::new("save_backups")));
+    assert!(memory.exists(Path::new("save_backups/saves/slot.ron/nested.ron")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rename_file_cache()
+{
+    Io.with_file_system(MemoryFs::new(), || {
+        // Only in the cache
+        Io.set_file_cache("rename_file_cache/cached.ron", room("cached").to_ron().unwrap().into_bytes());
+        Io.rename("rename_file_cache/cached.ron", "rename_file_cache/moved.ron").unwrap();
+        assert!(!Io.exists("rename_file_cache/cached.ron"));
+        assert_eq!(Room::load("rename_file_cache/moved.ron").unwrap(), room("cached"));
+        Io.remove("rename_file_cache/moved.ron").unwrap();
+
+        assert!(
+            Io.rename("rename_file_cache/missing.ron", "rename_file_cache/moved.ron")
+                .is_err_and(|err| err.kind.is_not_found())
+        );
+    });
+}
+
+#[test]
+fn list_glob_metadata()
+{
+    let check = |dir: &Path| {
+        for path in ["levels/intro.ron", "levels/world1/a.ron", "levels/world1/deep/b.ron", "levels/world1/notes.txt"]
+        {
+            Io.save_bytes(dir.join(path), room(path).to_ron().unwrap().as_bytes()).unwrap();
+        }
+        let levels = dir.join("levels");
+        assert_eq!(Io.list_dir(&levels).unwrap(), [levels.join("intro.ron"), levels.join("world1")]);
+        let pattern = format!("{}/**/*.ron", levels.display());
+        assert_eq!(
+            Io.glob(&pattern).unwrap(),
+            [levels.join("intro.ron"), levels.join("world1/a.ron"), levels.join("world1/deep/b.ron")]
+        );
+        assert_eq!(Io.glob(&format!("{}/*/?.ron", levels.display())).unwrap(), [levels.join("world1/a.ron")]);
+        assert_eq!(Io.walk_dir(&levels).unwrap().len(), 4);
+
+        let intro = levels.join("intro.ron");
+        let metadata = Io.metadata(&intro).unwrap();
+        assert!(metadata.is_file());
+        assert_eq!(metadata.size, room("levels/intro.ron").to_ron().unwrap().len() as u64);
+        assert!(metadata.modified.is_some());
+        assert!(Io.metadata(&levels).unwrap().is_dir);
+
+        Io.rename(&intro, levels.join("moved/intro.ron")).unwrap();
+        assert!(!Io.exists(&intro));
+        assert_eq!(Room::load(levels.join("moved/intro.ron")).unwrap(), room("levels/intro.ron"));
+
+        Io.remove(levels.join("world1")).unwrap();
+        assert!(!Io.exists(levels.join("world1/a.ron")));
+        assert_eq!(Io.walk_dir(&levels).unwrap(), [levels.join("moved/intro.ron")]);
+        assert!(Io.remove(&intro).is_err_and(|err| err.kind.is_not_found()));
+    };
+
+    let dir = std::env::temp_dir().join(format!("hexga_list_glob_{}", std::process::id()));
+    check(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    Io.with_file_system(MemoryFs::new(), || check(Path::new("list_glob")));
+
+    // Mounted pack
+    let mut writer = PackWriter::new(Vec::new()).unwrap();
+    writer.add("levels/intro.ron", &room("intro")).unwrap();
+    writer.add("levels/world1/a.ron", &room("a")).unwrap();
+    let id = Io.mount("list_glob_pack", 0, PackFs::from_bytes(writer.finish().unwrap()).unwrap());
+    assert_eq!(
+        Io.glob("list_glob_pack://**/*.ron").unwrap(),
+        [
+            Path::new("list_glob_pack://levels/intro.ron"),
+            Path::new("list_glob_pack://levels/world1/a.ron")
+        ]
+    );
+    assert!(Io.exists("list_glob_pack://levels/world1"));
+    assert!(matches!(
+        Io.remove("list_glob_pack://levels/intro.ron"),
+        Err(IoError { kind: FileError::ReadOnly, .. })
+    ));
+    Io.unmount(id);
+}
+
+#[test]
+fn glob_missing_dir_and_symlink_loop()
+{
+    Io.with_file_system(MemoryFs::new(), || {
+        assert_eq!(Io.glob("missing/**/*.ron").unwrap(), Vec::<PathBuf>::new());
+        assert!(Io.walk_dir("missing").is_err_and(|err| err.kind.is_not_found()));
+    });
+
+    #[cfg(unix)]
+    {
+        let dir = std::env::temp_dir().join(format!("hexga_glob_symlink_{}", std::process::id()));
+        Io.save_bytes(dir.join("levels/world1/a.ron"), b"()").unwrap();
+        // a link to its parent would loop forever if it was followed
+        std::os::unix::fs::symlink(dir.join("levels"), dir.join("levels/world1/parent")).unwrap();
+        let levels = dir.join("levels");
+        assert_eq!(Io.glob(&format!("{}/**/*.ron", levels.display())).unwrap(), [levels.join("world1/a.ron")]);
+        assert_eq!(Io.walk_dir(&levels).unwrap(), [levels.join("world1/a.ron")]);
+        // but a link can still be named in the pattern
+        assert_eq!(
+            Io.glob(&format!("{}/world1/parent/*/*.ron", levels.display())).unwrap(),
+            [levels.join("world1/parent/world1/a.ron")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+mod common;
+use common::room;
+use hexga_io::prelude::*;
+use std::path::Path;
+
+#[test]
+fn watcher()
+{
+    // In memory, the changes are found by comparing the metadata, so this doesn't depend on the OS timing
+    Io.with_file_system(MemoryFs::new(), || {
+        room("intro").save("levels/intro.ron").unwrap();
+
+        let mut watchers = [Watcher::new(), Watcher::polling()];
+        for watcher in &mut watchers
+        {
+            watcher.watch("levels").watch("hero.png");
+            assert!(watcher.changed().is_empty());
+        }
+
+        room("a").save("levels/world1/a.ron").unwrap();
+        Io.save_str("hero.png.meta", "sidecar").unwrap();
+        Io.save_str("other.png", "not watched").unwrap();
+        for watcher in &mut watchers
+        {
+            assert_eq!(watcher.changed(), [Path::new("hero.png.meta"), Path::new("levels/world1/a.ron")]);
+            assert!(watcher.changed().is_empty());
+        }
+
+        // In the new directory
+        room("a modified").save("levels/world1/a.ron").unwrap();
+        Io.remove("levels/intro.ron").unwrap();
+        Io.save_str("hero.png", "png").unwrap();
+        for watcher in &mut watchers
+        {
+            assert_eq!(
+                watcher.changed(),
+                [Path::new("hero.png"), Path::new("levels/intro.ron"), Path::new("levels/world1/a.ron")]
+            );
+        }
+
+        assert!(watchers[0].unwatch("hero.png"));
+        Io.save_str("hero.png", "png modified").unwrap();
+        assert!(watchers[0].changed().is_empty());
+        assert_eq!(watchers[1].changed(), [Path::new("hero.png")]);
+    });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn watcher_recreated_dir()
+{
+    let dir = std::env::temp_dir().join(format!("hexga_watcher_recreated_{}", std::process::id()));
+    room("intro").save(dir.join("levels/intro.ron")).unwrap();
+    let mut watcher = Watcher::new();
+    watcher.watch(dir.join("levels"));
+
+    // The OS stop watching a deleted directory
+    std::fs::remove_dir_all(dir.join("levels")).unwrap();
+    std::fs::create_dir_all(dir.join("levels")).unwrap();
+    assert_eq!(watcher.changed(), [dir.join("levels/intro.ron")]);
+    room("boss").save(dir.join("levels/boss.ron")).unwrap();
+    assert_eq!(watcher.changed(), [dir.join("levels/boss.ron")]);
+
+    // Deleted, then created after a call
+    std::fs::remove_dir_all(dir.join("levels")).unwrap();
+    assert_eq!(watcher.changed(), [dir.join("levels/boss.ron")]);
+    room("intro").save(dir.join("levels/intro.ron")).unwrap();
+    assert_eq!(watcher.changed(), [dir.join("levels/intro.ron")]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
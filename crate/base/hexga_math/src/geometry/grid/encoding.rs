@@ -0,0 +1,274 @@
+use super::*;
+use hexga_encoding::prelude::*;
+
+#[cfg(feature = "serde")]
+use hexga_encoding::markup::AnyFormat;
+#[cfg(feature = "serde")]
+use serde::de::{DeserializeSeed, SeqAccess};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeSeq;
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+/// How the values are saved: `flat` in one sequence (the default), or `nested` with one sequence per axis, ex: the rows of a 2d grid.
+///
+/// ex: `mygrid.nested.ron`
+const GRID_EXTENSION_ARGS: [&str; 2] = ["flat", "nested"];
+
+impl<T, Idx, const N: usize> SaveExtension for GridOf<T, Idx, N>
+where
+    T: CfgSerialize,
+    Idx: Integer + CfgSerialize,
+{
+    /// `flat` (the default) or `nested`, ex: `mygrid.nested.ron`
+    fn save_custom_extension_args() -> impl Iterator<Item = &'static str> { GRID_EXTENSION_ARGS.into_iter() }
+
+    #[cfg(feature = "serde")]
+    fn save_to_writer_with_format<W>(&self, writer: W, format: AnyFormat, args: ExtensionArgs<'_>) -> EncodeResult
+    where
+        W: Write,
+    {
+        match args.contains("nested")
+        {
+            true =>
+            {
+                let sizes = (0..N).map(|axis| self.size[axis].to_usize()).collect::<Vec<_>>();
+                let grid = NestedGridRef {
+                    size: &self.size,
+                    values: NestedValues {
+                        values: &self.values,
+                        sizes: &sizes,
+                    },
+                };
+                format.encode_with_writer_and_args(&grid, writer, args)
+            }
+            false => format.encode_with_writer_and_args(self, writer, args),
+        }
+    }
+}
+
+impl<T, Idx, const N: usize> LoadExtension for GridOf<T, Idx, N>
+where
+    T: for<'de> CfgDeserialize<'de>,
+    Idx: Integer + for<'de> CfgDeserialize<'de>,
+{
+    /// `flat` (the default) or `nested`, ex: `mygrid.nested.ron`
+    fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { GRID_EXTENSION_ARGS.into_iter() }
+
+    #[cfg(feature = "serde")]
+    fn load_from_bytes_borrowed_with_format<'a>(bytes: &'a [u8], format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + Deserialize<'a>,
+    {
+        match args.contains("nested")
+        {
+            true => format.from_bytes_with_limits::<NestedGrid<T, Idx, N>>(bytes, limits).map(|grid| grid.0),
+            false => format.from_bytes_with_limits(bytes, limits),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_from_reader_with_format<R>(reader: R, format: AnyFormat, limits: DecodeLimits, args: ExtensionArgs<'_>) -> EncodeResult<Self>
+    where
+        Self: Sized + for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        match args.contains("nested")
+        {
+            true => format.from_reader_with_limits::<NestedGrid<T, Idx, N>, R>(reader, limits).map(|grid| grid.0),
+            false => format.from_reader_with_limits(reader, limits),
+        }
+    }
+}
+
+/// Save a [`GridOf`] with the `nested` layout: the same as the flat one, but the values are nested by axis, the last axis outside.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(rename = "Grid")]
+struct NestedGridRef<'a, T, Idx, const N: usize>
+where
+    Idx: Integer,
+{
+    size: &'a Vector<Idx, N>,
+    values: NestedValues<'a, T>,
+}
+
+/// The values of the axes up to `sizes.len()`
+#[cfg(feature = "serde")]
+struct NestedValues<'a, T>
+{
+    values: &'a [T],
+    sizes: &'a [usize],
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T> Serialize for NestedValues<'a, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let Some((len, inner_sizes)) = self.sizes.split_last()
+        else
+        {
+            return self.values.serialize(serializer);
+        };
+        if inner_sizes.is_empty()
+        {
+            return self.values.serialize(serializer);
+        }
+        let chunk = inner_sizes.iter().product::<usize>();
+        let mut seq = serializer.serialize_seq(Some(*len))?;
+        for i in 0..*len
+        {
+            seq.serialize_element(&NestedValues {
+                values: &self.values[i * chunk..(i + 1) * chunk],
+                sizes: inner_sizes,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+/// Load a [`GridOf`] saved with the `nested` layout, see [`NestedGridRef`]
+#[cfg(feature = "serde")]
+struct NestedGrid<T, Idx, const N: usize>(GridOf<T, Idx, N>)
+where
+    Idx: Integer;
+
+#[cfg(feature = "serde")]
+impl<'de, T, Idx, const N: usize> Deserialize<'de> for NestedGrid<T, Idx, N>
+where
+    Idx: Integer + Deserialize<'de>,
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "Grid")]
+        struct Grid<T, Idx, const N: usize>
+        where
+            Idx: Integer,
+        {
+            size: Vector<Idx, N>,
+            values: NestedVec<T, N>,
+        }
+
+        let Grid::<T, Idx, N> { size, values } = Grid::deserialize(deserializer)?;
+        let mismatch = (0..N).any(|axis| values.sizes[axis].is_some_and(|len| size[axis] < Idx::ZERO || size[axis].to_usize() != len));
+        if mismatch
+        {
+            return Err(serde::de::Error::custom("the nested values don't match the size of the grid"));
+        }
+        GridOf::try_from_vec(size, values.values).map(NestedGrid).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The values, and the length of the sequences of each axis. `None` if an outer sequence is empty.
+#[cfg(feature = "serde")]
+struct NestedVec<T, const N: usize>
+{
+    values: Vec<T>,
+    sizes: [Option<usize>; N],
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> Deserialize<'de> for NestedVec<T, N>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut nested = NestedVec {
+            values: Vec::new(),
+            sizes: [None; N],
+        };
+        match N
+        {
+            0 => nested.values = Vec::deserialize(deserializer)?,
+            _ => NestedSeed {
+                axis: N - 1,
+                values: &mut nested.values,
+                sizes: &mut nested.sizes,
+            }
+            .deserialize(deserializer)?,
+        }
+        Ok(nested)
+    }
+}
+
+/// The sequence of the values of `axis`, and of the axes inside it
+#[cfg(feature = "serde")]
+struct NestedSeed<'a, T>
+{
+    axis: usize,
+    values: &'a mut Vec<T>,
+    sizes: &'a mut [Option<usize>],
+}
+
+#[cfg(feature = "serde")]
+impl<'a, 'de, T> DeserializeSeed<'de> for NestedSeed<'a, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, 'de, T> Visitor<'de> for NestedSeed<'a, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result { write!(formatter, "the nested values of the axis {}", self.axis) }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut len = 0;
+        loop
+        {
+            let next = match self.axis
+            {
+                0 => seq.next_element::<T>()?.map(|value| self.values.push(value)),
+                axis => seq.next_element_seed(NestedSeed {
+                    axis: axis - 1,
+                    values: &mut *self.values,
+                    sizes: &mut *self.sizes,
+                })?,
+            };
+            if next.is_none()
+            {
+                break;
+            }
+            len += 1;
+        }
+        match self.sizes[self.axis]
+        {
+            Some(expected) if expected != len => Err(serde::de::Error::custom(format!(
+                "the nested values of the axis {} have different lengths: {expected} and {len}",
+                self.axis
+            ))),
+            _ =>
+            {
+                self.sizes[self.axis] = Some(len);
+                Ok(())
+            }
+        }
+    }
+}
@@ -5,6 +5,8 @@ mod serde_impl;
 #[cfg(feature = "serde")]
 pub use serde_impl::*;
 
+mod encoding;
+
 mod typedef;
 pub use typedef::*;
 
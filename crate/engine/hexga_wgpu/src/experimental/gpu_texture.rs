@@ -21,7 +21,9 @@ impl LoadExtension for GpuTexture
 {
     fn load_custom_extensions() -> impl Iterator<Item = &'static extension> { Image::load_custom_extensions() }
 
-    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>) -> EncodeResult<Self>
+    fn load_custom_extension_args() -> impl Iterator<Item = &'static str> { Image::load_custom_extension_args() }
+
+    fn load_from_reader_with_custom_extension<R>(reader: R, extension: Option<&extension>, args: ExtensionArgs<'_>) -> EncodeResult<Self>
     where
         Self: Sized,
         R: std::io::Read,
@@ -30,7 +32,7 @@ impl LoadExtension for GpuTexture
         {
             return Err(EncodeError::custom("The Gpu was not initialized"));
         }
-        let img = Image::load_from_reader_with_custom_extension(reader, extension)?;
+        let img = Image::load_from_reader_with_custom_extension(reader, extension, args)?;
         Ok(Self::from(img))
     }
 }